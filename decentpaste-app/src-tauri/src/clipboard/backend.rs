@@ -0,0 +1,101 @@
+//! Clipboard change notifications for the monitor.
+//!
+//! A backend tells the monitor when the clipboard may have changed; the monitor
+//! then reads the text and compares hashes as before. Where the platform can
+//! announce changes (X11 XFixes, Wayland data-control) nothing is read until
+//! something is copied. Everywhere else, and whenever an event backend fails,
+//! the clipboard is polled at the configured interval.
+
+use std::time::Duration;
+
+use tokio::sync::mpsc;
+use tracing::{debug, info};
+
+use super::ClipboardSelection;
+
+/// A source of "the clipboard may have changed" notifications.
+pub trait ClipboardBackend: Send {
+    /// Short name for logs.
+    fn name(&self) -> &'static str;
+
+    /// Start watching and send `()` on `notify` for every change. Watching stops
+    /// once `notify` is closed; the backend drops its sender when it fails, which
+    /// closes the receiver. Returns an error if the backend isn't available here.
+    fn watch(self: Box<Self>, notify: mpsc::UnboundedSender<()>) -> Result<(), String>;
+}
+
+/// Notifies at a fixed interval, whether or not anything changed.
+pub struct PollingBackend {
+    pub interval: Duration,
+}
+
+impl ClipboardBackend for PollingBackend {
+    fn name(&self) -> &'static str {
+        "polling"
+    }
+
+    fn watch(self: Box<Self>, notify: mpsc::UnboundedSender<()>) -> Result<(), String> {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(self.interval);
+            loop {
+                interval.tick().await;
+                if notify.send(()).is_err() {
+                    break;
+                }
+            }
+        });
+        Ok(())
+    }
+}
+
+/// Watch `selection` with the first event backend that is available, falling
+/// back to polling every `poll_interval`. The first notification is sent right
+/// away so the current content is read once at startup.
+pub fn watch(
+    selection: ClipboardSelection,
+    poll_interval: Duration,
+) -> mpsc::UnboundedReceiver<()> {
+    for backend in event_backends(selection) {
+        let name = backend.name();
+        let (tx, rx) = mpsc::unbounded_channel();
+        let _ = tx.send(());
+        match backend.watch(tx) {
+            Ok(()) => {
+                info!("Watching the clipboard with the {} backend", name);
+                return rx;
+            }
+            Err(e) => debug!("{} clipboard backend unavailable: {}", name, e),
+        }
+    }
+    poll(poll_interval)
+}
+
+/// Poll the clipboard every `interval`.
+pub fn poll(interval: Duration) -> mpsc::UnboundedReceiver<()> {
+    let (tx, rx) = mpsc::unbounded_channel();
+    // Polling never fails to start
+    let _ = Box::new(PollingBackend { interval }).watch(tx);
+    debug!("Polling the clipboard every {:?}", interval);
+    rx
+}
+
+/// Event backends for `selection` in the current session, most specific first.
+fn event_backends(selection: ClipboardSelection) -> Vec<Box<dyn ClipboardBackend>> {
+    #[cfg(target_os = "linux")]
+    {
+        if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+            // XWayland doesn't see every Wayland copy, so XFixes isn't used here
+            vec![Box::new(super::linux::WaylandBackend { selection })]
+        } else if std::env::var_os("DISPLAY").is_some() {
+            vec![Box::new(super::linux::X11Backend { selection })]
+        } else {
+            Vec::new()
+        }
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = selection;
+        Vec::new()
+    }
+}
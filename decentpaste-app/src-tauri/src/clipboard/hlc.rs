@@ -0,0 +1,247 @@
+use std::sync::Mutex;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Remote clocks further ahead than this are not followed, so one device with a
+/// wrong clock can't drag everyone's HLC into the future.
+const MAX_REMOTE_DRIFT_MS: i64 = 2 * 60 * 1000;
+
+/// Hybrid logical clock timestamp: physical milliseconds plus a logical counter.
+///
+/// Orders clipboard events consistently across devices even when their wall
+/// clocks disagree. The default (zero) value marks entries from before HLCs.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+pub struct HlcTimestamp {
+    /// Milliseconds since the Unix epoch
+    pub wall_ms: i64,
+    /// Events within the same millisecond (or behind a faster clock)
+    pub counter: u32,
+}
+
+impl HlcTimestamp {
+    /// Approximate an HLC for an event that only has a wall-clock time.
+    pub fn from_physical(timestamp: DateTime<Utc>) -> Self {
+        Self {
+            wall_ms: timestamp.timestamp_millis(),
+            counter: 0,
+        }
+    }
+
+    pub fn is_unset(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// This device's hybrid logical clock.
+#[derive(Debug, Default)]
+pub struct HybridClock {
+    last: Mutex<HlcTimestamp>,
+}
+
+impl HybridClock {
+    /// Timestamp for a local event (a copy on this device).
+    pub fn now(&self) -> HlcTimestamp {
+        let physical = Utc::now().timestamp_millis();
+        let mut last = self.last.lock().unwrap_or_else(|e| e.into_inner());
+        *last = if physical > last.wall_ms {
+            HlcTimestamp {
+                wall_ms: physical,
+                counter: 0,
+            }
+        } else {
+            HlcTimestamp {
+                wall_ms: last.wall_ms,
+                counter: last.counter.saturating_add(1),
+            }
+        };
+        *last
+    }
+
+    /// Merge a timestamp received from another device, so later local events
+    /// order after it.
+    pub fn observe(&self, remote: HlcTimestamp) {
+        let physical = Utc::now().timestamp_millis();
+        if remote.wall_ms - physical > MAX_REMOTE_DRIFT_MS {
+            return;
+        }
+
+        let mut last = self.last.lock().unwrap_or_else(|e| e.into_inner());
+        let wall_ms = physical.max(last.wall_ms).max(remote.wall_ms);
+        let counter = if wall_ms == last.wall_ms && wall_ms == remote.wall_ms {
+            last.counter.max(remote.counter).saturating_add(1)
+        } else if wall_ms == last.wall_ms {
+            last.counter.saturating_add(1)
+        } else if wall_ms == remote.wall_ms {
+            remote.counter.saturating_add(1)
+        } else {
+            0
+        };
+        *last = HlcTimestamp { wall_ms, counter };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clipboard::ClipboardEntry;
+
+    /// A clock whose last timestamp is `last` (e.g. ahead of the wall clock).
+    fn clock_at(last: HlcTimestamp) -> HybridClock {
+        HybridClock {
+            last: Mutex::new(last),
+        }
+    }
+
+    fn now_ms() -> i64 {
+        Utc::now().timestamp_millis()
+    }
+
+    #[test]
+    fn test_now_is_monotonic() {
+        let clock = HybridClock::default();
+        let mut previous = clock.now();
+        for _ in 0..1000 {
+            let next = clock.now();
+            assert!(next > previous);
+            previous = next;
+        }
+    }
+
+    #[test]
+    fn test_now_within_one_millisecond_counts_up() {
+        // The wall clock can't catch up with a last timestamp a minute ahead
+        let wall_ms = now_ms() + 60_000;
+        let clock = clock_at(HlcTimestamp {
+            wall_ms,
+            counter: 3,
+        });
+
+        assert_eq!(
+            clock.now(),
+            HlcTimestamp {
+                wall_ms,
+                counter: 4
+            }
+        );
+        assert_eq!(
+            clock.now(),
+            HlcTimestamp {
+                wall_ms,
+                counter: 5
+            }
+        );
+    }
+
+    #[test]
+    fn test_observe_remote_ahead() {
+        let clock = HybridClock::default();
+        let remote = HlcTimestamp {
+            wall_ms: now_ms() + 60_000,
+            counter: 5,
+        };
+        clock.observe(remote);
+
+        let next = clock.now();
+        assert!(next > remote);
+        assert_eq!(next.wall_ms, remote.wall_ms);
+    }
+
+    #[test]
+    fn test_observe_remote_behind() {
+        let clock = HybridClock::default();
+        let before = clock.now();
+        let remote = HlcTimestamp {
+            wall_ms: before.wall_ms - 60_000,
+            counter: 9,
+        };
+        clock.observe(remote);
+
+        let next = clock.now();
+        assert!(next > before);
+        assert!(next > remote);
+    }
+
+    #[test]
+    fn test_observe_same_millisecond_takes_higher_counter() {
+        let wall_ms = now_ms() + 60_000;
+        let clock = clock_at(HlcTimestamp {
+            wall_ms,
+            counter: 3,
+        });
+        clock.observe(HlcTimestamp {
+            wall_ms,
+            counter: 7,
+        });
+
+        assert_eq!(
+            clock.now(),
+            HlcTimestamp {
+                wall_ms,
+                counter: 9
+            }
+        );
+    }
+
+    #[test]
+    fn test_observe_ignores_drift_beyond_limit() {
+        let clock = HybridClock::default();
+        let remote = HlcTimestamp {
+            wall_ms: now_ms() + MAX_REMOTE_DRIFT_MS + 60_000,
+            counter: 0,
+        };
+        clock.observe(remote);
+        assert!(clock.now() < remote);
+
+        // Just inside the limit is still followed
+        let remote = HlcTimestamp {
+            wall_ms: now_ms() + MAX_REMOTE_DRIFT_MS - 60_000,
+            counter: 0,
+        };
+        clock.observe(remote);
+        assert!(clock.now() > remote);
+    }
+
+    #[test]
+    fn test_order_key_falls_back_to_timestamp() {
+        let timestamp = Utc::now();
+        let legacy = ClipboardEntry::new_remote(
+            "old".into(),
+            "hash-old".into(),
+            timestamp,
+            HlcTimestamp::default(),
+            "device-a",
+            "A",
+        );
+        assert_eq!(
+            legacy.order_key(),
+            (HlcTimestamp::from_physical(timestamp), "device-a")
+        );
+
+        // A later HLC entry orders after it; an earlier one before it
+        let hlc = HlcTimestamp::from_physical(timestamp);
+        let newer = ClipboardEntry::new_remote(
+            "new".into(),
+            "hash-new".into(),
+            timestamp,
+            HlcTimestamp { counter: 1, ..hlc },
+            "device-a",
+            "A",
+        );
+        let older = ClipboardEntry::new_remote(
+            "older".into(),
+            "hash-older".into(),
+            timestamp,
+            HlcTimestamp {
+                wall_ms: hlc.wall_ms - 1,
+                counter: 0,
+            },
+            "device-b",
+            "B",
+        );
+        assert!(newer.order_key() > legacy.order_key());
+        assert!(older.order_key() < legacy.order_key());
+    }
+}
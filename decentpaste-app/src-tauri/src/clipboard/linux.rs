@@ -0,0 +1,609 @@
+//! Linux clipboard targets and change events.
+//!
+//! Password managers advertise extra targets next to the text to ask clipboard
+//! managers not to record it. The text API of the clipboard plugin can't see them,
+//! so the targets are read directly: with the data-control protocol on Wayland
+//! (wlroots/KDE compositors) and from the X server otherwise (including XWayland).
+//!
+//! The same two protocols announce clipboard changes, which `X11Backend` and
+//! `WaylandBackend` forward to the monitor instead of it polling. Both also
+//! watch the PRIMARY selection (select-to-copy), whose text is read and written
+//! with `read_primary`/`write_primary`.
+
+use std::io::Read;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use tokio::sync::mpsc::UnboundedSender;
+use tracing::debug;
+use x11rb::connection::Connection;
+use x11rb::protocol::xproto::{AtomEnum, ConnectionExt as _, CreateWindowAux, WindowClass};
+use x11rb::protocol::Event;
+use x11rb::{COPY_DEPTH_FROM_PARENT, CURRENT_TIME, NONE};
+
+use super::ClipboardSelection;
+
+/// KDE convention (also set by KeePassXC): the data is `secret` for passwords.
+const KDE_PASSWORD_MANAGER_HINT: &str = "x-kde-passwordManagerHint";
+
+/// Targets whose presence alone marks the content as concealed or transient
+/// (nspasteboard.org conventions and the Windows clipboard-history format).
+const CONCEALED_TARGETS: &[&str] = &[
+    "org.nspasteboard.ConcealedType",
+    "org.nspasteboard.TransientType",
+    "org.nspasteboard.AutoGeneratedType",
+    "application/x-nspasteboard-concealed-type",
+    "application/x-nspasteboard-transient-type",
+    "ExcludeClipboardContentFromMonitorProcessing",
+];
+
+/// How long the X11 selection owner has to answer a conversion request.
+const X11_CONVERT_TIMEOUT: Duration = Duration::from_millis(250);
+
+/// Kept open so text we put in PRIMARY is served until another app selects
+/// something (on X11 the selection owner has to answer every paste).
+static PRIMARY: Mutex<Option<arboard::Clipboard>> = Mutex::new(None);
+
+/// Where the targets were read from; the hint's data is read from the same place.
+#[derive(Clone, Copy)]
+enum Source {
+    Wayland,
+    X11,
+}
+
+/// The hint that marks the current content of `selection` as not to be recorded
+/// (e.g. `x-kde-passwordManagerHint: secret`), if any.
+///
+/// Blocking; call from `spawn_blocking`. Returns `None` if the targets can't be read.
+pub fn concealed_hint(selection: ClipboardSelection) -> Option<String> {
+    let (source, targets) = match read_targets(selection) {
+        Ok(found) => found,
+        Err(e) => {
+            debug!("Could not read {:?} targets: {}", selection, e);
+            return None;
+        }
+    };
+
+    find_concealed_hint(&targets, |target| read_target(source, selection, target))
+}
+
+/// The first of `targets` that marks the content as concealed. `read` returns a
+/// target's data, for hints whose value matters.
+fn find_concealed_hint(
+    targets: &[String],
+    read: impl Fn(&str) -> Result<String, String>,
+) -> Option<String> {
+    targets.iter().find_map(|target| {
+        if target == KDE_PASSWORD_MANAGER_HINT {
+            let value = read(target).ok()?;
+            (value.trim() == "secret").then(|| format!("{}: secret", target))
+        } else if CONCEALED_TARGETS.contains(&target.as_str()) {
+            Some(target.clone())
+        } else {
+            None
+        }
+    })
+}
+
+fn read_targets(selection: ClipboardSelection) -> Result<(Source, Vec<String>), String> {
+    if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+        match wayland_targets(selection) {
+            Ok(targets) => return Ok((Source::Wayland, targets)),
+            // Compositors without data-control (e.g. GNOME): try XWayland
+            Err(e) => debug!("Wayland clipboard targets unavailable: {}", e),
+        }
+    }
+    x11_targets(selection).map(|targets| (Source::X11, targets))
+}
+
+fn read_target(
+    source: Source,
+    selection: ClipboardSelection,
+    target: &str,
+) -> Result<String, String> {
+    match source {
+        Source::Wayland => wayland_read(selection, target),
+        Source::X11 => x11_read(selection, target),
+    }
+}
+
+fn wayland_clipboard_type(selection: ClipboardSelection) -> wl_clipboard_rs::paste::ClipboardType {
+    use wl_clipboard_rs::paste::ClipboardType;
+
+    match selection {
+        ClipboardSelection::Clipboard => ClipboardType::Regular,
+        ClipboardSelection::Primary => ClipboardType::Primary,
+    }
+}
+
+fn wayland_targets(selection: ClipboardSelection) -> Result<Vec<String>, String> {
+    use wl_clipboard_rs::paste::{get_mime_types, Seat};
+
+    get_mime_types(wayland_clipboard_type(selection), Seat::Unspecified)
+        .map(|types| types.into_iter().collect())
+        .map_err(|e| e.to_string())
+}
+
+fn wayland_read(selection: ClipboardSelection, target: &str) -> Result<String, String> {
+    use wl_clipboard_rs::paste::{get_contents, MimeType, Seat};
+
+    let (mut pipe, _) = get_contents(
+        wayland_clipboard_type(selection),
+        Seat::Unspecified,
+        MimeType::Specific(target),
+    )
+    .map_err(|e| e.to_string())?;
+    let mut value = String::new();
+    pipe.read_to_string(&mut value).map_err(|e| e.to_string())?;
+    Ok(value)
+}
+
+/// Text in the PRIMARY selection. Blocking.
+pub fn read_primary() -> Result<String, String> {
+    use arboard::{GetExtLinux, LinuxClipboardKind};
+
+    with_primary(|clipboard| {
+        clipboard
+            .get()
+            .clipboard(LinuxClipboardKind::Primary)
+            .text()
+    })
+}
+
+/// Put `text` in the PRIMARY selection. Blocking.
+pub fn write_primary(text: &str) -> Result<(), String> {
+    use arboard::{LinuxClipboardKind, SetExtLinux};
+
+    with_primary(|clipboard| {
+        clipboard
+            .set()
+            .clipboard(LinuxClipboardKind::Primary)
+            .text(text)
+    })
+}
+
+fn with_primary<T>(
+    f: impl FnOnce(&mut arboard::Clipboard) -> Result<T, arboard::Error>,
+) -> Result<T, String> {
+    let mut primary = PRIMARY.lock().map_err(|e| e.to_string())?;
+    if primary.is_none() {
+        *primary = Some(arboard::Clipboard::new().map_err(|e| e.to_string())?);
+    }
+    let clipboard = primary.as_mut().ok_or("PRIMARY selection unavailable")?;
+    f(clipboard).map_err(|e| e.to_string())
+}
+
+fn x11_targets(kind: ClipboardSelection) -> Result<Vec<String>, String> {
+    let selection = X11Selection::open(kind)?;
+    let targets = selection.atom("TARGETS")?;
+    let data = selection.convert(targets)?;
+
+    let mut names = Vec::new();
+    for atom in data.chunks_exact(4) {
+        let atom = u32::from_ne_bytes([atom[0], atom[1], atom[2], atom[3]]);
+        let reply = selection
+            .conn
+            .get_atom_name(atom)
+            .map_err(|e| e.to_string())?
+            .reply()
+            .map_err(|e| e.to_string())?;
+        names.push(String::from_utf8_lossy(&reply.name).into_owned());
+    }
+    Ok(names)
+}
+
+fn x11_read(kind: ClipboardSelection, target: &str) -> Result<String, String> {
+    let selection = X11Selection::open(kind)?;
+    let target = selection.atom(target)?;
+    let data = selection.convert(target)?;
+    Ok(String::from_utf8_lossy(&data).into_owned())
+}
+
+/// A connection and hidden window for reading the CLIPBOARD or PRIMARY selection.
+struct X11Selection {
+    conn: x11rb::rust_connection::RustConnection,
+    window: u32,
+    selection: u32,
+    property: u32,
+}
+
+impl X11Selection {
+    fn open(kind: ClipboardSelection) -> Result<Self, String> {
+        let (conn, screen_num) = x11rb::connect(None).map_err(|e| e.to_string())?;
+        let screen = &conn.setup().roots[screen_num];
+        let window = conn.generate_id().map_err(|e| e.to_string())?;
+        conn.create_window(
+            COPY_DEPTH_FROM_PARENT,
+            window,
+            screen.root,
+            0,
+            0,
+            1,
+            1,
+            0,
+            WindowClass::INPUT_OUTPUT,
+            screen.root_visual,
+            &CreateWindowAux::new(),
+        )
+        .map_err(|e| e.to_string())?;
+
+        let mut selection = Self {
+            conn,
+            window,
+            selection: NONE,
+            property: NONE,
+        };
+        selection.selection = selection.atom(match kind {
+            ClipboardSelection::Clipboard => "CLIPBOARD",
+            ClipboardSelection::Primary => "PRIMARY",
+        })?;
+        selection.property = selection.atom("DECENTPASTE_SELECTION")?;
+        Ok(selection)
+    }
+
+    fn atom(&self, name: &str) -> Result<u32, String> {
+        Ok(self
+            .conn
+            .intern_atom(false, name.as_bytes())
+            .map_err(|e| e.to_string())?
+            .reply()
+            .map_err(|e| e.to_string())?
+            .atom)
+    }
+
+    /// Ask the selection owner to convert the selection to `target` and return the data.
+    fn convert(&self, target: u32) -> Result<Vec<u8>, String> {
+        self.conn
+            .convert_selection(
+                self.window,
+                self.selection,
+                target,
+                self.property,
+                CURRENT_TIME,
+            )
+            .map_err(|e| e.to_string())?;
+        self.conn.flush().map_err(|e| e.to_string())?;
+
+        let deadline = Instant::now() + X11_CONVERT_TIMEOUT;
+        loop {
+            match self.conn.poll_for_event().map_err(|e| e.to_string())? {
+                Some(Event::SelectionNotify(event)) if event.requestor == self.window => {
+                    if event.property == NONE {
+                        return Err("Selection owner refused the conversion".into());
+                    }
+                    let reply = self
+                        .conn
+                        .get_property(
+                            true,
+                            self.window,
+                            self.property,
+                            AtomEnum::ANY,
+                            0,
+                            u32::MAX / 4,
+                        )
+                        .map_err(|e| e.to_string())?
+                        .reply()
+                        .map_err(|e| e.to_string())?;
+                    return Ok(reply.value);
+                }
+                Some(_) => {}
+                None if Instant::now() >= deadline => {
+                    return Err("Selection owner didn't answer".into());
+                }
+                None => std::thread::sleep(Duration::from_millis(5)),
+            }
+        }
+    }
+}
+
+/// Change events from the X server via XFixes (X11 sessions).
+pub struct X11Backend {
+    pub selection: ClipboardSelection,
+}
+
+impl super::backend::ClipboardBackend for X11Backend {
+    fn name(&self) -> &'static str {
+        "X11 XFixes"
+    }
+
+    fn watch(self: Box<Self>, notify: UnboundedSender<()>) -> Result<(), String> {
+        use x11rb::protocol::xfixes::{ConnectionExt as _, SelectionEventMask};
+
+        let selection = X11Selection::open(self.selection)?;
+        selection
+            .conn
+            .xfixes_query_version(5, 0)
+            .map_err(|e| e.to_string())?
+            .reply()
+            .map_err(|e| e.to_string())?;
+        selection
+            .conn
+            .xfixes_select_selection_input(
+                selection.window,
+                selection.selection,
+                SelectionEventMask::SET_SELECTION_OWNER
+                    | SelectionEventMask::SELECTION_WINDOW_DESTROY
+                    | SelectionEventMask::SELECTION_CLIENT_CLOSE,
+            )
+            .map_err(|e| e.to_string())?;
+        selection.conn.flush().map_err(|e| e.to_string())?;
+
+        std::thread::spawn(move || loop {
+            match selection.conn.wait_for_event() {
+                Ok(Event::XfixesSelectionNotify(_)) => {
+                    if notify.send(()).is_err() {
+                        break;
+                    }
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    debug!("X11 clipboard events stopped: {}", e);
+                    break;
+                }
+            }
+        });
+        Ok(())
+    }
+}
+
+/// Change events from the compositor via wlr data-control (wlroots and KDE
+/// Wayland sessions). GNOME doesn't implement the protocol.
+pub struct WaylandBackend {
+    pub selection: ClipboardSelection,
+}
+
+impl super::backend::ClipboardBackend for WaylandBackend {
+    fn name(&self) -> &'static str {
+        "Wayland data-control"
+    }
+
+    fn watch(self: Box<Self>, notify: UnboundedSender<()>) -> Result<(), String> {
+        use wayland_client::globals::registry_queue_init;
+        use wayland_client::protocol::wl_seat::WlSeat;
+        use wayland_client::{Connection, Proxy};
+        use wayland_protocols_wlr::data_control::v1::client::zwlr_data_control_manager_v1::ZwlrDataControlManagerV1;
+
+        let conn = Connection::connect_to_env().map_err(|e| e.to_string())?;
+        let (globals, mut queue) =
+            registry_queue_init::<WaylandWatch>(&conn).map_err(|e| e.to_string())?;
+        let qh = queue.handle();
+        let manager: ZwlrDataControlManagerV1 =
+            globals.bind(&qh, 1..=2, ()).map_err(|e| e.to_string())?;
+        if self.selection == ClipboardSelection::Primary && manager.version() < 2 {
+            return Err("data-control v1 has no PRIMARY selection events".into());
+        }
+        let seat: WlSeat = globals.bind(&qh, 1..=1, ()).map_err(|e| e.to_string())?;
+        manager.get_data_device(&seat, &qh, ());
+
+        let mut watch = WaylandWatch {
+            selection: self.selection,
+            notify,
+            closed: false,
+        };
+        std::thread::spawn(move || {
+            // Keep the connection open for as long as the queue is dispatched
+            let _conn = conn;
+            while !watch.closed {
+                if let Err(e) = queue.blocking_dispatch(&mut watch) {
+                    debug!("Wayland clipboard events stopped: {}", e);
+                    break;
+                }
+            }
+        });
+        Ok(())
+    }
+}
+
+/// Dispatch state for `WaylandBackend`.
+struct WaylandWatch {
+    selection: ClipboardSelection,
+    notify: UnboundedSender<()>,
+    closed: bool,
+}
+
+mod wayland_dispatch {
+    use wayland_client::globals::GlobalListContents;
+    use wayland_client::protocol::{wl_registry, wl_seat};
+    use wayland_client::{event_created_child, Connection, Dispatch, QueueHandle};
+    use wayland_protocols_wlr::data_control::v1::client::{
+        zwlr_data_control_device_v1::{self, ZwlrDataControlDeviceV1},
+        zwlr_data_control_manager_v1::ZwlrDataControlManagerV1,
+        zwlr_data_control_offer_v1::ZwlrDataControlOfferV1,
+    };
+
+    use super::{ClipboardSelection, WaylandWatch};
+
+    impl Dispatch<ZwlrDataControlDeviceV1, ()> for WaylandWatch {
+        fn event(
+            state: &mut Self,
+            device: &ZwlrDataControlDeviceV1,
+            event: zwlr_data_control_device_v1::Event,
+            _: &(),
+            _: &Connection,
+            _: &QueueHandle<Self>,
+        ) {
+            // The text itself is read elsewhere (clipboard plugin / `read_primary`)
+            let (selection, offer) = match event {
+                zwlr_data_control_device_v1::Event::Selection { id } => {
+                    (ClipboardSelection::Clipboard, id)
+                }
+                zwlr_data_control_device_v1::Event::PrimarySelection { id } => {
+                    (ClipboardSelection::Primary, id)
+                }
+                zwlr_data_control_device_v1::Event::Finished => {
+                    device.destroy();
+                    state.closed = true;
+                    return;
+                }
+                _ => return,
+            };
+            if let Some(offer) = offer {
+                offer.destroy();
+            }
+            if selection == state.selection && state.notify.send(()).is_err() {
+                state.closed = true;
+            }
+        }
+
+        event_created_child!(WaylandWatch, ZwlrDataControlDeviceV1, [
+            zwlr_data_control_device_v1::EVT_DATA_OFFER_OPCODE => (ZwlrDataControlOfferV1, ()),
+        ]);
+    }
+
+    impl Dispatch<wl_registry::WlRegistry, GlobalListContents> for WaylandWatch {
+        fn event(
+            _: &mut Self,
+            _: &wl_registry::WlRegistry,
+            _: wl_registry::Event,
+            _: &GlobalListContents,
+            _: &Connection,
+            _: &QueueHandle<Self>,
+        ) {
+        }
+    }
+
+    impl Dispatch<wl_seat::WlSeat, ()> for WaylandWatch {
+        fn event(
+            _: &mut Self,
+            _: &wl_seat::WlSeat,
+            _: wl_seat::Event,
+            _: &(),
+            _: &Connection,
+            _: &QueueHandle<Self>,
+        ) {
+        }
+    }
+
+    impl Dispatch<ZwlrDataControlManagerV1, ()> for WaylandWatch {
+        fn event(
+            _: &mut Self,
+            _: &ZwlrDataControlManagerV1,
+            _: <ZwlrDataControlManagerV1 as wayland_client::Proxy>::Event,
+            _: &(),
+            _: &Connection,
+            _: &QueueHandle<Self>,
+        ) {
+        }
+    }
+
+    impl Dispatch<ZwlrDataControlOfferV1, ()> for WaylandWatch {
+        fn event(
+            _: &mut Self,
+            _: &ZwlrDataControlOfferV1,
+            _: <ZwlrDataControlOfferV1 as wayland_client::Proxy>::Event,
+            _: &(),
+            _: &Connection,
+            _: &QueueHandle<Self>,
+        ) {
+        }
+    }
+}
+
+// The ignored tests need a display server: run them with e.g.
+// `xvfb-run cargo test -- --ignored x11_`, or inside a headless wlroots compositor
+// (`sway --headless`) for the Wayland ones.
+#[cfg(test)]
+mod tests {
+    use super::super::backend::ClipboardBackend;
+    use super::*;
+    use tokio::sync::mpsc;
+    use tokio::time::timeout;
+
+    const EVENT_TIMEOUT: Duration = Duration::from_secs(2);
+
+    fn targets(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn concealed_target_is_found() {
+        let targets = targets(&["UTF8_STRING", "org.nspasteboard.ConcealedType"]);
+        let hint = find_concealed_hint(&targets, |_| Err("not read".into()));
+        assert_eq!(hint.as_deref(), Some("org.nspasteboard.ConcealedType"));
+    }
+
+    #[test]
+    fn password_manager_hint_needs_secret() {
+        let targets = targets(&["text/plain", KDE_PASSWORD_MANAGER_HINT]);
+        let hint = find_concealed_hint(&targets, |_| Ok("secret\n".into()));
+        assert_eq!(hint.as_deref(), Some("x-kde-passwordManagerHint: secret"));
+        assert_eq!(find_concealed_hint(&targets, |_| Ok("public".into())), None);
+    }
+
+    #[test]
+    fn plain_text_is_not_concealed() {
+        let targets = targets(&["TARGETS", "UTF8_STRING", "text/plain"]);
+        assert_eq!(find_concealed_hint(&targets, |_| Ok("secret".into())), None);
+    }
+
+    #[test]
+    #[ignore = "needs a Wayland compositor with data-control v2 (sway --headless)"]
+    fn concealed_primary_selection_is_detected() {
+        use wl_clipboard_rs::copy::{ClipboardType, MimeSource, MimeType, Options, Source};
+
+        let mut options = Options::new();
+        options.clipboard(ClipboardType::Primary);
+        options
+            .copy_multi(vec![
+                MimeSource {
+                    source: Source::Bytes(b"hunter2".to_vec().into()),
+                    mime_type: MimeType::Text,
+                },
+                MimeSource {
+                    source: Source::Bytes(Vec::new().into()),
+                    mime_type: MimeType::Specific("org.nspasteboard.ConcealedType".into()),
+                },
+            ])
+            .unwrap();
+
+        assert_eq!(
+            concealed_hint(ClipboardSelection::Primary).as_deref(),
+            Some("org.nspasteboard.ConcealedType")
+        );
+    }
+
+    #[tokio::test]
+    #[ignore = "needs an X server (xvfb-run)"]
+    async fn x11_backend_notifies_on_new_owner() {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let backend = X11Backend {
+            selection: ClipboardSelection::Clipboard,
+        };
+        Box::new(backend).watch(tx).unwrap();
+
+        let owner = X11Selection::open(ClipboardSelection::Clipboard).unwrap();
+        owner
+            .conn
+            .set_selection_owner(owner.window, owner.selection, CURRENT_TIME)
+            .unwrap();
+        owner.conn.flush().unwrap();
+
+        let event = timeout(EVENT_TIMEOUT, rx.recv()).await;
+        assert_eq!(event, Ok(Some(())));
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a Wayland compositor with data-control (sway --headless)"]
+    async fn wayland_backend_notifies_on_copy() {
+        use wl_clipboard_rs::copy::{MimeType, Options, Source};
+
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let backend = WaylandBackend {
+            selection: ClipboardSelection::Clipboard,
+        };
+        Box::new(backend).watch(tx).unwrap();
+        // The current selection is announced as soon as the device is created
+        let initial = timeout(EVENT_TIMEOUT, rx.recv()).await;
+        assert_eq!(initial, Ok(Some(())));
+
+        Options::new()
+            .copy(
+                Source::Bytes(b"decentpaste".to_vec().into()),
+                MimeType::Text,
+            )
+            .unwrap();
+
+        let event = timeout(EVENT_TIMEOUT, rx.recv()).await;
+        assert_eq!(event, Ok(Some(())));
+    }
+}
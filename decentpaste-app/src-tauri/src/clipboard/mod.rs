@@ -1,5 +1,14 @@
+pub mod backend;
+pub mod filter;
+pub mod hlc;
+#[cfg(target_os = "linux")]
+pub mod linux;
 pub mod monitor;
+pub mod reconcile;
 pub mod sync;
 
-pub use monitor::{ClipboardChange, ClipboardMonitor};
-pub use sync::ClipboardEntry;
+pub use filter::{SensitiveAction, SensitiveFilter, SensitiveRule};
+pub use hlc::{HlcTimestamp, HybridClock};
+pub use monitor::{ClipboardChange, ClipboardMonitor, ClipboardSelection};
+pub use reconcile::{HistoryItemRef, HistoryTombstone};
+pub use sync::{ClipboardEntry, DeliveryStatus};
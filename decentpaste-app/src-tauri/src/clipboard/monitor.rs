@@ -1,3 +1,4 @@
+use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use std::time::Duration;
 use tauri::AppHandle;
@@ -5,15 +6,36 @@ use tauri_plugin_clipboard_manager::ClipboardExt;
 use tokio::sync::{mpsc, RwLock};
 use tracing::{debug, warn};
 
+/// Bounds for `primary_debounce_ms`.
+pub const MIN_PRIMARY_DEBOUNCE_MS: u64 = 100;
+pub const MAX_PRIMARY_DEBOUNCE_MS: u64 = 10_000;
+
+/// The system clipboard, or the X11/Wayland PRIMARY selection (select-to-copy,
+/// middle-click paste).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ClipboardSelection {
+    #[default]
+    Clipboard,
+    Primary,
+}
+
 #[derive(Debug, Clone)]
 pub struct ClipboardChange {
     pub content: String,
     pub content_hash: String,
     pub is_local: bool,
+    /// Where a local change was copied from
+    pub selection: ClipboardSelection,
 }
 
 pub struct ClipboardMonitor {
     last_hash: Arc<RwLock<Option<String>>>,
+    /// Clipboard text replaced by the last remote item (memory only), for undo
+    overwritten: Arc<RwLock<Option<String>>>,
+    /// Like `last_hash`, for the PRIMARY selection
+    #[cfg(target_os = "linux")]
+    last_primary_hash: Arc<RwLock<Option<String>>>,
     poll_interval: Duration,
     running: Arc<RwLock<bool>>,
 }
@@ -22,6 +44,9 @@ impl ClipboardMonitor {
     pub fn new(poll_interval_ms: u64) -> Self {
         Self {
             last_hash: Arc::new(RwLock::new(None)),
+            overwritten: Arc::new(RwLock::new(None)),
+            #[cfg(target_os = "linux")]
+            last_primary_hash: Arc::new(RwLock::new(None)),
             poll_interval: Duration::from_millis(poll_interval_ms),
             running: Arc::new(RwLock::new(false)),
         }
@@ -49,7 +74,17 @@ impl ClipboardMonitor {
         let running = self.running.clone();
 
         tokio::spawn(async move {
+            let mut changes = super::backend::watch(ClipboardSelection::Clipboard, poll_interval);
             loop {
+                // Wait until the clipboard may have changed
+                if changes.recv().await.is_none() {
+                    warn!("Clipboard change events stopped, falling back to polling");
+                    changes = super::backend::poll(poll_interval);
+                    continue;
+                }
+                // Several events may arrive for one copy; read the clipboard once
+                while changes.try_recv().is_ok() {}
+
                 // Check if we should stop
                 if !*running.read().await {
                     debug!("Clipboard monitor stopping");
@@ -70,11 +105,33 @@ impl ClipboardMonitor {
                                 if last.as_ref() != Some(&hash) {
                                     debug!("Clipboard content changed, hash: {}", &hash[..8]);
                                     *last = Some(hash.clone());
+                                    drop(last);
+
+                                    // Password managers mark secrets as not to be recorded
+                                    #[cfg(target_os = "linux")]
+                                    {
+                                        let hint = tokio::task::spawn_blocking(|| {
+                                            super::linux::concealed_hint(
+                                                ClipboardSelection::Clipboard,
+                                            )
+                                        })
+                                        .await
+                                        .ok()
+                                        .flatten();
+                                        if let Some(hint) = hint {
+                                            debug!(
+                                                "Skipping concealed clipboard content ({})",
+                                                hint
+                                            );
+                                            continue;
+                                        }
+                                    }
 
                                     let change = ClipboardChange {
                                         content: text,
                                         content_hash: hash,
                                         is_local: true,
+                                        selection: ClipboardSelection::Clipboard,
                                     };
 
                                     if tx.send(change).await.is_err() {
@@ -102,14 +159,111 @@ impl ClipboardMonitor {
                     // Users can manually share clipboard content via the UI
                     let _ = (&app_handle, &last_hash, &tx); // Suppress unused warnings
                 }
-
-                tokio::time::sleep(poll_interval).await;
             }
 
             *running.write().await = false;
         });
     }
 
+    /// Watch the PRIMARY selection as a second source of local changes, while
+    /// `primary_selection_enabled` is on. Selections change on every drag, so one
+    /// is only sent after it stayed the same for `primary_debounce_ms`. Text that
+    /// was also copied to the clipboard is left to the clipboard source.
+    #[cfg(target_os = "linux")]
+    pub fn start_primary(&self, app_handle: AppHandle, tx: mpsc::Sender<ClipboardChange>) {
+        use tauri::Manager;
+
+        let last_hash = self.last_hash.clone();
+        let last_primary_hash = self.last_primary_hash.clone();
+        let poll_interval = self.poll_interval;
+        let running = self.running.clone();
+
+        tokio::spawn(async move {
+            let state = app_handle.state::<crate::state::AppState>();
+            let mut changes = super::backend::watch(ClipboardSelection::Primary, poll_interval);
+            'changes: loop {
+                if changes.recv().await.is_none() {
+                    warn!("PRIMARY selection events stopped, falling back to polling");
+                    changes = super::backend::poll(poll_interval);
+                    continue;
+                }
+                while changes.try_recv().is_ok() {}
+
+                if !*running.read().await {
+                    break;
+                }
+                let (enabled, debounce) = {
+                    let settings = state.settings.read().await;
+                    (
+                        settings.primary_selection_enabled,
+                        Duration::from_millis(settings.primary_debounce_ms),
+                    )
+                };
+                if !enabled {
+                    continue;
+                }
+
+                let mut text = match read_primary_text().await {
+                    Some(text) => text,
+                    None => continue,
+                };
+                if last_primary_hash.read().await.as_deref()
+                    == Some(crate::security::hash_content(&text).as_str())
+                {
+                    continue;
+                }
+
+                // Wait until the selection stops changing
+                loop {
+                    tokio::time::sleep(debounce).await;
+                    match read_primary_text().await {
+                        Some(again) if again == text => break,
+                        Some(again) => text = again,
+                        None => continue 'changes,
+                    }
+                }
+                // Events from the drag itself are already covered
+                while changes.try_recv().is_ok() {}
+
+                // Password managers can mark selections as not to be recorded too
+                let hint = tokio::task::spawn_blocking(|| {
+                    super::linux::concealed_hint(ClipboardSelection::Primary)
+                })
+                .await
+                .ok()
+                .flatten();
+                if let Some(hint) = hint {
+                    debug!("Skipping concealed PRIMARY selection ({})", hint);
+                    continue;
+                }
+
+                let hash = crate::security::hash_content(&text);
+                {
+                    let mut last_primary = last_primary_hash.write().await;
+                    if last_primary.as_ref() == Some(&hash) {
+                        continue;
+                    }
+                    *last_primary = Some(hash.clone());
+                }
+                if last_hash.read().await.as_ref() == Some(&hash) {
+                    continue;
+                }
+
+                debug!("PRIMARY selection changed, hash: {}", &hash[..8]);
+                let change = ClipboardChange {
+                    content: text,
+                    content_hash: hash,
+                    is_local: true,
+                    selection: ClipboardSelection::Primary,
+                };
+                if tx.send(change).await.is_err() {
+                    tracing::error!("Failed to send PRIMARY selection change - receiver dropped");
+                    break;
+                }
+            }
+        });
+    }
+
     #[allow(dead_code)]
     pub async fn stop(&self) {
         let mut running = self.running.write().await;
@@ -123,6 +277,82 @@ impl ClipboardMonitor {
         *last = Some(hash);
     }
 
+    /// Write content received from another device to the clipboard, keeping the
+    /// text it replaces so `undo_remote_apply` can put it back. Echo-safe like
+    /// `set_last_hash`.
+    pub async fn apply_remote(&self, app_handle: &AppHandle, content: &str) -> Result<(), String> {
+        let previous = app_handle
+            .clipboard()
+            .read_text()
+            .ok()
+            .filter(|text| !text.is_empty() && text != content);
+
+        self.set_last_hash(crate::security::hash_content(content))
+            .await;
+        set_clipboard_content(app_handle, content)?;
+        if previous.is_some() {
+            *self.overwritten.write().await = previous;
+        }
+        Ok(())
+    }
+
+    /// Write content received from another device to `target`. PRIMARY is only
+    /// written on Linux, elsewhere everything goes to the clipboard (undoable).
+    pub async fn apply_remote_to(
+        &self,
+        app_handle: &AppHandle,
+        content: &str,
+        target: ClipboardSelection,
+    ) -> Result<(), String> {
+        #[cfg(target_os = "linux")]
+        if target == ClipboardSelection::Primary {
+            *self.last_primary_hash.write().await = Some(crate::security::hash_content(content));
+            let content = content.to_string();
+            return tokio::task::spawn_blocking(move || super::linux::write_primary(&content))
+                .await
+                .map_err(|e| e.to_string())?;
+        }
+        #[cfg(not(target_os = "linux"))]
+        let _ = target;
+
+        self.apply_remote(app_handle, content).await
+    }
+
+    /// Restore the clipboard text replaced by the last `apply_remote`, without it
+    /// being broadcast as a local copy. Returns `false` if there is nothing to undo.
+    pub async fn undo_remote_apply(&self, app_handle: &AppHandle) -> Result<bool, String> {
+        let previous = match self.overwritten.write().await.take() {
+            Some(previous) => previous,
+            None => return Ok(false),
+        };
+        self.set_last_hash(crate::security::hash_content(&previous))
+            .await;
+        set_clipboard_content(app_handle, &previous)?;
+        Ok(true)
+    }
+
+    /// Clear the clipboard if it still holds the content with `hash`. Returns
+    /// whether it was cleared.
+    pub async fn clear_if_current(
+        &self,
+        app_handle: &AppHandle,
+        hash: &str,
+    ) -> Result<bool, String> {
+        let current = app_handle.clipboard().read_text().ok();
+        match current {
+            Some(text) if crate::security::hash_content(&text) == hash => {
+                set_clipboard_content(app_handle, "")?;
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    /// Forget the text kept for `undo_remote_apply` (e.g. when the vault locks).
+    pub async fn clear_overwritten(&self) {
+        *self.overwritten.write().await = None;
+    }
+
     #[allow(dead_code)]
     pub async fn get_last_hash(&self) -> Option<String> {
         let last = self.last_hash.read().await;
@@ -130,6 +360,22 @@ impl ClipboardMonitor {
     }
 }
 
+/// Text in the PRIMARY selection, if there is any and it isn't too large to sync.
+#[cfg(target_os = "linux")]
+async fn read_primary_text() -> Option<String> {
+    const MAX_CLIPBOARD_SIZE: usize = 1024 * 1024; // 1MB limit
+    match tokio::task::spawn_blocking(super::linux::read_primary).await {
+        Ok(Ok(text)) if !text.is_empty() && text.len() <= MAX_CLIPBOARD_SIZE => Some(text),
+        Ok(Ok(_)) => None,
+        Ok(Err(e)) => {
+            // Nothing selected, or the selection isn't text
+            debug!("Could not read PRIMARY selection: {}", e);
+            None
+        }
+        Err(_) => None,
+    }
+}
+
 pub fn set_clipboard_content(app_handle: &AppHandle, content: &str) -> Result<(), String> {
     app_handle
         .clipboard()
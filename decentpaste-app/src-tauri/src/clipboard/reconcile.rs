@@ -0,0 +1,391 @@
+//! History reconciliation between paired devices.
+//!
+//! Each side splits its history into buckets by the first hex digit of the
+//! content hash and compares one digest per bucket. Only mismatched buckets are
+//! listed item by item, so two devices with the same history exchange a single
+//! small message.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use super::hlc::HlcTimestamp;
+use super::sync::ClipboardEntry;
+
+/// Number of digest buckets (one per leading hex digit of the content hash).
+pub const HISTORY_BUCKETS: usize = 16;
+
+/// Maximum number of tombstones kept; the oldest are dropped first.
+pub const MAX_HISTORY_TOMBSTONES: usize = 1000;
+
+/// Records that an entry was deleted, so the deletion reaches other devices
+/// instead of the entry being synced back.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryTombstone {
+    pub content_hash: String,
+    /// Entries with this hash and an HLC up to this one are deleted
+    pub deleted_at: HlcTimestamp,
+}
+
+/// One history item (live entry or tombstone) as listed in a summary.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryItemRef {
+    pub hash: String,
+    pub hlc: HlcTimestamp,
+    pub deleted: bool,
+}
+
+/// Bucket of a content hash.
+pub fn bucket_of(hash: &str) -> usize {
+    hash.chars()
+        .next()
+        .and_then(|c| c.to_digit(16))
+        .map_or(0, |d| d as usize)
+}
+
+/// Whether `entry` is covered by one of `tombstones`.
+pub fn is_deleted(entry: &ClipboardEntry, tombstones: &[HistoryTombstone]) -> bool {
+    tombstones
+        .iter()
+        .any(|t| t.content_hash == entry.content_hash && entry.order_key().0 <= t.deleted_at)
+}
+
+/// The items taking part in reconciliation: the newest `limit` entries plus
+/// all tombstones.
+pub fn history_items(
+    history: &[ClipboardEntry],
+    tombstones: &[HistoryTombstone],
+    limit: usize,
+) -> Vec<HistoryItemRef> {
+    let entries = history.iter().take(limit).map(|e| HistoryItemRef {
+        hash: e.content_hash.clone(),
+        hlc: e.order_key().0,
+        deleted: false,
+    });
+    let deleted = tombstones.iter().map(|t| HistoryItemRef {
+        hash: t.content_hash.clone(),
+        hlc: t.deleted_at,
+        deleted: true,
+    });
+    entries.chain(deleted).collect()
+}
+
+/// Digest of each bucket, over the sorted hashes of its items.
+///
+/// HLCs are left out: the same content copied on two devices has different
+/// HLCs but is the same history item.
+pub fn bucket_digests(items: &[HistoryItemRef]) -> Vec<String> {
+    let mut buckets: Vec<Vec<String>> = vec![Vec::new(); HISTORY_BUCKETS];
+    for item in items {
+        let key = if item.deleted {
+            format!("-{}", item.hash)
+        } else {
+            item.hash.clone()
+        };
+        buckets[bucket_of(&item.hash)].push(key);
+    }
+
+    buckets
+        .into_iter()
+        .map(|mut keys| {
+            keys.sort();
+            let mut hasher = Sha256::new();
+            for key in keys {
+                hasher.update(key.as_bytes());
+                hasher.update(b"\n");
+            }
+            hex::encode(hasher.finalize())
+        })
+        .collect()
+}
+
+/// Buckets whose digest differs from the peer's (a missing digest counts as different).
+pub fn mismatched_buckets(ours: &[String], theirs: &[String]) -> Vec<usize> {
+    (0..ours.len())
+        .filter(|&i| theirs.get(i) != Some(&ours[i]))
+        .collect()
+}
+
+/// Outcome of comparing our items with a peer's `Summary`.
+#[derive(Debug, Default)]
+pub struct SummaryDiff {
+    /// Entries the peer has that we neither have nor deleted later
+    pub fetch: Vec<String>,
+    /// Entries we have that the peer neither has nor deleted later
+    pub push: Vec<String>,
+    /// Our deletions the peer doesn't know about yet
+    pub push_tombstones: Vec<HistoryTombstone>,
+    /// The peer's deletions, for us to merge
+    pub their_tombstones: Vec<HistoryTombstone>,
+}
+
+/// Compare our items in `buckets` with the peer's items of the same buckets.
+///
+/// A tombstone beats an entry of the same hash with an HLC up to its own; a newer
+/// entry survives it (the content was copied again after the deletion).
+pub fn diff_summary(
+    ours: &[HistoryItemRef],
+    buckets: &[usize],
+    theirs: &[HistoryItemRef],
+) -> SummaryDiff {
+    let ours: Vec<&HistoryItemRef> = ours
+        .iter()
+        .filter(|item| buckets.contains(&bucket_of(&item.hash)))
+        .collect();
+    let tombstone = |item: &HistoryItemRef| HistoryTombstone {
+        content_hash: item.hash.clone(),
+        deleted_at: item.hlc,
+    };
+
+    let fetch = theirs
+        .iter()
+        .filter(|t| !t.deleted)
+        .filter(|t| {
+            !ours
+                .iter()
+                .any(|o| o.hash == t.hash && (!o.deleted || o.hlc >= t.hlc))
+        })
+        .map(|t| t.hash.clone())
+        .collect();
+    let push = ours
+        .iter()
+        .filter(|o| !o.deleted)
+        .filter(|o| {
+            !theirs
+                .iter()
+                .any(|t| t.hash == o.hash && (!t.deleted || t.hlc >= o.hlc))
+        })
+        .map(|o| o.hash.clone())
+        .collect();
+    let push_tombstones = ours
+        .iter()
+        .filter(|o| o.deleted)
+        .filter(|o| {
+            !theirs
+                .iter()
+                .any(|t| t.deleted && t.hash == o.hash && t.hlc >= o.hlc)
+        })
+        .map(|o| tombstone(o))
+        .collect();
+    let their_tombstones = theirs.iter().filter(|t| t.deleted).map(tombstone).collect();
+
+    SummaryDiff {
+        fetch,
+        push,
+        push_tombstones,
+        their_tombstones,
+    }
+}
+
+/// Add `incoming` tombstones to `tombstones`, keeping the latest deletion per
+/// hash and at most `MAX_HISTORY_TOMBSTONES`.
+pub fn merge_tombstones(tombstones: &mut Vec<HistoryTombstone>, incoming: &[HistoryTombstone]) {
+    let mut by_hash: HashMap<String, HlcTimestamp> = tombstones
+        .drain(..)
+        .map(|t| (t.content_hash, t.deleted_at))
+        .collect();
+    for t in incoming {
+        let deleted_at = by_hash.entry(t.content_hash.clone()).or_default();
+        if t.deleted_at > *deleted_at {
+            *deleted_at = t.deleted_at;
+        }
+    }
+
+    tombstones.extend(
+        by_hash
+            .into_iter()
+            .map(|(content_hash, deleted_at)| HistoryTombstone {
+                content_hash,
+                deleted_at,
+            }),
+    );
+    // Newest first, then drop the oldest
+    tombstones.sort_by_key(|t| std::cmp::Reverse(t.deleted_at));
+    tombstones.truncate(MAX_HISTORY_TOMBSTONES);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hlc(wall_ms: i64) -> HlcTimestamp {
+        HlcTimestamp {
+            wall_ms,
+            counter: 0,
+        }
+    }
+
+    fn live(hash: &str, wall_ms: i64) -> HistoryItemRef {
+        HistoryItemRef {
+            hash: hash.to_string(),
+            hlc: hlc(wall_ms),
+            deleted: false,
+        }
+    }
+
+    fn deleted(hash: &str, wall_ms: i64) -> HistoryItemRef {
+        HistoryItemRef {
+            hash: hash.to_string(),
+            hlc: hlc(wall_ms),
+            deleted: true,
+        }
+    }
+
+    fn tombstone(hash: &str, wall_ms: i64) -> HistoryTombstone {
+        HistoryTombstone {
+            content_hash: hash.to_string(),
+            deleted_at: hlc(wall_ms),
+        }
+    }
+
+    fn entry(hash: &str, wall_ms: i64) -> ClipboardEntry {
+        ClipboardEntry::new_remote(
+            "content".into(),
+            hash.to_string(),
+            chrono::Utc::now(),
+            hlc(wall_ms),
+            "device",
+            "Device",
+        )
+    }
+
+    fn all_buckets() -> Vec<usize> {
+        (0..HISTORY_BUCKETS).collect()
+    }
+
+    fn hashes(tombstones: &[HistoryTombstone]) -> Vec<&str> {
+        tombstones.iter().map(|t| t.content_hash.as_str()).collect()
+    }
+
+    #[test]
+    fn test_bucket_of() {
+        assert_eq!(bucket_of("0abc"), 0);
+        assert_eq!(bucket_of("a123"), 10);
+        assert_eq!(bucket_of("f000"), 15);
+        assert_eq!(bucket_of(""), 0);
+    }
+
+    #[test]
+    fn test_bucket_digests() {
+        let items = [live("a1", 1), live("b2", 2)];
+        let digests = bucket_digests(&items);
+        assert_eq!(digests.len(), HISTORY_BUCKETS);
+
+        // Order and HLCs don't matter, only which items are in a bucket
+        assert_eq!(digests, bucket_digests(&[live("b2", 7), live("a1", 9)]));
+
+        // Changing one item only changes its own bucket
+        let changed = bucket_digests(&[live("a1", 1), live("b3", 2)]);
+        assert_eq!(mismatched_buckets(&digests, &changed), vec![11]);
+
+        // A deletion isn't the same item as the entry
+        let with_tombstone = bucket_digests(&[deleted("a1", 1), live("b2", 2)]);
+        assert_eq!(mismatched_buckets(&digests, &with_tombstone), vec![10]);
+    }
+
+    #[test]
+    fn test_equal_histories_match() {
+        let items = [live("a1", 1), live("b2", 2), deleted("c3", 3)];
+        let digests = bucket_digests(&items);
+        assert!(mismatched_buckets(&digests, &digests.clone()).is_empty());
+
+        let diff = diff_summary(&items, &all_buckets(), &items);
+        assert!(diff.fetch.is_empty());
+        assert!(diff.push.is_empty());
+        assert!(diff.push_tombstones.is_empty());
+    }
+
+    #[test]
+    fn test_missing_digests_mismatch() {
+        let digests = bucket_digests(&[live("a1", 1)]);
+        assert_eq!(
+            mismatched_buckets(&digests, &[]),
+            (0..HISTORY_BUCKETS).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_summary_exchanges_missing_entries() {
+        let ours = [live("a1", 1), deleted("c3", 3)];
+        let theirs = [live("b2", 2)];
+        let diff = diff_summary(&ours, &all_buckets(), &theirs);
+        assert_eq!(diff.fetch, vec!["b2"]);
+        assert_eq!(diff.push, vec!["a1"]);
+        assert_eq!(hashes(&diff.push_tombstones), vec!["c3"]);
+        assert!(diff.their_tombstones.is_empty());
+    }
+
+    #[test]
+    fn test_summary_only_compares_listed_buckets() {
+        let ours = [live("a1", 1), live("b2", 2)];
+        let diff = diff_summary(&ours, &[11], &[]);
+        assert_eq!(diff.push, vec!["b2"]);
+    }
+
+    #[test]
+    fn test_tombstone_beats_older_entry() {
+        // We have the entry, the peer deleted it later
+        let ours = [live("a1", 5)];
+        let theirs = [deleted("a1", 7)];
+        let diff = diff_summary(&ours, &all_buckets(), &theirs);
+        assert!(diff.push.is_empty());
+        assert!(diff.fetch.is_empty());
+        assert_eq!(hashes(&diff.their_tombstones), vec!["a1"]);
+        assert!(is_deleted(&entry("a1", 5), &diff.their_tombstones));
+
+        // And the other way round: our tombstone reaches the peer
+        let diff = diff_summary(&theirs, &all_buckets(), &ours);
+        assert!(diff.fetch.is_empty());
+        assert_eq!(hashes(&diff.push_tombstones), vec!["a1"]);
+    }
+
+    #[test]
+    fn test_entry_newer_than_tombstone_survives() {
+        // Copied again after the peer deleted it
+        let ours = [live("a1", 9)];
+        let theirs = [deleted("a1", 7)];
+        let diff = diff_summary(&ours, &all_buckets(), &theirs);
+        assert_eq!(diff.push, vec!["a1"]);
+        assert!(!is_deleted(&entry("a1", 9), &diff.their_tombstones));
+
+        let diff = diff_summary(&theirs, &all_buckets(), &ours);
+        assert_eq!(diff.fetch, vec!["a1"]);
+    }
+
+    #[test]
+    fn test_is_deleted() {
+        let tombstones = [tombstone("a1", 5)];
+        assert!(is_deleted(&entry("a1", 4), &tombstones));
+        assert!(is_deleted(&entry("a1", 5), &tombstones));
+        assert!(!is_deleted(&entry("a1", 6), &tombstones));
+        assert!(!is_deleted(&entry("b2", 1), &tombstones));
+    }
+
+    #[test]
+    fn test_merge_tombstones_keeps_latest() {
+        let mut tombstones = vec![tombstone("a1", 5), tombstone("b2", 3)];
+        merge_tombstones(&mut tombstones, &[tombstone("a1", 2), tombstone("b2", 8)]);
+
+        assert_eq!(tombstones.len(), 2);
+        // Newest first
+        assert_eq!(tombstones[0].content_hash, "b2");
+        assert_eq!(tombstones[0].deleted_at, hlc(8));
+        assert_eq!(tombstones[1].content_hash, "a1");
+        assert_eq!(tombstones[1].deleted_at, hlc(5));
+    }
+
+    #[test]
+    fn test_merge_tombstones_caps_count() {
+        let mut tombstones = Vec::new();
+        let incoming: Vec<HistoryTombstone> = (0..MAX_HISTORY_TOMBSTONES as i64 + 10)
+            .map(|i| tombstone(&format!("{:x}", i), i))
+            .collect();
+        merge_tombstones(&mut tombstones, &incoming);
+
+        assert_eq!(tombstones.len(), MAX_HISTORY_TOMBSTONES);
+        // The oldest deletions are the ones dropped
+        assert_eq!(tombstones.last().map(|t| t.deleted_at), Some(hlc(10)));
+        assert!(!tombstones.iter().any(|t| t.deleted_at < hlc(10)));
+    }
+}
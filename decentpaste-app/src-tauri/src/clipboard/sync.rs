@@ -1,7 +1,10 @@
+use std::collections::HashMap;
+
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use super::hlc::HlcTimestamp;
 use crate::security::hash_content;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -9,22 +12,73 @@ pub struct ClipboardEntry {
     pub id: String,
     pub content: String,
     pub content_hash: String,
+    /// Wall-clock time on the origin device, for display only
     pub timestamp: DateTime<Utc>,
+    /// Origin device's hybrid logical clock, used for ordering
+    #[serde(default)]
+    pub hlc: HlcTimestamp,
     pub origin_device_id: String,
     pub origin_device_name: String,
     pub is_local: bool,
+    /// Delivery state at each recipient (peer_id -> status), for entries we sent
+    #[serde(default)]
+    pub delivery: HashMap<String, DeliveryStatus>,
+    /// Set when the entry was sent to this one paired device only
+    #[serde(default)]
+    pub target_peer_id: Option<String>,
+    /// Removed from history (and cleared from the clipboard) at this time, for
+    /// copies matched by an `auto_expire` sensitive-content rule
+    #[serde(default)]
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+/// Delivery state of a sent entry at one recipient.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DeliveryStatus {
+    /// Sent or queued, no acknowledgement yet
+    Pending,
+    /// The recipient added it to its history
+    Delivered,
+    /// The recipient also put it on its system clipboard
+    Applied,
+    /// Never acknowledged, and no longer queued for the recipient
+    Failed,
+}
+
+impl DeliveryStatus {
+    /// Whether a report of `self` should replace `current`. Acknowledgements
+    /// only move forward, and a late one still beats `Failed`.
+    pub fn supersedes(self, current: DeliveryStatus) -> bool {
+        let rank = |status| match status {
+            DeliveryStatus::Pending => 0,
+            DeliveryStatus::Failed => 1,
+            DeliveryStatus::Delivered => 2,
+            DeliveryStatus::Applied => 3,
+        };
+        rank(self) > rank(current)
+    }
 }
 
 impl ClipboardEntry {
-    pub fn new_local(content: String, device_id: &str, device_name: &str) -> Self {
+    pub fn new_local(
+        content: String,
+        hlc: HlcTimestamp,
+        device_id: &str,
+        device_name: &str,
+    ) -> Self {
         Self {
             id: Uuid::new_v4().to_string(),
             content_hash: hash_content(&content),
             content,
             timestamp: Utc::now(),
+            hlc,
             origin_device_id: device_id.to_string(),
             origin_device_name: device_name.to_string(),
             is_local: true,
+            delivery: HashMap::new(),
+            target_peer_id: None,
+            expires_at: None,
         }
     }
 
@@ -32,6 +86,7 @@ impl ClipboardEntry {
         content: String,
         content_hash: String,
         timestamp: DateTime<Utc>,
+        hlc: HlcTimestamp,
         device_id: &str,
         device_name: &str,
     ) -> Self {
@@ -40,12 +95,27 @@ impl ClipboardEntry {
             content,
             content_hash,
             timestamp,
+            hlc,
             origin_device_id: device_id.to_string(),
             origin_device_name: device_name.to_string(),
             is_local: false,
+            delivery: HashMap::new(),
+            target_peer_id: None,
+            expires_at: None,
         }
     }
 
+    /// Position in the history (newest is greatest): the HLC, with the origin device
+    /// breaking ties. Entries stored before HLCs existed fall back to their timestamp.
+    pub fn order_key(&self) -> (HlcTimestamp, &str) {
+        let hlc = if self.hlc.is_unset() {
+            HlcTimestamp::from_physical(self.timestamp)
+        } else {
+            self.hlc
+        };
+        (hlc, &self.origin_device_id)
+    }
+
     pub fn preview(&self, max_length: usize) -> String {
         if self.content.len() <= max_length {
             self.content.clone()
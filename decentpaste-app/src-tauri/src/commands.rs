@@ -8,10 +8,20 @@ use tracing::{debug, info, warn};
 
 use crate::clipboard::ClipboardEntry;
 use crate::error::{DecentPasteError, Result};
+use crate::network::protocol::GroupPayload;
 use crate::network::{DiscoveredPeer, NetworkCommand, NetworkStatus};
-use crate::security::{generate_pin, PairingSession, PairingState};
+use crate::security::{
+    generate_key, generate_pin, invite_request_proof, public_key_fingerprint,
+    regenerate_identity_keys, safety_number, validate_passphrase, IdentityRotation, PairingInvite,
+    PairingSession, PairingState, PairingUri, PakeState, PassphraseListener,
+};
 use crate::state::AppState;
-use crate::storage::{save_settings, AppSettings, PairedPeer};
+use crate::storage::{
+    save_settings, AppSettings, GroupMember, PairedPeer, PairingExpiry, PairingGroup, SyncPolicy,
+};
+
+/// Largest clipboard content we send (1MB), to prevent memory exhaustion.
+const MAX_CLIPBOARD_SIZE: usize = 1024 * 1024;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DeviceInfo {
@@ -170,22 +180,20 @@ pub async fn remove_paired_peer(
     use chrono::Utc;
     use tauri::Emitter;
 
-    // Get the peer info before removing (we'll use it to re-emit as discovered)
-    let peer_info = {
-        let peers = state.paired_peers.read().await;
-        peers
-            .iter()
-            .find(|p| p.peer_id == peer_id)
-            .map(|p| (p.peer_id.clone(), p.device_name.clone()))
-    };
-
-    // Remove from paired list and flush to vault immediately
-    {
-        let mut peers = state.paired_peers.write().await;
-        peers.retain(|p| p.peer_id != peer_id);
+    // Group members are paired through the group key; removing one is a group re-key
+    if let Some(ref group) = *state.pairing_group.read().await {
+        if group.is_member(&peer_id) {
+            return Err(DecentPasteError::InvalidInput(
+                "Peer is a group member - remove it from the group instead".into(),
+            ));
+        }
     }
-    // Flush-on-write: persist immediately to prevent data loss
-    state.flush_paired_peers().await?;
+
+    // Remove the pairing and tell the peer; keep the info to re-emit it as discovered
+    let peer_info = state
+        .unpair_peer(&peer_id)
+        .await?
+        .map(|p| (p.peer_id, p.device_name));
 
     // Emit directly using the info we have from the paired peer
     // This ensures the peer appears in discovered list with correct device name
@@ -213,6 +221,476 @@ pub async fn remove_paired_peer(
     Ok(())
 }
 
+/// Key fingerprints for a paired peer, compared out of band before marking it verified.
+#[derive(Debug, Clone, Serialize)]
+pub struct PeerFingerprint {
+    pub peer_id: String,
+    /// Fingerprint of this device's identity key
+    pub local_fingerprint: String,
+    /// Fingerprint of the peer's recorded identity key (`None` until one is known)
+    pub peer_fingerprint: Option<String>,
+    /// Shown identically on both devices if neither key was substituted
+    pub safety_number: Option<String>,
+    pub verified: bool,
+}
+
+#[tauri::command]
+pub async fn get_peer_fingerprint(
+    state: State<'_, AppState>,
+    peer_id: String,
+) -> Result<PeerFingerprint> {
+    let local_key = state
+        .device_identity
+        .read()
+        .await
+        .as_ref()
+        .map(|i| i.public_key.clone())
+        .ok_or(DecentPasteError::NotInitialized)?;
+
+    let peers = state.paired_peers.read().await;
+    let peer = peers
+        .iter()
+        .find(|p| p.peer_id == peer_id)
+        .ok_or_else(|| DecentPasteError::PeerNotFound(peer_id.clone()))?;
+
+    Ok(PeerFingerprint {
+        peer_id: peer.peer_id.clone(),
+        local_fingerprint: public_key_fingerprint(&local_key),
+        peer_fingerprint: peer.public_key.as_deref().map(public_key_fingerprint),
+        safety_number: peer
+            .public_key
+            .as_deref()
+            .map(|key| safety_number(&local_key, key)),
+        verified: peer.verified,
+    })
+}
+
+/// Mark a paired peer as verified (after comparing safety numbers) or unverified.
+#[tauri::command]
+pub async fn set_peer_verified(
+    state: State<'_, AppState>,
+    peer_id: String,
+    verified: bool,
+) -> Result<()> {
+    {
+        let mut peers = state.paired_peers.write().await;
+        let peer = peers
+            .iter_mut()
+            .find(|p| p.peer_id == peer_id)
+            .ok_or_else(|| DecentPasteError::PeerNotFound(peer_id.clone()))?;
+        if verified && peer.public_key.is_none() {
+            return Err(DecentPasteError::InvalidInput(
+                "No identity key recorded for this peer yet".into(),
+            ));
+        }
+        peer.verified = verified;
+    }
+    state.flush_paired_peers().await
+}
+
+/// Choose what we exchange with a paired peer: whether we send to it, accept
+/// content from it, and whether accepted content replaces our clipboard or only
+/// goes to history.
+#[tauri::command]
+pub async fn set_peer_sync_policy(
+    state: State<'_, AppState>,
+    peer_id: String,
+    policy: SyncPolicy,
+) -> Result<()> {
+    // Group messages are encrypted with the shared group key, readable by every member
+    if !policy.send {
+        if let Some(ref group) = *state.pairing_group.read().await {
+            if group.is_member(&peer_id) {
+                return Err(DecentPasteError::InvalidInput(
+                    "Group members receive everything sent to the group - remove it from the group instead".into(),
+                ));
+            }
+        }
+    }
+
+    {
+        let mut peers = state.paired_peers.write().await;
+        let peer = peers
+            .iter_mut()
+            .find(|p| p.peer_id == peer_id)
+            .ok_or_else(|| DecentPasteError::PeerNotFound(peer_id.clone()))?;
+        peer.sync_policy = policy;
+    }
+    state.flush_paired_peers().await?;
+
+    // Drop anything buffered for a peer we no longer send to
+    if !policy.send {
+        state.remove_message_buffer(&peer_id).await;
+    }
+    Ok(())
+}
+
+/// Longest allowed lifetime of a temporary pairing.
+const MAX_PAIRING_EXPIRY_SECS: u64 = 365 * 24 * 60 * 60;
+
+/// Make a pairing temporary: it is removed (and the peer notified) after
+/// `expires_in_secs` or once `max_messages` clipboard messages were exchanged,
+/// whichever comes first. With neither set the pairing becomes permanent again.
+#[tauri::command]
+pub async fn set_pairing_expiry(
+    state: State<'_, AppState>,
+    peer_id: String,
+    expires_in_secs: Option<u64>,
+    max_messages: Option<u32>,
+) -> Result<()> {
+    // Group pairings live and die with the group
+    if let Some(ref group) = *state.pairing_group.read().await {
+        if group.is_member(&peer_id) {
+            return Err(DecentPasteError::InvalidInput(
+                "Group members can't have a temporary pairing".into(),
+            ));
+        }
+    }
+
+    if expires_in_secs.is_some_and(|s| s == 0 || s > MAX_PAIRING_EXPIRY_SECS)
+        || max_messages == Some(0)
+    {
+        return Err(DecentPasteError::InvalidInput(
+            "Temporary pairings must last between 1 second and 1 year, and at least 1 message"
+                .into(),
+        ));
+    }
+
+    let expiry = match (expires_in_secs, max_messages) {
+        (None, None) => None,
+        (secs, messages) => Some(PairingExpiry {
+            expires_at: secs.map(|s| chrono::Utc::now() + chrono::Duration::seconds(s as i64)),
+            messages_remaining: messages,
+        }),
+    };
+
+    {
+        let mut peers = state.paired_peers.write().await;
+        let peer = peers
+            .iter_mut()
+            .find(|p| p.peer_id == peer_id)
+            .ok_or_else(|| DecentPasteError::PeerNotFound(peer_id.clone()))?;
+        peer.expiry = expiry.clone();
+    }
+    state.flush_paired_peers().await?;
+
+    // Temporary pairings get no sync backlog
+    if expiry.is_some() {
+        state.remove_message_buffer(&peer_id).await;
+    }
+    Ok(())
+}
+
+/// Offer one clipboard item to a discovered device we are not paired with.
+///
+/// Returns the transfer ID. The item is only sent once the receiver accepts
+/// and `confirm_nearby_send` is called after comparing confirmation codes.
+#[tauri::command]
+pub async fn start_nearby_send(
+    state: State<'_, AppState>,
+    peer_id: String,
+    content: String,
+) -> Result<String> {
+    use crate::network::protocol::NearbyMessage;
+    use crate::security::NearbyTransfer;
+
+    const MAX_NEARBY_SIZE: usize = 1024 * 1024;
+    if content.is_empty() || content.len() > MAX_NEARBY_SIZE {
+        return Err(DecentPasteError::InvalidInput(
+            "Nearby send content must be between 1 byte and 1MB".into(),
+        ));
+    }
+    if !state
+        .discovered_peers
+        .read()
+        .await
+        .iter()
+        .any(|p| p.peer_id == peer_id)
+    {
+        return Err(DecentPasteError::PeerNotFound(peer_id));
+    }
+
+    let device_name = state
+        .device_identity
+        .read()
+        .await
+        .as_ref()
+        .map(|i| i.device_name.clone())
+        .ok_or(DecentPasteError::NotInitialized)?;
+
+    let transfer_id = uuid::Uuid::new_v4().to_string();
+    let transfer = NearbyTransfer::new(transfer_id.clone(), peer_id.clone(), Some(content));
+    let public_key = transfer.public_key.clone();
+    state.nearby_transfers.write().await.push(transfer);
+
+    send_nearby_message(
+        &state,
+        peer_id,
+        NearbyMessage::Offer {
+            transfer_id: transfer_id.clone(),
+            device_name,
+            public_key,
+        },
+    )
+    .await?;
+
+    Ok(transfer_id)
+}
+
+/// Accept or decline an incoming nearby send offer.
+///
+/// Accepting completes the key exchange; the confirmation code is returned
+/// and also emitted as `nearby-send-code`.
+#[tauri::command]
+pub async fn respond_to_nearby_send(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+    transfer_id: String,
+    accept: bool,
+) -> Result<Option<String>> {
+    use crate::network::protocol::NearbyMessage;
+    use crate::security::NearbyTransferState;
+    use tauri::Emitter;
+
+    let (peer_id, public_key, code) = {
+        let mut transfers = state.nearby_transfers.write().await;
+        let index = transfers
+            .iter()
+            .position(|t| {
+                t.transfer_id == transfer_id
+                    && !t.is_sender
+                    && t.state == NearbyTransferState::Offered
+            })
+            .ok_or_else(|| DecentPasteError::InvalidInput("Unknown nearby send".into()))?;
+
+        if !accept {
+            let transfer = transfers.remove(index);
+            (transfer.peer_id.clone(), None, None)
+        } else {
+            let transfer = &mut transfers[index];
+            let peer_key = transfer.peer_public_key.clone().unwrap_or_default();
+            transfer.agree(&peer_key)?;
+            (
+                transfer.peer_id.clone(),
+                Some(transfer.public_key.clone()),
+                transfer.confirmation_code.clone(),
+            )
+        }
+    };
+
+    let message = match public_key {
+        Some(public_key) => NearbyMessage::Accept {
+            transfer_id: transfer_id.clone(),
+            public_key,
+        },
+        None => NearbyMessage::Cancel {
+            transfer_id: transfer_id.clone(),
+        },
+    };
+    send_nearby_message(&state, peer_id, message).await?;
+
+    if let Some(ref code) = code {
+        let _ = app_handle.emit(
+            "nearby-send-code",
+            serde_json::json!({ "transferId": transfer_id, "code": code }),
+        );
+    }
+    Ok(code)
+}
+
+/// Send the item once the user confirmed both devices show the same code.
+#[tauri::command]
+pub async fn confirm_nearby_send(state: State<'_, AppState>, transfer_id: String) -> Result<()> {
+    use crate::network::protocol::NearbyMessage;
+    use crate::security::{encrypt_content, hash_content, NearbyTransferState};
+
+    let (peer_id, message) = {
+        let mut transfers = state.nearby_transfers.write().await;
+        let transfer = transfers
+            .iter_mut()
+            .find(|t| {
+                t.transfer_id == transfer_id
+                    && t.is_sender
+                    && t.state == NearbyTransferState::AwaitingConfirmation
+            })
+            .ok_or_else(|| DecentPasteError::InvalidInput("Unknown nearby send".into()))?;
+
+        let (content, key) = match (transfer.content(), transfer.transfer_key()) {
+            (Some(content), Some(key)) => (content, key),
+            _ => {
+                return Err(DecentPasteError::InvalidInput(
+                    "Nearby send not ready".into(),
+                ))
+            }
+        };
+        let message = NearbyMessage::Content {
+            transfer_id: transfer_id.clone(),
+            encrypted_content: encrypt_content(content.as_bytes(), key)?,
+            content_hash: hash_content(content),
+        };
+        transfer.state = NearbyTransferState::Sent;
+        (transfer.peer_id.clone(), message)
+    };
+
+    send_nearby_message(&state, peer_id, message).await
+}
+
+/// Abort a nearby send from either side.
+#[tauri::command]
+pub async fn cancel_nearby_send(state: State<'_, AppState>, transfer_id: String) -> Result<()> {
+    use crate::network::protocol::NearbyMessage;
+
+    let transfer = {
+        let mut transfers = state.nearby_transfers.write().await;
+        match transfers.iter().position(|t| t.transfer_id == transfer_id) {
+            Some(index) => transfers.remove(index),
+            None => return Ok(()),
+        }
+    };
+
+    send_nearby_message(
+        &state,
+        transfer.peer_id.clone(),
+        NearbyMessage::Cancel { transfer_id },
+    )
+    .await
+}
+
+async fn send_nearby_message(
+    state: &AppState,
+    peer_id: String,
+    message: crate::network::protocol::NearbyMessage,
+) -> Result<()> {
+    let tx = state.network_command_tx.read().await;
+    let tx = tx.as_ref().ok_or(DecentPasteError::NotInitialized)?;
+    tx.send(NetworkCommand::SendNearbyMessage { peer_id, message })
+        .await
+        .map_err(|_| DecentPasteError::ChannelSend)
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct IdentityRotationResult {
+    /// PeerId after the rotation (unchanged unless `rotate_peer_id` was set)
+    pub peer_id: String,
+    /// Paired devices that will be told about the new keys
+    pub notified_peers: usize,
+    /// A new PeerId only takes effect once the app is restarted
+    pub restart_required: bool,
+}
+
+/// Replace this device's X25519 identity key and, with `rotate_peer_id`, its
+/// libp2p keypair (and so its PeerId).
+///
+/// Every paired device receives an announcement signed with the old libp2p key.
+/// Devices that are offline get it when they reconnect.
+#[tauri::command]
+pub async fn rotate_identity(
+    state: State<'_, AppState>,
+    rotate_peer_id: bool,
+) -> Result<IdentityRotationResult> {
+    // A device that missed the last rotation can only read announcements
+    // encrypted with the secret it still has
+    if !state.pending_rotations.read().await.is_empty() {
+        return Err(DecentPasteError::InvalidInput(
+            "The previous identity rotation hasn't reached every paired device yet".into(),
+        ));
+    }
+    // Group pair secrets are derived from the PeerIds
+    if rotate_peer_id && state.pairing_group.read().await.is_some() {
+        return Err(DecentPasteError::InvalidInput(
+            "Leave the pairing group before changing the peer ID".into(),
+        ));
+    }
+
+    let identity = state
+        .device_identity
+        .read()
+        .await
+        .clone()
+        .ok_or(DecentPasteError::NotInitialized)?;
+    let new_identity = regenerate_identity_keys(&identity);
+    let new_private_key = new_identity
+        .private_key
+        .clone()
+        .ok_or(DecentPasteError::NotInitialized)?;
+
+    let new_keypair = rotate_peer_id.then(libp2p::identity::Keypair::generate_ed25519);
+    let rotation = {
+        let mut vault_manager = state.vault_manager.write().await;
+        let manager = vault_manager
+            .as_mut()
+            .ok_or(DecentPasteError::VaultLocked)?;
+        let old_keypair = manager
+            .get_libp2p_keypair()?
+            .ok_or(DecentPasteError::NotInitialized)?;
+        let new_peer_id = new_keypair
+            .as_ref()
+            .unwrap_or(&old_keypair)
+            .public()
+            .to_peer_id()
+            .to_string();
+        let rotation = IdentityRotation::sign(
+            &old_keypair,
+            new_peer_id,
+            identity.public_key.clone(),
+            new_identity.public_key.clone(),
+        )?;
+
+        // Persisted together with the re-keyed pairings below
+        manager.set_device_identity(&new_identity)?;
+        if let Some(ref keypair) = new_keypair {
+            manager.set_libp2p_keypair(keypair)?;
+        }
+        rotation
+    };
+    *state.device_identity.write().await = Some(new_identity);
+
+    let notified_peers = state
+        .queue_identity_rotation(&rotation, &new_private_key)
+        .await?;
+
+    let tx = state.network_command_tx.read().await;
+    if let Some(tx) = tx.as_ref() {
+        // Peers that haven't processed the rotation would flag the new key as a
+        // change, so stop announcing a key until every peer has acked
+        let _ = tx
+            .send(NetworkCommand::SetAnnouncedIdentityKey { public_key: None })
+            .await;
+
+        // A new PeerId can only be proven by connecting with it, so in that case
+        // the announcements go out from PeerReady after the restart
+        if !rotation.peer_id_changed() {
+            let ready: Vec<String> = state.ready_peers.read().await.iter().cloned().collect();
+            for pending in state.pending_rotations.read().await.iter() {
+                if ready.contains(&pending.peer_id) {
+                    let _ = tx
+                        .send(NetworkCommand::SendIdentityRotation {
+                            peer_id: pending.peer_id.clone(),
+                            old_peer_id: pending.old_peer_id.clone(),
+                            encrypted_payload: pending.encrypted_payload.clone(),
+                        })
+                        .await;
+                }
+            }
+        }
+    }
+
+    info!(
+        "Rotated identity keys{} ({} paired devices to notify)",
+        if rotation.peer_id_changed() {
+            " and peer ID"
+        } else {
+            ""
+        },
+        notified_peers
+    );
+    Ok(IdentityRotationResult {
+        peer_id: rotation.new_peer_id.clone(),
+        notified_peers,
+        restart_required: rotation.peer_id_changed(),
+    })
+}
+
 // Pairing flow
 #[tauri::command]
 pub async fn initiate_pairing(state: State<'_, AppState>, peer_id: String) -> Result<String> {
@@ -236,19 +714,32 @@ pub async fn initiate_pairing(state: State<'_, AppState>, peer_id: String) -> Re
     let session = PairingSession::new(session_id.clone(), peer_id.clone(), true)
         .with_peer_addresses(peer_addresses);
 
-    let mut sessions = state.pairing_sessions.write().await;
-    sessions.push(session);
+    state.pairing_sessions.write().await.push(session);
 
-    // Send pairing request through network
+    send_pairing_request(&state, &session_id, peer_id, None).await?;
+
+    Ok(session_id)
+}
+
+/// Send our `PairingRequest` for an initiator session.
+/// `invite_secret` is the secret from a scanned pairing URI, if any.
+async fn send_pairing_request(
+    state: &AppState,
+    session_id: &str,
+    peer_id: String,
+    invite_secret: Option<&[u8]>,
+) -> Result<()> {
     let device_identity = state.device_identity.read().await;
     if let Some(ref identity) = *device_identity {
         let tx = state.network_command_tx.read().await;
         if let Some(tx) = tx.as_ref() {
             let request = crate::network::PairingRequest {
-                session_id: session_id.clone(), // Include session_id so responder uses the same one
+                session_id: session_id.to_string(), // Include session_id so responder uses the same one
                 device_name: identity.device_name.clone(),
                 device_id: identity.device_id.clone(),
                 public_key: identity.public_key.clone(),
+                invite_proof: invite_secret
+                    .map(|secret| invite_request_proof(secret, session_id, &identity.public_key)),
             };
 
             let message = crate::network::ProtocolMessage::Pairing(
@@ -264,6 +755,206 @@ pub async fn initiate_pairing(state: State<'_, AppState>, peer_id: String) -> Re
         }
     }
 
+    Ok(())
+}
+
+/// Listen addresses worth handing to another device (loopback ones are useless to it).
+async fn advertised_addresses(state: &AppState) -> Vec<String> {
+    state
+        .listen_addresses
+        .read()
+        .await
+        .iter()
+        .filter(|a| !a.starts_with("/ip4/127.") && !a.starts_with("/ip6/::1/"))
+        .cloned()
+        .collect()
+}
+
+/// A pairing URI and its QR code, shown on the device being paired with.
+#[derive(Debug, Clone, Serialize)]
+pub struct PairingUriInfo {
+    pub uri: String,
+    /// SVG rendering of `uri`
+    pub qr_svg: String,
+    pub expires_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Create a one-time pairing URI (and QR code) for devices that can't see each
+/// other via mDNS. Scanning it pairs without a PIN.
+#[tauri::command]
+pub async fn create_pairing_uri(state: State<'_, AppState>) -> Result<PairingUriInfo> {
+    let peer_id = state
+        .local_peer_id
+        .read()
+        .await
+        .clone()
+        .ok_or(DecentPasteError::NotInitialized)?;
+
+    let addresses = advertised_addresses(&state).await;
+    if addresses.is_empty() {
+        return Err(DecentPasteError::Network(
+            "No listen addresses available".into(),
+        ));
+    }
+
+    let (device_name, fingerprint) = {
+        let identity = state.device_identity.read().await;
+        let identity = identity.as_ref().ok_or(DecentPasteError::NotInitialized)?;
+        (
+            identity.device_name.clone(),
+            public_key_fingerprint(&identity.public_key),
+        )
+    };
+
+    let invite = PairingInvite::new();
+    let pairing_uri = PairingUri {
+        peer_id,
+        addresses,
+        device_name,
+        fingerprint,
+        secret: invite.secret().to_vec(),
+    };
+    let info = PairingUriInfo {
+        uri: pairing_uri.to_uri(),
+        qr_svg: pairing_uri.to_qr_svg()?,
+        expires_at: invite.created_at
+            + chrono::Duration::seconds(state.settings.read().await.pairing_timeout_secs as i64),
+    };
+
+    state.pairing_invites.write().await.push(invite);
+    info!("Created pairing URI");
+
+    Ok(info)
+}
+
+/// Pair with the device that created `uri` (scanned QR code or pasted link).
+/// Dials the addresses in the URI and runs the pairing flow without a PIN.
+#[tauri::command]
+pub async fn pair_with_uri(state: State<'_, AppState>, uri: String) -> Result<String> {
+    let pairing_uri = PairingUri::parse(&uri)?;
+    let peer_id = pairing_uri.peer_id.clone();
+
+    if state.local_peer_id.read().await.as_deref() == Some(peer_id.as_str()) {
+        return Err(DecentPasteError::InvalidInput(
+            "Cannot pair with this device".into(),
+        ));
+    }
+    if state.is_peer_paired(&peer_id).await {
+        return Err(DecentPasteError::AlreadyPaired(peer_id));
+    }
+
+    let tx = state
+        .network_command_tx
+        .read()
+        .await
+        .clone()
+        .ok_or(DecentPasteError::NotInitialized)?;
+    tx.send(NetworkCommand::DialPeer {
+        peer_id: peer_id.clone(),
+        addresses: pairing_uri.addresses.clone(),
+    })
+    .await
+    .map_err(|_| DecentPasteError::ChannelSend)?;
+
+    let session_id = uuid::Uuid::new_v4().to_string();
+    let session = PairingSession::new(session_id.clone(), peer_id.clone(), true)
+        .with_peer_name(pairing_uri.device_name.clone())
+        .with_peer_addresses(pairing_uri.addresses.clone())
+        .with_invite(pairing_uri.secret.clone(), pairing_uri.fingerprint.clone());
+    state.pairing_sessions.write().await.push(session);
+
+    send_pairing_request(&state, &session_id, peer_id, Some(&pairing_uri.secret)).await?;
+
+    Ok(session_id)
+}
+
+/// Accept the next passphrase pairing attempt (for devices that can't show a PIN).
+/// The passphrase is single-use and expires with the pairing timeout.
+#[tauri::command]
+pub async fn enable_passphrase_pairing(
+    state: State<'_, AppState>,
+    passphrase: String,
+) -> Result<()> {
+    let listener = PassphraseListener::new(passphrase)?;
+    *state.passphrase_listener.write().await = Some(listener);
+    info!("Passphrase pairing enabled");
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn disable_passphrase_pairing(state: State<'_, AppState>) -> Result<()> {
+    *state.passphrase_listener.write().await = None;
+    Ok(())
+}
+
+/// Pair with a peer that has enabled passphrase pairing with the same passphrase.
+/// Runs SPAKE2 instead of the PIN flow; the agreed key becomes the shared secret.
+#[tauri::command]
+pub async fn initiate_passphrase_pairing(
+    state: State<'_, AppState>,
+    peer_id: String,
+    passphrase: String,
+) -> Result<String> {
+    validate_passphrase(&passphrase)?;
+    if state.is_peer_paired(&peer_id).await {
+        return Err(DecentPasteError::AlreadyPaired(peer_id));
+    }
+
+    let local_peer_id = state
+        .local_peer_id
+        .read()
+        .await
+        .clone()
+        .ok_or(DecentPasteError::NotInitialized)?;
+    let (device_name, public_key) = state
+        .device_identity
+        .read()
+        .await
+        .as_ref()
+        .map(|i| (i.device_name.clone(), i.public_key.clone()))
+        .ok_or(DecentPasteError::NotInitialized)?;
+
+    let peer_addresses = {
+        let discovered = state.discovered_peers.read().await;
+        discovered
+            .iter()
+            .find(|p| p.peer_id == peer_id)
+            .map(|p| p.addresses.clone())
+            .unwrap_or_default()
+    };
+
+    let session_id = uuid::Uuid::new_v4().to_string();
+    let session = PairingSession::new(session_id.clone(), peer_id.clone(), true)
+        .with_peer_addresses(peer_addresses);
+
+    let (pake, message) = PakeState::start_initiator(&passphrase, &local_peer_id, &peer_id);
+    state
+        .pake_states
+        .write()
+        .await
+        .insert(session_id.clone(), pake);
+    state.pairing_sessions.write().await.push(session);
+
+    let start = crate::network::protocol::PakeStart {
+        session_id: session_id.clone(),
+        device_name,
+        message,
+        public_key,
+    };
+    let message = crate::network::ProtocolMessage::Pairing(
+        crate::network::protocol::PairingMessage::PakeStart(start),
+    );
+
+    let tx = state.network_command_tx.read().await;
+    if let Some(tx) = tx.as_ref() {
+        tx.send(NetworkCommand::SendPairingRequest {
+            peer_id,
+            message: message.to_bytes().unwrap_or_default(),
+        })
+        .await
+        .map_err(|_| DecentPasteError::ChannelSend)?;
+    }
+
     Ok(session_id)
 }
 
@@ -285,6 +976,9 @@ pub async fn respond_to_pairing(
                 tracing::debug!("respond_to_pairing called again for already-accepted session, returning existing PIN");
                 return Ok(session.pin.clone());
             }
+            if session.state == PairingState::Expired {
+                return Err(DecentPasteError::PairingTimeout);
+            }
             if matches!(
                 session.state,
                 PairingState::Failed(_)
@@ -336,6 +1030,7 @@ pub async fn respond_to_pairing(
                         pin: pin.clone(),
                         device_name: identity.device_name.clone(),
                         public_key: identity.public_key.clone(), // Our X25519 public key for ECDH
+                        invite_proof: None,
                     })
                     .await
                     .is_err()
@@ -360,6 +1055,7 @@ pub async fn respond_to_pairing(
                 .send(NetworkCommand::RejectPairing {
                     peer_id,
                     session_id,
+                    reason: None,
                 })
                 .await;
         }
@@ -381,6 +1077,9 @@ pub async fn confirm_pairing(
         let mut sessions = state.pairing_sessions.write().await;
 
         if let Some(session) = sessions.iter_mut().find(|s| s.session_id == session_id) {
+            if session.state == PairingState::Expired {
+                return Err(DecentPasteError::PairingTimeout);
+            }
             if session.pin.as_ref() != Some(&pin) {
                 session.state = PairingState::Failed("Invalid PIN".into());
                 return Ok(false);
@@ -440,6 +1139,256 @@ pub async fn cancel_pairing(state: State<'_, AppState>, session_id: String) -> R
     Ok(())
 }
 
+// Pairing groups
+
+/// Pairing group as exposed to the frontend (without the group key).
+#[derive(Debug, Clone, Serialize)]
+pub struct GroupInfo {
+    pub group_id: String,
+    pub name: String,
+    pub epoch: u64,
+    pub members: Vec<GroupMember>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl From<&PairingGroup> for GroupInfo {
+    fn from(group: &PairingGroup) -> Self {
+        Self {
+            group_id: group.group_id.clone(),
+            name: group.name.clone(),
+            epoch: group.epoch,
+            members: group.members.clone(),
+            created_at: group.created_at,
+        }
+    }
+}
+
+async fn require_local_peer_id(state: &AppState) -> Result<String> {
+    state
+        .local_peer_id
+        .read()
+        .await
+        .clone()
+        .ok_or(DecentPasteError::NotInitialized)
+}
+
+async fn require_pairing_group(state: &AppState) -> Result<PairingGroup> {
+    state
+        .pairing_group
+        .read()
+        .await
+        .clone()
+        .ok_or_else(|| DecentPasteError::Pairing("Not in a pairing group".into()))
+}
+
+#[tauri::command]
+pub async fn get_group(state: State<'_, AppState>) -> Result<Option<GroupInfo>> {
+    let group = state.pairing_group.read().await;
+    Ok(group.as_ref().map(GroupInfo::from))
+}
+
+/// Create a pairing group with this device as its only member.
+#[tauri::command]
+pub async fn create_group(state: State<'_, AppState>, name: String) -> Result<GroupInfo> {
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err(DecentPasteError::InvalidInput(
+            "Group name cannot be empty".into(),
+        ));
+    }
+    if state.pairing_group.read().await.is_some() {
+        return Err(DecentPasteError::Pairing(
+            "Already in a pairing group".into(),
+        ));
+    }
+
+    let local_peer_id = require_local_peer_id(&state).await?;
+    let (device_name, public_key) = state
+        .device_identity
+        .read()
+        .await
+        .as_ref()
+        .map(|i| (i.device_name.clone(), i.public_key.clone()))
+        .ok_or(DecentPasteError::NotInitialized)?;
+
+    let founder = GroupMember {
+        peer_id: local_peer_id.clone(),
+        device_name,
+        addresses: advertised_addresses(&state).await,
+        public_key: Some(public_key),
+    };
+    let group = PairingGroup::new(name, generate_key(), founder);
+    let info = GroupInfo::from(&group);
+    state.apply_pairing_group(group, &local_peer_id).await?;
+
+    info!("Created pairing group {}", info.group_id);
+    Ok(info)
+}
+
+/// Add an already paired peer to our group.
+/// The new group state is sent to every member, which pairs them with the new peer.
+#[tauri::command]
+pub async fn invite_to_group(state: State<'_, AppState>, peer_id: String) -> Result<GroupInfo> {
+    let local_peer_id = require_local_peer_id(&state).await?;
+    let mut group = require_pairing_group(&state).await?;
+    if group.is_member(&peer_id) {
+        return Err(DecentPasteError::InvalidInput(
+            "Peer is already a group member".into(),
+        ));
+    }
+
+    let member = {
+        let peers = state.paired_peers.read().await;
+        let peer = peers
+            .iter()
+            .find(|p| p.peer_id == peer_id)
+            .ok_or_else(|| DecentPasteError::PeerNotFound(peer_id.clone()))?;
+        GroupMember {
+            peer_id: peer.peer_id.clone(),
+            device_name: peer.device_name.clone(),
+            addresses: peer.last_known_addresses.clone(),
+            public_key: peer.public_key.clone(),
+        }
+    };
+
+    group.members.push(member);
+    group.epoch += 1;
+    let info = GroupInfo::from(&group);
+    state.apply_pairing_group(group, &local_peer_id).await?;
+    state.broadcast_group_state(&local_peer_id).await;
+
+    info!("Invited {} to pairing group {}", peer_id, info.group_id);
+    Ok(info)
+}
+
+/// Remove a member from our group and re-key it, so the removed device can't
+/// decrypt anything sent to the group afterwards.
+#[tauri::command]
+pub async fn remove_group_member(state: State<'_, AppState>, peer_id: String) -> Result<GroupInfo> {
+    let local_peer_id = require_local_peer_id(&state).await?;
+    let mut group = require_pairing_group(&state).await?;
+    if peer_id == local_peer_id {
+        return Err(DecentPasteError::InvalidInput(
+            "Use leave_group to leave the group".into(),
+        ));
+    }
+    if !group.is_member(&peer_id) {
+        return Err(DecentPasteError::PeerNotFound(peer_id));
+    }
+
+    // Tell the removed member first: its group-derived pairing with us is about to go away
+    let removed = GroupPayload::Removed {
+        group_id: group.group_id.clone(),
+    };
+    if let Err(e) = state.send_group_payload(&peer_id, &removed).await {
+        warn!("Failed to notify {} of group removal: {}", peer_id, e);
+    }
+
+    group.members.retain(|m| m.peer_id != peer_id);
+    group.group_key = generate_key();
+    group.epoch += 1;
+    let info = GroupInfo::from(&group);
+    state.apply_pairing_group(group, &local_peer_id).await?;
+    state.broadcast_group_state(&local_peer_id).await;
+
+    info!("Removed {} from pairing group {}", peer_id, info.group_id);
+    Ok(info)
+}
+
+/// Leave our group. The remaining members re-key without us.
+#[tauri::command]
+pub async fn leave_group(state: State<'_, AppState>) -> Result<()> {
+    let local_peer_id = require_local_peer_id(&state).await?;
+    let group = require_pairing_group(&state).await?;
+
+    let left = GroupPayload::Left {
+        group_id: group.group_id.clone(),
+    };
+    for member in group.members.iter().filter(|m| m.peer_id != local_peer_id) {
+        if let Err(e) = state.send_group_payload(&member.peer_id, &left).await {
+            warn!("Failed to notify {} that we left: {}", member.peer_id, e);
+        }
+    }
+
+    state.clear_pairing_group().await?;
+    info!("Left pairing group {}", group.group_id);
+    Ok(())
+}
+
+/// Join the pairing group a paired device invited us to (see `group-invite`), or
+/// decline. Declining tells the inviter, which drops us and re-keys the group.
+#[tauri::command]
+pub async fn respond_to_group_invite(
+    state: State<'_, AppState>,
+    group_id: String,
+    accept: bool,
+) -> Result<Option<GroupInfo>> {
+    let invite = {
+        let mut pending = state.pending_group_invite.write().await;
+        match pending.take() {
+            Some(invite) if invite.group.group_id == group_id => invite,
+            other => {
+                *pending = other;
+                return Err(DecentPasteError::InvalidInput(
+                    "Unknown group invite".into(),
+                ));
+            }
+        }
+    };
+
+    // The inviter lists the identity key it recorded for us. If that isn't ours,
+    // our pairing with the inviter was tampered with and the group would hand
+    // the substituted key to every member.
+    let our_key = state
+        .device_identity
+        .read()
+        .await
+        .as_ref()
+        .map(|i| i.public_key.clone())
+        .ok_or(DecentPasteError::NotInitialized)?;
+    let local_peer_id = require_local_peer_id(&state).await?;
+    let key_mismatch = invite
+        .group
+        .members
+        .iter()
+        .find(|m| m.peer_id == local_peer_id)
+        .and_then(|m| m.public_key.as_ref())
+        .is_some_and(|listed| *listed != our_key);
+
+    if !accept || key_mismatch {
+        let declined = GroupPayload::Declined { group_id };
+        if let Err(e) = state
+            .send_group_payload(&invite.from_peer_id, &declined)
+            .await
+        {
+            warn!(
+                "Failed to decline group invite from {}: {}",
+                invite.from_peer_id, e
+            );
+        }
+        if key_mismatch {
+            return Err(DecentPasteError::Pairing(
+                "The group invite lists a different identity key for this device".into(),
+            ));
+        }
+        return Ok(None);
+    }
+
+    if state.pairing_group.read().await.is_some() {
+        return Err(DecentPasteError::Pairing(
+            "Already in a pairing group".into(),
+        ));
+    }
+    let info = GroupInfo::from(&invite.group);
+    state
+        .apply_pairing_group(invite.group, &local_peer_id)
+        .await?;
+    state.broadcast_group_state(&local_peer_id).await;
+
+    info!("Joined pairing group {}", info.group_id);
+    Ok(Some(info))
+}
+
 // Clipboard operations
 #[tauri::command]
 pub async fn get_clipboard_history(
@@ -459,12 +1408,238 @@ pub async fn set_clipboard(app_handle: AppHandle, content: String) -> Result<()>
 
 #[tauri::command]
 pub async fn clear_clipboard_history(state: State<'_, AppState>) -> Result<()> {
-    {
+    let cleared = {
         let mut history = state.clipboard_history.write().await;
-        history.clear();
-    }
+        std::mem::take(&mut *history)
+    };
     // Flush-on-write: persist cleared history to vault immediately
     state.flush_clipboard_history().await?;
+
+    // With history sync on, the deletion reaches connected devices right away
+    // (offline ones get it when they reconnect)
+    if let Err(e) = state.record_history_deletions(&cleared).await {
+        warn!("Failed to record history deletions: {}", e);
+    }
+    let ready_peers: Vec<String> = state.ready_peers.read().await.iter().cloned().collect();
+    for peer_id in ready_peers {
+        if state.history_sync_allowed(&peer_id).await {
+            if let Err(e) = state.start_history_sync(&peer_id).await {
+                warn!("Failed to start history sync with {}: {}", peer_id, e);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Send one clipboard item to a single paired device.
+///
+/// `content` defaults to the current system clipboard. The message is encrypted
+/// with the pairwise secret and sent point-to-point; it is also queued for the
+/// peer, so it is delivered on reconnect if the peer is offline.
+///
+/// With `ttl_secs` the item self-destructs: the receiver clears it from its
+/// clipboard after that many seconds, neither side adds it to history, and queued
+/// copies are dropped once it expires.
+#[tauri::command]
+pub async fn send_to_peer(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+    peer_id: String,
+    content: Option<String>,
+    ttl_secs: Option<u64>,
+) -> Result<ClipboardEntry> {
+    use crate::clipboard::DeliveryStatus;
+    use crate::network::protocol::HistoryRetention;
+    use crate::security::hash_content;
+    use crate::state::MAX_SELF_DESTRUCT_SECS;
+    use tauri::Emitter;
+    use tauri_plugin_clipboard_manager::ClipboardExt;
+
+    let content = match content {
+        Some(content) => content,
+        None => app_handle
+            .clipboard()
+            .read_text()
+            .map_err(|e| DecentPasteError::Clipboard(e.to_string()))?,
+    };
+    if content.is_empty() {
+        return Err(DecentPasteError::InvalidInput("Clipboard is empty".into()));
+    }
+    if content.len() > MAX_CLIPBOARD_SIZE {
+        return Err(DecentPasteError::InvalidInput(
+            "Clipboard content too large (max 1MB)".into(),
+        ));
+    }
+
+    if ttl_secs.is_some_and(|ttl| ttl == 0 || ttl > MAX_SELF_DESTRUCT_SECS) {
+        return Err(DecentPasteError::InvalidInput(
+            "Self-destruct time must be between 1 second and 1 hour".into(),
+        ));
+    }
+
+    let policy = state
+        .peer_sync_policy(&peer_id)
+        .await
+        .ok_or_else(|| DecentPasteError::PeerNotFound(peer_id.clone()))?;
+    if !policy.send {
+        return Err(DecentPasteError::InvalidInput(
+            "Sending to this device is disabled".into(),
+        ));
+    }
+
+    let content_hash = hash_content(&content);
+    let hlc = state.hlc.now();
+    let entry = {
+        let device_identity = state.device_identity.read().await;
+        let identity = device_identity
+            .as_ref()
+            .ok_or(DecentPasteError::NotInitialized)?;
+        let message = state
+            .prepare_clipboard_message_for_peer(
+                identity,
+                &peer_id,
+                &content,
+                &content_hash,
+                hlc,
+                ttl_secs,
+                HistoryRetention::Keep,
+            )
+            .await?;
+
+        // Queue first, so the message survives if the peer is offline
+        if !state.is_temporary_pairing(&peer_id).await {
+            state
+                .store_buffered_message(&peer_id, message.clone())
+                .await;
+        }
+        {
+            let tx = state.network_command_tx.read().await;
+            let tx = tx.as_ref().ok_or(DecentPasteError::NotInitialized)?;
+            tx.send(NetworkCommand::SendDirectClipboard {
+                peer_id: peer_id.clone(),
+                message,
+            })
+            .await
+            .map_err(|_| DecentPasteError::ChannelSend)?;
+        }
+
+        let mut entry =
+            ClipboardEntry::new_local(content, hlc, &identity.device_id, &identity.device_name);
+        entry.target_peer_id = Some(peer_id.clone());
+        entry.delivery = [(peer_id.clone(), DeliveryStatus::Pending)].into();
+        entry
+    };
+
+    state.count_pairing_messages(&[peer_id.clone()]).await?;
+    crate::expire_temporary_pairings(&app_handle).await;
+
+    // An offline peer may get it sooner through a relay
+    {
+        let device_identity = state.device_identity.read().await;
+        if let Some(identity) = device_identity.as_ref() {
+            state
+                .relay_to_offline_peers(
+                    identity,
+                    &[peer_id.clone()],
+                    &entry.content,
+                    &entry.content_hash,
+                    hlc,
+                    ttl_secs,
+                    HistoryRetention::Keep,
+                )
+                .await;
+        }
+    }
+
+    // Self-destructing items are never kept in history
+    if ttl_secs.is_none() {
+        state.add_clipboard_entry(entry.clone()).await;
+        let _ = app_handle.emit("clipboard-sent", entry.clone());
+    }
+    info!("Sent clipboard to {}", peer_id);
+    Ok(entry)
+}
+
+/// Put a history entry on the system clipboard (e.g. one kept in history by
+/// `receive_history_only`). Without `entry_id`, the latest received entry is used.
+#[tauri::command]
+pub async fn apply_history_entry(
+    app_handle: AppHandle,
+    entry_id: Option<String>,
+) -> Result<ClipboardEntry> {
+    crate::apply_history_entry(&app_handle, entry_id.as_deref()).await
+}
+
+/// Restore the clipboard content that the last item received from another device
+/// replaced. Fails if there is nothing to undo.
+#[tauri::command]
+pub async fn undo_last_remote_apply(app_handle: AppHandle) -> Result<()> {
+    if crate::undo_last_remote_apply(&app_handle).await? {
+        Ok(())
+    } else {
+        Err(DecentPasteError::InvalidInput("Nothing to undo".into()))
+    }
+}
+
+/// Ask a paired device for its latest clipboard entry.
+///
+/// Returns the request id. The entry arrives as `clipboard-received` and is put
+/// on the clipboard; a refusal or timeout is emitted as `clipboard-pull-failed`.
+#[tauri::command]
+pub async fn pull_clipboard(state: State<'_, AppState>, peer_id: String) -> Result<String> {
+    use crate::network::protocol::PullMessage;
+    use crate::state::ClipboardPull;
+
+    let policy = state
+        .peer_sync_policy(&peer_id)
+        .await
+        .ok_or_else(|| DecentPasteError::PeerNotFound(peer_id.clone()))?;
+    if !policy.receive {
+        return Err(DecentPasteError::InvalidInput(
+            "Receiving from this device is disabled".into(),
+        ));
+    }
+    if !state.ready_peers.read().await.contains(&peer_id) {
+        return Err(DecentPasteError::InvalidInput("Device is offline".into()));
+    }
+
+    let request_id = uuid::Uuid::new_v4().to_string();
+    state
+        .add_clipboard_pull(ClipboardPull {
+            request_id: request_id.clone(),
+            peer_id: peer_id.clone(),
+            incoming: false,
+            created_at: chrono::Utc::now(),
+        })
+        .await;
+
+    let tx = state.network_command_tx.read().await;
+    let tx = tx.as_ref().ok_or(DecentPasteError::NotInitialized)?;
+    tx.send(NetworkCommand::SendPullMessage {
+        peer_id,
+        message: PullMessage::Request {
+            request_id: request_id.clone(),
+        },
+    })
+    .await
+    .map_err(|_| DecentPasteError::ChannelSend)?;
+    Ok(request_id)
+}
+
+/// Approve or decline a paired device's clipboard pull (see
+/// `AppSettings::pull_requires_approval`).
+#[tauri::command]
+pub async fn respond_to_clipboard_pull(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+    request_id: String,
+    accept: bool,
+) -> Result<()> {
+    let pull = state
+        .take_clipboard_pull(&request_id, None, true)
+        .await
+        .ok_or_else(|| DecentPasteError::InvalidInput("Unknown clipboard pull".into()))?;
+    crate::answer_clipboard_pull(&app_handle, &pull.peer_id, request_id, accept).await;
     Ok(())
 }
 
@@ -477,6 +1652,54 @@ pub async fn get_settings(state: State<'_, AppState>) -> Result<AppSettings> {
 
 #[tauri::command]
 pub async fn update_settings(state: State<'_, AppState>, settings: AppSettings) -> Result<()> {
+    use crate::clipboard::filter::{validate_pattern, SensitiveDetector, SensitiveFilter};
+    use crate::clipboard::monitor::{MAX_PRIMARY_DEBOUNCE_MS, MIN_PRIMARY_DEBOUNCE_MS};
+    use crate::state::{
+        MAX_PAIRING_TIMEOUT_SECS, MAX_SYNC_BUFFER_SIZE, MAX_SYNC_BUFFER_TTL_SECS,
+        MIN_PAIRING_TIMEOUT_SECS,
+    };
+
+    if !(MIN_PAIRING_TIMEOUT_SECS..=MAX_PAIRING_TIMEOUT_SECS)
+        .contains(&settings.pairing_timeout_secs)
+    {
+        return Err(DecentPasteError::InvalidInput(format!(
+            "Pairing timeout must be between {} seconds and {} minutes",
+            MIN_PAIRING_TIMEOUT_SECS,
+            MAX_PAIRING_TIMEOUT_SECS / 60
+        )));
+    }
+    if settings.sync_buffer_size == 0 || settings.sync_buffer_size > MAX_SYNC_BUFFER_SIZE {
+        return Err(DecentPasteError::InvalidInput(format!(
+            "Offline queue size must be between 1 and {}",
+            MAX_SYNC_BUFFER_SIZE
+        )));
+    }
+    if settings.sync_buffer_ttl_secs == 0
+        || settings.sync_buffer_ttl_secs > MAX_SYNC_BUFFER_TTL_SECS
+    {
+        return Err(DecentPasteError::InvalidInput(
+            "Offline queue TTL must be between 1 second and 7 days".into(),
+        ));
+    }
+
+    if !(MIN_PRIMARY_DEBOUNCE_MS..=MAX_PRIMARY_DEBOUNCE_MS).contains(&settings.primary_debounce_ms)
+    {
+        return Err(DecentPasteError::InvalidInput(
+            "Selection debounce must be between 100 ms and 10 seconds".into(),
+        ));
+    }
+
+    for rule in &settings.sensitive_rules {
+        if let SensitiveDetector::Regex { ref pattern } = rule.detector {
+            validate_pattern(pattern).map_err(|e| {
+                DecentPasteError::InvalidInput(format!(
+                    "Invalid pattern for '{}': {}",
+                    rule.name, e
+                ))
+            })?;
+        }
+    }
+
     // Check if device name changed
     let old_device_name = {
         let current = state.settings.read().await;
@@ -491,6 +1714,10 @@ pub async fn update_settings(state: State<'_, AppState>, settings: AppSettings)
         let mut current = state.settings.write().await;
         *current = settings.clone();
     }
+    *state.sensitive_filter.write().await = SensitiveFilter::new(&settings.sensitive_rules);
+
+    // Apply a smaller queue depth or TTL right away
+    state.prune_message_buffers().await;
 
     // If device name changed, broadcast the new name to all peers
     if name_changed {
@@ -533,20 +1760,26 @@ pub async fn get_device_info(state: State<'_, AppState>) -> Result<DeviceInfo> {
     if let Some(ref id) = *identity {
         Ok(DeviceInfo {
             device_id: id.device_id.clone(),
-            peer_id: None, // Will be set from network
+            peer_id: state.local_peer_id.read().await.clone(),
         })
     } else {
         Err(DecentPasteError::NotInitialized)
     }
 }
 
+/// Clipboard messages rejected by replay protection since the app started.
+#[tauri::command]
+pub async fn get_replay_stats(state: State<'_, AppState>) -> Result<crate::security::ReplayStats> {
+    Ok(state.replay_counters.snapshot())
+}
+
 // Get active pairing sessions
 #[tauri::command]
 pub async fn get_pairing_sessions(state: State<'_, AppState>) -> Result<Vec<PairingSession>> {
     let sessions = state.pairing_sessions.read().await;
     Ok(sessions
         .iter()
-        .filter(|s| !s.is_expired())
+        .filter(|s| s.state != PairingState::Expired)
         .cloned()
         .collect())
 }
@@ -910,6 +2143,36 @@ pub async fn unlock_vault(
         *paired_peers = peers;
     }
 
+    if let Ok(group) = manager.get_pairing_group() {
+        let mut pairing_group = state.pairing_group.write().await;
+        *pairing_group = group;
+    }
+
+    if let Ok(unpairs) = manager.get_pending_unpairs() {
+        let mut pending_unpairs = state.pending_unpairs.write().await;
+        *pending_unpairs = unpairs;
+    }
+
+    if let Ok(rotations) = manager.get_pending_rotations() {
+        let mut pending_rotations = state.pending_rotations.write().await;
+        *pending_rotations = rotations;
+    }
+
+    if let Ok(buffers) = manager.get_message_buffers() {
+        let mut message_buffers = state.message_buffers.write().await;
+        *message_buffers = buffers;
+    }
+
+    if let Ok(buffers) = manager.get_relay_buffers() {
+        let mut relay_buffers = state.relay_buffers.write().await;
+        *relay_buffers = buffers;
+    }
+
+    if let Ok(tombstones) = manager.get_history_tombstones() {
+        let mut history_tombstones = state.history_tombstones.write().await;
+        *history_tombstones = tombstones;
+    }
+
     if let Ok(history) = manager.get_clipboard_history() {
         let mut clipboard_history = state.clipboard_history.write().await;
         *clipboard_history = history;
@@ -949,6 +2212,13 @@ pub async fn lock_vault(app_handle: AppHandle, state: State<'_, AppState>) -> Re
     // Flush current state to vault before locking (safety net - data should already be persisted)
     let _ = state.flush_all_to_vault().await;
 
+    // The clipboard text kept for undo is never persisted; don't keep it while locked
+    if let Some(monitor) = state.clipboard_monitor.read().await.as_ref() {
+        monitor.clear_overwritten().await;
+    }
+    // Likewise an unanswered group invite (it carries the group key)
+    *state.pending_group_invite.write().await = None;
+
     // Lock the vault (clears encryption key from memory)
     {
         let mut manager = state.vault_manager.write().await;
@@ -1016,6 +2286,34 @@ pub async fn reset_vault(app_handle: AppHandle, state: State<'_, AppState>) -> R
         let mut paired_peers = state.paired_peers.write().await;
         paired_peers.clear();
     }
+    {
+        let mut pairing_group = state.pairing_group.write().await;
+        *pairing_group = None;
+    }
+    {
+        let mut pending_group_invite = state.pending_group_invite.write().await;
+        *pending_group_invite = None;
+    }
+    {
+        let mut pending_unpairs = state.pending_unpairs.write().await;
+        pending_unpairs.clear();
+    }
+    {
+        let mut pending_rotations = state.pending_rotations.write().await;
+        pending_rotations.clear();
+    }
+    {
+        let mut message_buffers = state.message_buffers.write().await;
+        message_buffers.clear();
+    }
+    {
+        let mut relay_buffers = state.relay_buffers.write().await;
+        relay_buffers.clear();
+    }
+    {
+        let mut history_tombstones = state.history_tombstones.write().await;
+        history_tombstones.clear();
+    }
     {
         let mut clipboard_history = state.clipboard_history.write().await;
         clipboard_history.clear();
@@ -1309,14 +2607,14 @@ async fn share_clipboard_content(
     state: State<'_, AppState>,
     content: String,
 ) -> Result<()> {
-    use crate::clipboard::ClipboardEntry;
-    use crate::network::{ClipboardMessage, NetworkCommand};
-    use crate::security::{encrypt_content, hash_content};
-    use chrono::Utc;
+    use crate::clipboard::{ClipboardEntry, ClipboardSelection, DeliveryStatus};
+    use crate::network::protocol::HistoryRetention;
+    use crate::network::NetworkCommand;
+    use crate::security::hash_content;
+    use crate::state::OutgoingClipboard;
     use tauri::Emitter;
 
-    // Limit clipboard content size to prevent memory exhaustion (1MB max)
-    const MAX_CLIPBOARD_SIZE: usize = 1024 * 1024;
+    // Limit clipboard content size to prevent memory exhaustion
     if content.len() > MAX_CLIPBOARD_SIZE {
         return Err(DecentPasteError::InvalidInput(
             "Clipboard content too large (max 1MB)".into(),
@@ -1331,35 +2629,40 @@ async fn share_clipboard_content(
         .as_ref()
         .ok_or(DecentPasteError::NotInitialized)?;
 
-    // Check if we have any paired peers
-    let paired_peers = state.paired_peers.read().await;
-    if paired_peers.is_empty() {
+    // Encrypt for EACH paired peer with their specific shared secret
+    // (or once for our pairing group, with the group key)
+    let hlc = state.hlc.now();
+    let outgoing = state
+        .prepare_clipboard_messages(
+            identity,
+            &content,
+            &content_hash,
+            hlc,
+            ClipboardSelection::Clipboard,
+            HistoryRetention::Keep,
+        )
+        .await;
+    if outgoing.is_empty() {
         return Err(DecentPasteError::Pairing("No paired peers".into()));
     }
 
-    // Encrypt and send to EACH paired peer with their specific shared secret
-    let tx = state.network_command_tx.read().await;
     let mut broadcast_count = 0;
-
-    for peer in paired_peers.iter() {
-        let encrypted = encrypt_content(content.as_bytes(), &peer.shared_secret)
-            .map_err(|e| DecentPasteError::Encryption(e.to_string()))?;
-
-        let msg = ClipboardMessage {
-            id: uuid::Uuid::new_v4().to_string(),
-            content_hash: content_hash.clone(),
-            encrypted_content: encrypted,
-            timestamp: Utc::now(),
-            origin_device_id: identity.device_id.clone(),
-            origin_device_name: identity.device_name.clone(),
-        };
-
-        // Send via network
-        if let Some(tx) = tx.as_ref() {
-            tx.send(NetworkCommand::BroadcastClipboard { message: msg })
-                .await
-                .map_err(|_| DecentPasteError::ChannelSend)?;
-            broadcast_count += 1;
+    let mut all_recipients = Vec::new();
+    {
+        let tx = state.network_command_tx.read().await;
+        for OutgoingClipboard {
+            message: msg,
+            recipients,
+        } in outgoing
+        {
+            // Send via network
+            if let Some(tx) = tx.as_ref() {
+                tx.send(NetworkCommand::BroadcastClipboard { message: msg })
+                    .await
+                    .map_err(|_| DecentPasteError::ChannelSend)?;
+                broadcast_count += 1;
+                all_recipients.extend(recipients);
+            }
         }
     }
 
@@ -1367,8 +2670,16 @@ async fn share_clipboard_content(
         return Err(DecentPasteError::ChannelSend);
     }
 
-    // Add to history (once, not per peer)
-    let entry = ClipboardEntry::new_local(content, &identity.device_id, &identity.device_name);
+    state.count_pairing_messages(&all_recipients).await?;
+    crate::expire_temporary_pairings(&app_handle).await;
+
+    // Add to history (once, not per peer), pending at every recipient
+    let mut entry =
+        ClipboardEntry::new_local(content, hlc, &identity.device_id, &identity.device_name);
+    entry.delivery = all_recipients
+        .iter()
+        .map(|peer_id| (peer_id.clone(), DeliveryStatus::Pending))
+        .collect();
     state.add_clipboard_entry(entry.clone()).await;
 
     // Emit to frontend
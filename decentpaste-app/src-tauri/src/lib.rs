@@ -10,22 +10,30 @@ pub mod vault;
 
 use chrono::Utc;
 use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
 use tauri::{AppHandle, Emitter, Manager};
 use tokio::sync::mpsc;
 use tracing::{debug, error, info, warn};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
-use clipboard::{ClipboardChange, ClipboardEntry, ClipboardMonitor};
+use clipboard::{
+    ClipboardChange, ClipboardEntry, ClipboardMonitor, ClipboardSelection, DeliveryStatus,
+    SensitiveAction, SensitiveFilter,
+};
+use network::protocol::{GroupPayload, HistoryRetention, PAIRING_TIMEOUT_ERROR};
 use network::{ClipboardMessage, NetworkCommand, NetworkEvent, NetworkManager};
 #[cfg(any(target_os = "android", target_os = "ios"))]
 use state::PendingClipboard;
-use state::AppState;
-use storage::{init_data_dir, load_settings};
+use state::{AppState, GroupInvite, OutgoingClipboard};
+use storage::{init_data_dir, load_settings, PairingGroup};
 use vault::{VaultManager, VaultStatus};
 
 /// Track whether network services have been started (to prevent double-start)
 static SERVICES_STARTED: AtomicBool = AtomicBool::new(false);
 
+/// How often pairing sessions are checked against the pairing timeout.
+const PAIRING_SWEEP_INTERVAL: Duration = Duration::from_secs(5);
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     // Initialize tracing
@@ -94,24 +102,35 @@ pub fn run() {
                     None,
                 ))?;
 
-                // Global shortcut to show/focus window (CommandOrControl+Shift+D)
+                // Global shortcuts: show/focus window (CommandOrControl+Shift+D), send the
+                // clipboard to one device (CommandOrControl+Alt+D), apply the latest
+                // received item (CommandOrControl+Alt+V) and undo the last remote
+                // overwrite (CommandOrControl+Alt+Z)
                 use tauri_plugin_global_shortcut::{
-                    Builder as GlobalShortcutBuilder, ShortcutState,
+                    Builder as GlobalShortcutBuilder, Code, Modifiers, ShortcutState,
                 };
-                match GlobalShortcutBuilder::new()
-                    .with_shortcuts(["CommandOrControl+Shift+D"])
-                {
+                match GlobalShortcutBuilder::new().with_shortcuts([
+                    "CommandOrControl+Shift+D",
+                    "CommandOrControl+Alt+D",
+                    "CommandOrControl+Alt+V",
+                    "CommandOrControl+Alt+Z",
+                ]) {
                     Ok(builder) => {
                         if let Err(e) = app.handle().plugin(
                             builder
-                                .with_handler(|app, _shortcut, event| {
-                                    if event.state == ShortcutState::Pressed {
-                                        if let Some(window) =
-                                            app.get_webview_window("main")
-                                        {
-                                            let _ = window.show();
-                                            let _ = window.set_focus();
-                                        }
+                                .with_handler(|app, shortcut, event| {
+                                    if event.state != ShortcutState::Pressed {
+                                        return;
+                                    }
+                                    if shortcut.key == Code::KeyV {
+                                        tray::apply_latest_received(app);
+                                    } else if shortcut.key == Code::KeyZ {
+                                        tray::undo_remote_apply(app);
+                                    } else if shortcut.mods.contains(Modifiers::ALT) {
+                                        tray::request_send_to_peer(app);
+                                    } else if let Some(window) = app.get_webview_window("main") {
+                                        let _ = window.show();
+                                        let _ = window.set_focus();
                                     }
                                 })
                                 .build(),
@@ -142,16 +161,42 @@ pub fn run() {
             commands::get_discovered_peers,
             commands::get_paired_peers,
             commands::remove_paired_peer,
+            commands::get_peer_fingerprint,
+            commands::set_peer_verified,
+            commands::set_peer_sync_policy,
+            commands::set_pairing_expiry,
+            commands::start_nearby_send,
+            commands::respond_to_nearby_send,
+            commands::confirm_nearby_send,
+            commands::cancel_nearby_send,
+            commands::rotate_identity,
             commands::initiate_pairing,
+            commands::create_pairing_uri,
+            commands::pair_with_uri,
+            commands::enable_passphrase_pairing,
+            commands::disable_passphrase_pairing,
+            commands::initiate_passphrase_pairing,
             commands::respond_to_pairing,
             commands::confirm_pairing,
             commands::cancel_pairing,
+            commands::get_group,
+            commands::create_group,
+            commands::invite_to_group,
+            commands::remove_group_member,
+            commands::leave_group,
+            commands::respond_to_group_invite,
             commands::get_clipboard_history,
+            commands::send_to_peer,
+            commands::pull_clipboard,
+            commands::apply_history_entry,
+            commands::undo_last_remote_apply,
+            commands::respond_to_clipboard_pull,
             commands::set_clipboard,
             commands::clear_clipboard_history,
             commands::get_settings,
             commands::update_settings,
             commands::get_device_info,
+            commands::get_replay_stats,
             commands::get_pairing_sessions,
             // Vault commands
             commands::get_vault_status,
@@ -345,6 +390,7 @@ async fn initialize_app(
         let mut s = state.settings.write().await;
         *s = settings.clone();
     }
+    *state.sensitive_filter.write().await = SensitiveFilter::new(&settings.sensitive_rules);
 
     // Check vault status
     let vault_exists = VaultManager::exists().unwrap_or(false);
@@ -410,6 +456,10 @@ pub async fn start_network_services(
     };
     info!("Loaded libp2p keypair from vault");
 
+    // Our PeerId is fixed by the keypair; listen addresses arrive as the swarm binds
+    *state.local_peer_id.write().await = Some(libp2p_keypair.public().to_peer_id().to_string());
+    state.listen_addresses.write().await.clear();
+
     // Create channels
     let (network_cmd_tx, network_cmd_rx) = mpsc::channel::<NetworkCommand>(100);
     let (network_event_tx, mut network_event_rx) = mpsc::channel::<NetworkEvent>(100);
@@ -423,6 +473,12 @@ pub async fn start_network_services(
 
     // Get device name for network identification
     let device_name = identity.device_name.clone();
+    // Peers that haven't seen our identity rotation yet would flag the new key as a change
+    let identity_public_key = if state.pending_rotations.read().await.is_empty() {
+        Some(identity.public_key.clone())
+    } else {
+        None
+    };
 
     // Start network manager
     let network_event_tx_clone = network_event_tx.clone();
@@ -432,6 +488,7 @@ pub async fn start_network_services(
             network_event_tx_clone,
             libp2p_keypair,
             device_name,
+            identity_public_key,
         )
         .await
         {
@@ -451,9 +508,14 @@ pub async fn start_network_services(
     // Start clipboard monitor (shared via Arc for echo prevention)
     let clipboard_monitor =
         std::sync::Arc::new(ClipboardMonitor::new(settings.clipboard_poll_interval_ms));
+    #[cfg(target_os = "linux")]
+    let primary_tx = clipboard_tx.clone();
     clipboard_monitor
         .start(app_handle.clone(), clipboard_tx)
         .await;
+    #[cfg(target_os = "linux")]
+    clipboard_monitor.start_primary(app_handle.clone(), primary_tx);
+    *state.clipboard_monitor.write().await = Some(clipboard_monitor.clone());
     let clipboard_monitor_network = clipboard_monitor.clone();
 
     // Handle clipboard changes - broadcast to network
@@ -472,67 +534,137 @@ pub async fn start_network_services(
             // The monitor already filters by hash change, and is_local ensures
             // we only broadcast user actions (not received clipboard updates)
             if change.is_local {
+                // Sensitive content: the most restrictive matching rule decides
+                let (sensitive, expire_secs) = {
+                    let settings = state.settings.read().await;
+                    let sensitive = if settings.sensitive_filter_enabled {
+                        state
+                            .sensitive_filter
+                            .read()
+                            .await
+                            .classify(&change.content)
+                    } else {
+                        None
+                    };
+                    (sensitive, settings.sensitive_expire_secs)
+                };
+                if let Some(ref matched) = sensitive {
+                    info!(
+                        "Local copy matched sensitive-content rule '{}' ({:?})",
+                        matched.rule_id, matched.action
+                    );
+                    let _ = app_handle_clipboard.emit(
+                        "sensitive-content-detected",
+                        serde_json::json!({
+                            "ruleId": matched.rule_id,
+                            "ruleName": matched.rule_name,
+                            "action": matched.action,
+                        }),
+                    );
+                    if matched.action == SensitiveAction::DontSync {
+                        continue;
+                    }
+                }
+                // Receiving devices apply the same history rule
+                let history = match sensitive.as_ref().map(|matched| matched.action) {
+                    Some(SensitiveAction::NoHistory) => HistoryRetention::Skip,
+                    Some(SensitiveAction::AutoExpire) => {
+                        HistoryRetention::Expire { secs: expire_secs }
+                    }
+                    _ => HistoryRetention::Keep,
+                };
+
                 // Get device info
                 let device_identity = state.device_identity.read().await;
                 if let Some(ref identity) = *device_identity {
-                    // Check if we have any paired peers
-                    let paired_peers = state.paired_peers.read().await;
-                    if paired_peers.is_empty() {
+                    // Encrypt for EACH paired peer with their specific shared secret
+                    // (or once for our pairing group, with the group key).
+                    // ALSO buffer for each recipient - this handles the case where a peer goes
+                    // offline mid-transmission (race condition). Sync will deliver it later.
+                    let hlc = state.hlc.now();
+                    let outgoing = state
+                        .prepare_clipboard_messages(
+                            identity,
+                            &change.content,
+                            &change.content_hash,
+                            hlc,
+                            change.selection,
+                            history,
+                        )
+                        .await;
+                    if outgoing.is_empty() {
                         continue;
                     }
 
-                    // Encrypt and broadcast to EACH paired peer with their specific shared secret
-                    // ALSO buffer for each peer - this handles the case where a peer goes offline
-                    // mid-transmission (race condition). Sync will deliver the message later.
                     let mut broadcast_count = 0;
-                    for peer in paired_peers.iter() {
-                        match security::encrypt_content(
-                            change.content.as_bytes(),
-                            &peer.shared_secret,
-                        ) {
-                            Ok(encrypted) => {
-                                let msg = ClipboardMessage {
-                                    id: uuid::Uuid::new_v4().to_string(),
-                                    content_hash: change.content_hash.clone(),
-                                    encrypted_content: encrypted,
-                                    timestamp: Utc::now(),
-                                    origin_device_id: identity.device_id.clone(),
-                                    origin_device_name: identity.device_name.clone(),
-                                };
-
-                                // 1. Send via gossipsub (fire-and-forget)
-                                if let Err(e) = network_cmd_tx_clipboard
-                                    .send(NetworkCommand::BroadcastClipboard {
-                                        message: msg.clone(),
-                                    })
-                                    .await
-                                {
-                                    error!("Failed to send clipboard to network: {}", e);
-                                } else {
-                                    broadcast_count += 1;
-                                }
+                    let mut all_recipients = Vec::new();
+                    for OutgoingClipboard {
+                        message: msg,
+                        recipients,
+                    } in outgoing
+                    {
+                        // 1. Send via gossipsub (fire-and-forget)
+                        if let Err(e) = network_cmd_tx_clipboard
+                            .send(NetworkCommand::BroadcastClipboard {
+                                message: msg.clone(),
+                            })
+                            .await
+                        {
+                            error!("Failed to send clipboard to network: {}", e);
+                        } else {
+                            broadcast_count += 1;
+                        }
 
-                                // 2. ALWAYS buffer for this peer (even if they appear online).
-                                // This handles the race condition where peer goes offline
-                                // mid-transmission. Sync ensures eventual delivery.
-                                state.store_buffered_message(&peer.peer_id, msg).await;
-                            }
-                            Err(e) => {
-                                error!(
-                                    "Failed to encrypt clipboard for peer {}: {}",
-                                    peer.peer_id, e
-                                );
+                        // 2. ALWAYS buffer for each recipient (even if they appear online).
+                        // This handles the race condition where peer goes offline
+                        // mid-transmission. Sync ensures eventual delivery.
+                        // Temporary pairings only get live messages, never a backlog.
+                        for peer_id in &recipients {
+                            if !state.is_temporary_pairing(peer_id).await {
+                                state.store_buffered_message(peer_id, msg.clone()).await;
                             }
                         }
+
+                        if let Err(e) = state.count_pairing_messages(&recipients).await {
+                            warn!("Failed to update pairing message counts: {}", e);
+                        }
+                        all_recipients.extend(recipients);
+                    }
+                    expire_temporary_pairings(&app_handle_clipboard).await;
+
+                    // 3. Offline recipients may get it sooner through a relay
+                    // (not worth it for transient PRIMARY selections)
+                    if change.selection == ClipboardSelection::Clipboard {
+                        state
+                            .relay_to_offline_peers(
+                                identity,
+                                &all_recipients,
+                                &change.content,
+                                &change.content_hash,
+                                hlc,
+                                None,
+                                history,
+                            )
+                            .await;
                     }
 
-                    if broadcast_count > 0 {
-                        // Add to history (once, not per peer)
-                        let entry = ClipboardEntry::new_local(
+                    let action = sensitive.map(|matched| matched.action);
+                    if broadcast_count > 0 && action != Some(SensitiveAction::NoHistory) {
+                        // Add to history (once, not per peer), pending at every recipient
+                        let mut entry = ClipboardEntry::new_local(
                             change.content,
+                            hlc,
                             &identity.device_id,
                             &identity.device_name,
                         );
+                        entry.delivery = all_recipients
+                            .into_iter()
+                            .map(|peer_id| (peer_id, DeliveryStatus::Pending))
+                            .collect();
+                        if action == Some(SensitiveAction::AutoExpire) {
+                            entry.expires_at =
+                                Some(Utc::now() + chrono::Duration::seconds(expire_secs as i64));
+                        }
                         state.add_clipboard_entry(entry.clone()).await;
 
                         // Emit to frontend
@@ -543,6 +675,9 @@ pub async fn start_network_services(
         }
     });
 
+    // Expire pairing sessions that outlive the pairing timeout
+    spawn_pairing_session_sweeper(app_handle.clone(), network_cmd_tx.clone());
+
     // Handle network events
     let app_handle_network = app_handle.clone();
     let network_cmd_tx_events = network_cmd_tx.clone();
@@ -561,6 +696,21 @@ pub async fn start_network_services(
                     let _ = app_handle_network.emit("network-status", status);
                 }
 
+                NetworkEvent::ListenAddressAdded(address) => {
+                    let mut addresses = state.listen_addresses.write().await;
+                    if !addresses.contains(&address) {
+                        addresses.push(address);
+                    }
+                }
+
+                NetworkEvent::ListenAddressRemoved(address) => {
+                    state
+                        .listen_addresses
+                        .write()
+                        .await
+                        .retain(|a| a != &address);
+                }
+
                 NetworkEvent::PeerDiscovered(peer) => {
                     // Check if this peer is already paired
                     let is_paired = {
@@ -666,6 +816,14 @@ pub async fn start_network_services(
                     }
                 }
 
+                NetworkEvent::PeerIdentityAnnounced {
+                    peer_id,
+                    public_key,
+                } => {
+                    check_peer_identity_key(&app_handle_network, &state, &peer_id, &public_key)
+                        .await;
+                }
+
                 NetworkEvent::PeerConnected(peer) => {
                     let _ = app_handle_network.emit("peer-connected", &peer);
 
@@ -707,7 +865,73 @@ pub async fn start_network_services(
                     // When a peer becomes ready, we request sync from them.
                     // This is bi-directional: both peers request sync from each other.
                     // This ensures both sides receive any missed messages.
-                    if state.is_peer_paired(peer_id).await {
+                    // Deliver an unpair notification queued while the peer was offline
+                    if let Some(proof) = state.pending_unpair_proof(peer_id).await {
+                        debug!("Resending unpair notification to {}", peer_id);
+                        if let Err(e) = network_cmd_tx
+                            .send(NetworkCommand::SendUnpair {
+                                peer_id: peer_id.clone(),
+                                proof,
+                            })
+                            .await
+                        {
+                            warn!("Failed to send unpair to {}: {}", peer_id, e);
+                        }
+                    }
+
+                    // Likewise for an identity rotation the peer hasn't acknowledged
+                    if let Some(rotation) = state.pending_rotation(peer_id).await {
+                        debug!("Resending identity rotation to {}", peer_id);
+                        if let Err(e) = network_cmd_tx
+                            .send(NetworkCommand::SendIdentityRotation {
+                                peer_id: peer_id.clone(),
+                                old_peer_id: rotation.old_peer_id,
+                                encrypted_payload: rotation.encrypted_payload,
+                            })
+                            .await
+                        {
+                            warn!("Failed to send identity rotation to {}: {}", peer_id, e);
+                        }
+                    }
+
+                    // Group members that were offline during a membership change
+                    // catch up here; the epoch decides which side is current
+                    let is_group_member = state
+                        .pairing_group
+                        .read()
+                        .await
+                        .as_ref()
+                        .is_some_and(|g| g.is_member(peer_id));
+                    if is_group_member {
+                        if let Some(group) = state.pairing_group.read().await.clone() {
+                            let payload = GroupPayload::State(group);
+                            if let Err(e) = state.send_group_payload(peer_id, &payload).await {
+                                warn!("Failed to send group state to {}: {}", peer_id, e);
+                            }
+                        }
+                    }
+
+                    // Reconcile the full history (opt-in). One side starts, so the
+                    // exchange runs once per connection
+                    let starts_history_sync = state
+                        .local_peer_id
+                        .read()
+                        .await
+                        .as_deref()
+                        .is_some_and(|local| local < peer_id.as_str());
+                    if starts_history_sync && state.history_sync_allowed(peer_id).await {
+                        debug!("Starting history sync with {}", peer_id);
+                        if let Err(e) = state.start_history_sync(peer_id).await {
+                            warn!("Failed to start history sync with {}: {}", peer_id, e);
+                        }
+                    }
+
+                    // No point asking for missed content we wouldn't accept
+                    if state
+                        .peer_sync_policy(peer_id)
+                        .await
+                        .is_some_and(|policy| policy.receive)
+                    {
                         debug!("Peer {} is paired and ready, requesting sync", peer_id);
                         if let Err(e) = network_cmd_tx
                             .send(NetworkCommand::RequestSync {
@@ -737,6 +961,17 @@ pub async fn start_network_services(
                     peer_id,
                     request,
                 } => {
+                    // A paired peer pairing again with a different key is suspicious
+                    if state.is_peer_paired(&peer_id).await {
+                        check_peer_identity_key(
+                            &app_handle_network,
+                            &state,
+                            &peer_id,
+                            &request.public_key,
+                        )
+                        .await;
+                    }
+
                     // Capture peer addresses NOW before mDNS can expire during pairing flow
                     let peer_addresses = {
                         let discovered = state.discovered_peers.read().await;
@@ -748,15 +983,79 @@ pub async fn start_network_services(
                     };
 
                     // Store the initiator's public key for ECDH key derivation later
-                    let session =
+                    let mut session =
                         security::PairingSession::new(session_id.clone(), peer_id.clone(), false)
                             .with_peer_name(request.device_name.clone())
                             .with_peer_public_key(request.public_key.clone())
                             .with_peer_addresses(peer_addresses);
 
+                    // Pairing URI flow: the request proves knowledge of one of our
+                    // invite secrets, so accept without asking the user for a PIN
+                    if let Some(ref proof) = request.invite_proof {
+                        let invite = state
+                            .take_pairing_invite(&session_id, &request.public_key, proof)
+                            .await;
+                        let our_public_key = state
+                            .device_identity
+                            .read()
+                            .await
+                            .as_ref()
+                            .map(|i| (i.device_name.clone(), i.public_key.clone()));
+
+                        match (invite, our_public_key) {
+                            (Some(invite), Some((our_name, our_public_key))) => {
+                                info!("Accepted pairing URI request from {}", peer_id);
+                                session.state = security::PairingState::AwaitingPeerConfirmation;
+                                state.pairing_sessions.write().await.push(session);
+
+                                let response_proof = security::invite_response_proof(
+                                    invite.secret(),
+                                    &session_id,
+                                    &our_public_key,
+                                    &request.public_key,
+                                );
+                                if let Err(e) = network_cmd_tx
+                                    .send(NetworkCommand::SendPairingChallenge {
+                                        peer_id: peer_id.clone(),
+                                        session_id: session_id.clone(),
+                                        pin: String::new(),
+                                        device_name: our_name,
+                                        public_key: our_public_key,
+                                        invite_proof: Some(response_proof),
+                                    })
+                                    .await
+                                {
+                                    error!("Failed to answer pairing URI request: {}", e);
+                                }
+
+                                let _ = app_handle_network.emit(
+                                    "pairing-invite-accepted",
+                                    serde_json::json!({
+                                        "sessionId": session_id,
+                                        "peerId": peer_id,
+                                        "deviceName": request.device_name,
+                                    }),
+                                );
+                            }
+                            _ => {
+                                warn!(
+                                    "Rejecting pairing request from {} with unknown or expired invite",
+                                    peer_id
+                                );
+                                let _ = network_cmd_tx
+                                    .send(NetworkCommand::RejectPairing {
+                                        peer_id,
+                                        session_id,
+                                        reason: Some("Pairing link expired or invalid".into()),
+                                    })
+                                    .await;
+                            }
+                        }
+                        continue;
+                    }
+
+                    // Stale sessions are expired and pruned by the pairing session sweeper
                     let mut sessions = state.pairing_sessions.write().await;
-                    // Clean up expired sessions before adding a new one
-                    sessions.retain(|s| !s.is_expired());
                     sessions.push(session);
 
                     // Note: Background pairing notifications are not possible on mobile.
@@ -779,8 +1078,88 @@ pub async fn start_network_services(
                     pin,
                     peer_device_name,
                     peer_public_key,
+                    invite_proof,
                 } => {
                     let mut sessions = state.pairing_sessions.write().await;
+
+                    // Pairing URI flow: verify the responder instead of showing a PIN
+                    if let Some(session) = sessions
+                        .iter_mut()
+                        .find(|s| s.session_id == session_id && s.invite_secret.is_some())
+                    {
+                        let our_public_key = state
+                            .device_identity
+                            .read()
+                            .await
+                            .as_ref()
+                            .map(|i| (i.device_name.clone(), i.public_key.clone()));
+                        let (our_name, our_public_key) = match our_public_key {
+                            Some(identity) => identity,
+                            None => continue,
+                        };
+
+                        let fingerprint_ok = session.expected_fingerprint.as_deref()
+                            == Some(security::public_key_fingerprint(&peer_public_key).as_str());
+                        let proof_ok = invite_proof.as_deref().is_some_and(|proof| {
+                            session.invite_secret.as_deref().is_some_and(|secret| {
+                                security::verify_invite_response_proof(
+                                    secret,
+                                    &session_id,
+                                    &peer_public_key,
+                                    &our_public_key,
+                                    proof,
+                                )
+                            })
+                        });
+                        let peer_id = session.peer_id.clone();
+                        // The secret is single-use either way
+                        session.invite_secret = None;
+
+                        if !(fingerprint_ok && proof_ok) {
+                            warn!(
+                                "Pairing URI verification failed for {} (fingerprint ok: {}, proof ok: {})",
+                                peer_id, fingerprint_ok, proof_ok
+                            );
+                            session.state =
+                                security::PairingState::Failed("Verification failed".into());
+                            drop(sessions);
+                            let _ = network_cmd_tx
+                                .send(NetworkCommand::SendPairingConfirm {
+                                    peer_id,
+                                    session_id: session_id.clone(),
+                                    success: false,
+                                    device_name: our_name,
+                                })
+                                .await;
+                            let _ = app_handle_network.emit(
+                                "pairing-failed",
+                                serde_json::json!({
+                                    "sessionId": session_id,
+                                    "error": "Device verification failed",
+                                }),
+                            );
+                            continue;
+                        }
+
+                        session.peer_name = Some(peer_device_name);
+                        session.peer_public_key = Some(peer_public_key); // Store for ECDH
+                        session.state = security::PairingState::AwaitingPeerConfirmation;
+                        drop(sessions);
+
+                        if let Err(e) = network_cmd_tx
+                            .send(NetworkCommand::SendPairingConfirm {
+                                peer_id,
+                                session_id,
+                                success: true,
+                                device_name: our_name,
+                            })
+                            .await
+                        {
+                            error!("Failed to confirm pairing URI session: {}", e);
+                        }
+                        continue;
+                    }
+
                     if let Some(session) = sessions.iter_mut().find(|s| s.session_id == session_id)
                     {
                         session.pin = Some(pin.clone());
@@ -806,12 +1185,30 @@ pub async fn start_network_services(
                     // Get the device name, peer's public key, and cached addresses from the session
                     let final_device_name: String;
                     let peer_public_key: Option<Vec<u8>>;
+                    let pake_secret: Option<Vec<u8>>;
                     let session_peer_addresses: Vec<String>;
                     {
                         let mut sessions = state.pairing_sessions.write().await;
                         if let Some(session) =
                             sessions.iter_mut().find(|s| s.session_id == session_id)
                         {
+                            // A confirmation that arrives after the session timed out
+                            // must not create a pairing
+                            if session.state == security::PairingState::Expired {
+                                warn!(
+                                    "Ignoring pairing completion for expired session {}",
+                                    session_id
+                                );
+                                let _ = app_handle_network.emit(
+                                    "pairing-failed",
+                                    serde_json::json!({
+                                        "sessionId": session_id,
+                                        "error": PAIRING_TIMEOUT_ERROR,
+                                        "reason": "timeout",
+                                    }),
+                                );
+                                continue;
+                            }
                             session.state = security::PairingState::Completed;
                             // Use the peer_name from session if available
                             final_device_name = session.peer_name.clone().unwrap_or_else(|| {
@@ -822,22 +1219,25 @@ pub async fn start_network_services(
                                 }
                             });
                             peer_public_key = session.peer_public_key.clone();
+                            pake_secret = session.pake_key.take().map(|k| k.shared_secret());
                             // Use cached addresses from session (captured at pairing start, before mDNS could expire)
                             session_peer_addresses = session.peer_addresses.clone();
                         } else {
                             final_device_name = device_name.clone();
                             peer_public_key = None;
+                            pake_secret = None;
                             session_peer_addresses = Vec::new();
                         }
                     }
 
-                    // Derive shared secret using ECDH
-                    let shared_secret = if let Some(peer_pubkey) = peer_public_key {
+                    // Derive shared secret using ECDH (passphrase pairing already agreed one)
+                    let shared_secret = if let Some(secret) = pake_secret {
+                        secret
+                    } else if let Some(ref peer_pubkey) = peer_public_key {
                         let device_identity = state.device_identity.read().await;
                         if let Some(ref identity) = *device_identity {
                             if let Some(ref our_private_key) = identity.private_key {
-                                match security::derive_shared_secret(our_private_key, &peer_pubkey)
-                                {
+                                match security::derive_shared_secret(our_private_key, peer_pubkey) {
                                     Ok(derived) => derived,
                                     Err(e) => {
                                         error!("Failed to derive shared secret: {}", e);
@@ -907,6 +1307,12 @@ pub async fn start_network_services(
                         paired_at: Utc::now(),
                         last_seen: Some(Utc::now()),
                         last_known_addresses,
+                        group_id: None,
+                        public_key: peer_public_key,
+                        verified: false,
+                        sync_policy: storage::SyncPolicy::default(),
+                        expiry: None,
+                        applied_rotation: None,
                     };
 
                     // Add to paired peers (release lock before flushing to avoid deadlock)
@@ -927,6 +1333,15 @@ pub async fn start_network_services(
                         }
                     }
 
+                    // A queued unpair from an earlier pairing must not undo this one
+                    if let Err(e) = state.remove_pending_unpair(&peer_id).await {
+                        warn!("Failed to drop pending unpair for {}: {}", peer_id, e);
+                    }
+                    // The new pairing already uses our current identity key
+                    if let Err(e) = state.remove_pending_rotation(&peer_id).await {
+                        warn!("Failed to drop pending rotation for {}: {}", peer_id, e);
+                    }
+
                     // Remove from discovered peers since they're now paired
                     {
                         let mut discovered = state.discovered_peers.write().await;
@@ -944,18 +1359,245 @@ pub async fn start_network_services(
                 }
 
                 NetworkEvent::PairingFailed { session_id, error } => {
-                    let mut sessions = state.pairing_sessions.write().await;
-                    if let Some(session) = sessions.iter_mut().find(|s| s.session_id == session_id)
+                    let timed_out = error == PAIRING_TIMEOUT_ERROR;
                     {
-                        session.state = security::PairingState::Failed(error.clone());
+                        let mut sessions = state.pairing_sessions.write().await;
+                        if let Some(session) =
+                            sessions.iter_mut().find(|s| s.session_id == session_id)
+                        {
+                            // Already finished locally (e.g. the ack to our own timeout notice)
+                            if !session.is_active() {
+                                debug!(
+                                    "Ignoring pairing failure for finished session {}: {}",
+                                    session_id, error
+                                );
+                                continue;
+                            }
+                            session.state = if timed_out {
+                                security::PairingState::Expired
+                            } else {
+                                security::PairingState::Failed(error.clone())
+                            };
+                        }
                     }
-                    let _ = app_handle_network.emit(
-                        "pairing-failed",
+                    let payload = if timed_out {
                         serde_json::json!({
                             "sessionId": session_id,
                             "error": error,
-                        }),
-                    );
+                            "reason": "timeout",
+                        })
+                    } else {
+                        serde_json::json!({
+                            "sessionId": session_id,
+                            "error": error,
+                        })
+                    };
+                    let _ = app_handle_network.emit("pairing-failed", payload);
+                }
+
+                NetworkEvent::PakeStartReceived { peer_id, start } => {
+                    // Passphrase pairing (responder): only answer if the user armed a
+                    // passphrase. Taking it makes each arming good for a single attempt.
+                    let listener = if state.is_peer_paired(&peer_id).await {
+                        None
+                    } else {
+                        state.passphrase_listener.write().await.take()
+                    };
+                    let local_peer_id = state.local_peer_id.read().await.clone();
+                    let ours = state
+                        .device_identity
+                        .read()
+                        .await
+                        .as_ref()
+                        .map(|i| (i.device_name.clone(), i.public_key.clone()));
+
+                    let reply = match (listener, local_peer_id, ours) {
+                        (Some(listener), Some(local_peer_id), Some((our_name, our_key))) => {
+                            let (pake, message) = security::PakeState::start_responder(
+                                listener.passphrase(),
+                                &peer_id,
+                                &local_peer_id,
+                            );
+                            pake.finish(&start.message, &start.public_key, &our_key)
+                                .ok()
+                                .map(|key| (key, message, our_name, our_key))
+                        }
+                        _ => None,
+                    };
+
+                    match reply {
+                        Some((key, message, our_name, our_key)) => {
+                            let peer_addresses = {
+                                let discovered = state.discovered_peers.read().await;
+                                discovered
+                                    .iter()
+                                    .find(|p| p.peer_id == peer_id)
+                                    .map(|p| p.addresses.clone())
+                                    .unwrap_or_default()
+                            };
+                            let confirmation = key.confirmation(false, &start.session_id);
+                            let mut session = security::PairingSession::new(
+                                start.session_id.clone(),
+                                peer_id.clone(),
+                                false,
+                            )
+                            .with_peer_name(start.device_name.clone())
+                            .with_peer_public_key(start.public_key.clone())
+                            .with_peer_addresses(peer_addresses);
+                            session.pake_key = Some(key);
+                            session.state = security::PairingState::AwaitingPeerConfirmation;
+                            state.pairing_sessions.write().await.push(session);
+
+                            if let Err(e) = network_cmd_tx
+                                .send(NetworkCommand::SendPakeReply {
+                                    peer_id: peer_id.clone(),
+                                    reply: network::protocol::PakeReply {
+                                        session_id: start.session_id.clone(),
+                                        device_name: our_name,
+                                        message,
+                                        public_key: our_key,
+                                        confirmation,
+                                    },
+                                })
+                                .await
+                            {
+                                error!("Failed to send passphrase pairing reply: {}", e);
+                            }
+
+                            let _ = app_handle_network.emit(
+                                "pairing-passphrase-started",
+                                serde_json::json!({
+                                    "sessionId": start.session_id,
+                                    "peerId": peer_id,
+                                    "deviceName": start.device_name,
+                                }),
+                            );
+                        }
+                        None => {
+                            warn!(
+                                "Rejecting passphrase pairing from {}: passphrase pairing not enabled",
+                                peer_id
+                            );
+                            let _ = network_cmd_tx
+                                .send(NetworkCommand::RejectPairing {
+                                    peer_id,
+                                    session_id: start.session_id,
+                                    reason: Some("Passphrase pairing not enabled".into()),
+                                })
+                                .await;
+                        }
+                    }
+                }
+
+                NetworkEvent::PakeReplyReceived { peer_id, reply } => {
+                    // Passphrase pairing (initiator): finish SPAKE2 and check the
+                    // responder's key confirmation before confirming ourselves
+                    let pake = state.pake_states.write().await.remove(&reply.session_id);
+                    let (our_name, our_key) = state
+                        .device_identity
+                        .read()
+                        .await
+                        .as_ref()
+                        .map(|i| (i.device_name.clone(), i.public_key.clone()))
+                        .unwrap_or_default();
+                    let key = pake
+                        .and_then(|p| p.finish(&reply.message, &our_key, &reply.public_key).ok())
+                        .filter(|key| {
+                            key.verify_confirmation(false, &reply.session_id, &reply.confirmation)
+                        });
+
+                    let mut sessions = state.pairing_sessions.write().await;
+                    let session = match sessions.iter_mut().find(|s| {
+                        s.session_id == reply.session_id && s.peer_id == peer_id && s.is_active()
+                    }) {
+                        Some(session) => session,
+                        None => continue,
+                    };
+
+                    match key {
+                        Some(key) => {
+                            let confirm = network::protocol::PakeConfirm {
+                                session_id: reply.session_id.clone(),
+                                confirmation: key.confirmation(true, &reply.session_id),
+                            };
+                            session.peer_name = Some(reply.device_name);
+                            session.peer_public_key = Some(reply.public_key);
+                            session.pake_key = Some(key);
+                            session.state = security::PairingState::AwaitingPeerConfirmation;
+                            drop(sessions);
+
+                            let message = network::ProtocolMessage::Pairing(
+                                network::protocol::PairingMessage::PakeConfirm(confirm),
+                            );
+                            if let Err(e) = network_cmd_tx
+                                .send(NetworkCommand::SendPairingRequest {
+                                    peer_id,
+                                    message: message.to_bytes().unwrap_or_default(),
+                                })
+                                .await
+                            {
+                                error!("Failed to send passphrase confirmation: {}", e);
+                            }
+                        }
+                        None => {
+                            warn!("Passphrase pairing with {} failed: key mismatch", peer_id);
+                            session.state =
+                                security::PairingState::Failed("Passphrase mismatch".into());
+                            drop(sessions);
+
+                            let _ = network_cmd_tx
+                                .send(NetworkCommand::SendPairingConfirm {
+                                    peer_id,
+                                    session_id: reply.session_id.clone(),
+                                    success: false,
+                                    device_name: our_name,
+                                })
+                                .await;
+                            let _ = app_handle_network.emit(
+                                "pairing-failed",
+                                serde_json::json!({
+                                    "sessionId": reply.session_id,
+                                    "error": "Passphrase mismatch",
+                                }),
+                            );
+                        }
+                    }
+                }
+
+                NetworkEvent::PakeConfirmReceived { peer_id, confirm } => {
+                    // Passphrase pairing (responder): the initiator proves it derived
+                    // the same key; the swarm then completes or fails the session
+                    let verified = {
+                        let sessions = state.pairing_sessions.read().await;
+                        sessions
+                            .iter()
+                            .find(|s| {
+                                s.session_id == confirm.session_id
+                                    && s.peer_id == peer_id
+                                    && s.is_active()
+                            })
+                            .and_then(|s| {
+                                let key = s.pake_key.as_ref()?;
+                                key.verify_confirmation(
+                                    true,
+                                    &confirm.session_id,
+                                    &confirm.confirmation,
+                                )
+                                .then(|| s.peer_name.clone().unwrap_or_default())
+                            })
+                    };
+                    if verified.is_none() {
+                        warn!("Passphrase pairing with {} failed: key mismatch", peer_id);
+                    }
+
+                    let _ = network_cmd_tx
+                        .send(NetworkCommand::AnswerPakeConfirm {
+                            peer_id,
+                            session_id: confirm.session_id,
+                            success: verified.is_some(),
+                            device_name: verified.unwrap_or_default(),
+                        })
+                        .await;
                 }
 
                 NetworkEvent::OutboundPairingFailed { peer_id, error } => {
@@ -989,7 +1631,10 @@ pub async fn start_network_services(
                     }
                 }
 
-                NetworkEvent::ClipboardReceived(msg) => {
+                NetworkEvent::ClipboardReceived {
+                    peer_id,
+                    message: msg,
+                } => {
                     // Safety check: ignore our own messages (belt-and-suspenders)
                     let my_device_id = state
                         .device_identity
@@ -1002,92 +1647,171 @@ pub async fn start_network_services(
                         continue;
                     }
 
-                    // Check if from paired peer
-                    let paired_peers = state.paired_peers.read().await;
+                    // Only accept content from paired peers whose policy allows it
+                    let sender = peer_id.unwrap_or_default();
+                    let policy = match state.peer_sync_policy(&sender).await {
+                        Some(policy) if policy.receive => policy,
+                        Some(_) => {
+                            debug!("Ignoring clipboard from {}: receiving disabled", sender);
+                            continue;
+                        }
+                        None => {
+                            debug!("Ignoring clipboard from unpaired peer {}", sender);
+                            continue;
+                        }
+                    };
 
-                    // Find the peer's shared secret
-                    // Try decrypting with each paired peer's secret until one succeeds
-                    let mut decrypted_successfully = false;
-                    for peer in paired_peers.iter() {
-                        match security::decrypt_content(&msg.encrypted_content, &peer.shared_secret)
-                        {
-                            Ok(decrypted) => {
-                                if let Ok(content) = String::from_utf8(decrypted) {
-                                    // Verify hash
-                                    let hash = security::hash_content(&content);
-                                    if hash == msg.content_hash {
-                                        decrypted_successfully = true;
-
-                                        // Check if we should queue for background (mobile only)
-                                        #[cfg(any(target_os = "android", target_os = "ios"))]
-                                        let is_foreground = *state.is_foreground.read().await;
-                                        #[cfg(not(any(target_os = "android", target_os = "ios")))]
-                                        let is_foreground = true;
-
-                                        if is_foreground {
-                                            // Update local clipboard directly
-                                            if let Err(e) =
-                                                clipboard::monitor::set_clipboard_content(
-                                                    &app_handle_network,
-                                                    &content,
-                                                )
-                                            {
-                                                error!("Failed to set clipboard: {}", e);
-                                            }
-
-                                            // Prevent echo: tell the monitor about this hash
-                                            // so it won't treat it as a local change
-                                            clipboard_monitor.set_last_hash(hash.clone()).await;
-                                        } else {
-                                            // Mobile background: queue clipboard silently (no notification)
-                                            // Clipboard will be copied when app resumes
-                                            #[cfg(any(target_os = "android", target_os = "ios"))]
-                                            {
-                                                info!(
-                                                    "App in background, queuing clipboard from {} (silent)",
-                                                    msg.origin_device_name
-                                                );
-
-                                                // Store pending clipboard - will be processed on resume
-                                                {
-                                                    let mut pending =
-                                                        state.pending_clipboard.write().await;
-                                                    *pending = Some(PendingClipboard {
-                                                        content: content.clone(),
-                                                        from_device: msg.origin_device_name.clone(),
-                                                    });
-                                                }
-                                            }
-                                        }
-
-                                        // Add to history (always, even for duplicates - moved to front)
-                                        let entry = ClipboardEntry::new_remote(
-                                            content,
-                                            msg.content_hash.clone(),
-                                            msg.timestamp,
-                                            &msg.origin_device_id,
-                                            &msg.origin_device_name,
-                                        );
-                                        state.add_clipboard_entry(entry.clone()).await;
+                    // Decrypt with the group key or the sender's shared secret, verify the hash
+                    // and reject replays
+                    let decrypted = state
+                        .decrypt_clipboard_message(
+                            &msg,
+                            Some(&sender),
+                            security::MAX_CLOCK_SKEW_SECS,
+                        )
+                        .await;
+                    let (content, ttl_secs, selection, retention) = match decrypted {
+                        Some(sealed) => (
+                            sealed.content,
+                            sealed.ttl_secs,
+                            sealed.selection,
+                            sealed.history,
+                        ),
+                        None => {
+                            tracing::warn!(
+                                "Failed to decrypt clipboard message from {} ({})",
+                                msg.origin_device_name,
+                                sender
+                            );
+                            continue;
+                        }
+                    };
 
-                                        // Emit to frontend
-                                        let _ =
-                                            app_handle_network.emit("clipboard-received", entry);
-
-                                        break;
-                                    }
-                                }
-                            }
-                            Err(_) => continue,
+                    // Self-destructing and no-history items skip history
+                    let skipped = if let Some(ttl_secs) = ttl_secs {
+                        Some(
+                            apply_self_destructing(
+                                &app_handle_network,
+                                &clipboard_monitor,
+                                &policy,
+                                &msg,
+                                &content,
+                                ttl_secs,
+                            )
+                            .await,
+                        )
+                    } else if retention == HistoryRetention::Skip {
+                        Some(
+                            apply_without_history(
+                                &app_handle_network,
+                                &clipboard_monitor,
+                                &policy,
+                                &msg,
+                                &content,
+                                selection,
+                            )
+                            .await,
+                        )
+                    } else {
+                        None
+                    };
+                    if let Some(status) = skipped {
+                        if let Err(e) = state.send_delivery_report(&sender, &msg, status).await {
+                            debug!("Failed to send delivery report to {}: {}", sender, e);
                         }
+                        if let Err(e) = state.count_pairing_messages(&[sender]).await {
+                            warn!("Failed to update pairing message count: {}", e);
+                        }
+                        expire_temporary_pairings(&app_handle_network).await;
+                        continue;
                     }
 
-                    if !decrypted_successfully && !paired_peers.is_empty() {
-                        tracing::warn!(
-                            "Failed to decrypt clipboard message from {} - no paired peer could decrypt it",
+                    // Add to history (always, even for duplicates - moved to its HLC position)
+                    let mut entry = ClipboardEntry::new_remote(
+                        content.clone(),
+                        msg.content_hash.clone(),
+                        msg.timestamp,
+                        msg.hlc,
+                        &msg.origin_device_id,
+                        &msg.origin_device_name,
+                    );
+                    entry.expires_at = retention_expiry(retention);
+                    let is_latest = state.add_clipboard_entry(entry.clone()).await;
+                    let mut applied = false;
+
+                    // Check if we should queue for background (mobile only)
+                    #[cfg(any(target_os = "android", target_os = "ios"))]
+                    let is_foreground = *state.is_foreground.read().await;
+                    #[cfg(not(any(target_os = "android", target_os = "ios")))]
+                    let is_foreground = true;
+
+                    let history_only = state.settings.read().await.receive_history_only;
+                    if !policy.applies_to_clipboard(history_only) {
+                        debug!(
+                            "Keeping clipboard from {} in history only",
                             msg.origin_device_name
                         );
+                        let _ = app_handle_network.emit(
+                            "clipboard-held",
+                            serde_json::json!({
+                                "entryId": entry.id,
+                                "deviceName": msg.origin_device_name,
+                            }),
+                        );
+                    } else if !is_latest {
+                        // Latest wins: a newer copy is already on the clipboard
+                        debug!(
+                            "Keeping older clipboard from {} in history only",
+                            msg.origin_device_name
+                        );
+                    } else if is_foreground {
+                        // Update local clipboard directly (echo-safe, undoable)
+                        let target = receive_target(&state, selection).await;
+                        match clipboard_monitor
+                            .apply_remote_to(&app_handle_network, &content, target)
+                            .await
+                        {
+                            Ok(()) => applied = true,
+                            Err(e) => error!("Failed to set clipboard: {}", e),
+                        }
+                    } else {
+                        // Mobile background: queue clipboard silently (no notification)
+                        // Clipboard will be copied when app resumes
+                        #[cfg(any(target_os = "android", target_os = "ios"))]
+                        {
+                            info!(
+                                "App in background, queuing clipboard from {} (silent)",
+                                msg.origin_device_name
+                            );
+
+                            // Store pending clipboard - will be processed on resume
+                            {
+                                let mut pending = state.pending_clipboard.write().await;
+                                *pending = Some(PendingClipboard {
+                                    content: content.clone(),
+                                    from_device: msg.origin_device_name.clone(),
+                                });
+                            }
+                        }
+                    }
+
+                    // Emit to frontend
+                    let _ = app_handle_network.emit("clipboard-received", entry);
+
+                    // Acknowledge to the sender
+                    let status = if applied {
+                        DeliveryStatus::Applied
+                    } else {
+                        DeliveryStatus::Delivered
+                    };
+                    if let Err(e) = state.send_delivery_report(&sender, &msg, status).await {
+                        debug!("Failed to send delivery report to {}: {}", sender, e);
+                    }
+
+                    if let Err(e) = state.count_pairing_messages(&[sender]).await {
+                        warn!("Failed to update pairing message count: {}", e);
                     }
+                    expire_temporary_pairings(&app_handle_network).await;
                 }
 
                 NetworkEvent::ClipboardSent { id, peer_count } => {
@@ -1104,6 +1828,292 @@ pub async fn start_network_services(
                     let _ = app_handle_network.emit("network-error", error);
                 }
 
+                // Pairing group events
+                NetworkEvent::GroupMessageReceived {
+                    peer_id,
+                    encrypted_payload,
+                } => {
+                    // Only paired peers can produce a payload we can decrypt,
+                    // which authenticates the sender
+                    let opened = state.open_group_payload(&peer_id, &encrypted_payload).await;
+                    let payload = match opened {
+                        Ok(payload) => payload,
+                        Err(e) => {
+                            warn!("Ignoring group message from {}: {}", peer_id, e);
+                            continue;
+                        }
+                    };
+                    let local_peer_id = match state.local_peer_id.read().await.clone() {
+                        Some(id) => id,
+                        None => continue,
+                    };
+                    let current = state.pairing_group.read().await.clone();
+
+                    match payload {
+                        GroupPayload::State(group) => {
+                            if !group.is_member(&local_peer_id) || !group.is_member(&peer_id) {
+                                warn!("Ignoring group state from {}: membership mismatch", peer_id);
+                                continue;
+                            }
+
+                            let current = match current {
+                                Some(current) => current,
+                                None => {
+                                    // Joining pairs us with every member: ask our user first
+                                    hold_group_invite(&app_handle_network, &peer_id, group).await;
+                                    continue;
+                                }
+                            };
+                            // Only current members may change the group; a removed device
+                            // keeps its ceremony pairing but needs a new invitation
+                            if !current.is_member(&peer_id) {
+                                warn!("Ignoring group state from non-member {}", peer_id);
+                                continue;
+                            }
+                            if current.supersedes(&group) {
+                                // The sender is behind: bring it up to date
+                                let reply = GroupPayload::State(current.clone());
+                                if let Err(e) = state.send_group_payload(&peer_id, &reply).await {
+                                    warn!("Failed to send group state to {}: {}", peer_id, e);
+                                }
+                                continue;
+                            }
+                            if !group.supersedes(&current) {
+                                continue;
+                            }
+                            if !group.is_valid_update(&current, &peer_id) {
+                                warn!(
+                                    "Ignoring group state from {}: invalid membership change",
+                                    peer_id
+                                );
+                                continue;
+                            }
+
+                            let info = commands::GroupInfo::from(&group);
+                            if let Err(e) = state.apply_pairing_group(group, &local_peer_id).await {
+                                error!("Failed to apply group state: {}", e);
+                                continue;
+                            }
+                            info!(
+                                "Pairing group {} updated to epoch {}",
+                                info.group_id, info.epoch
+                            );
+                            let _ = app_handle_network.emit("group-updated", info);
+                        }
+
+                        GroupPayload::Removed { group_id } => {
+                            let from_member = current
+                                .as_ref()
+                                .is_some_and(|g| g.group_id == group_id && g.is_member(&peer_id));
+                            if !from_member {
+                                continue;
+                            }
+                            if let Err(e) = state.clear_pairing_group().await {
+                                error!("Failed to clear pairing group: {}", e);
+                                continue;
+                            }
+                            info!("Removed from pairing group {} by {}", group_id, peer_id);
+                            let _ = app_handle_network
+                                .emit("group-removed", serde_json::json!({ "groupId": group_id }));
+                        }
+
+                        GroupPayload::Declined { group_id } => {
+                            let mut group = match current {
+                                Some(g) if g.group_id == group_id && g.is_member(&peer_id) => g,
+                                _ => continue,
+                            };
+
+                            // Only we (the inviter) hear about it: drop the member and
+                            // re-key, since the invitation carried the group key
+                            group.members.retain(|m| m.peer_id != peer_id);
+                            group.group_key = security::generate_key();
+                            group.epoch += 1;
+                            let info = commands::GroupInfo::from(&group);
+                            if let Err(e) = state.apply_pairing_group(group, &local_peer_id).await {
+                                error!("Failed to re-key pairing group: {}", e);
+                                continue;
+                            }
+                            state.broadcast_group_state(&local_peer_id).await;
+                            info!("{} declined pairing group {}, re-keyed", peer_id, group_id);
+                            let _ = app_handle_network.emit("group-updated", info);
+                        }
+
+                        GroupPayload::Left { group_id } => {
+                            let mut group = match current {
+                                Some(g) if g.group_id == group_id && g.is_member(&peer_id) => g,
+                                _ => continue,
+                            };
+
+                            // The coordinator of the remaining members re-keys so the
+                            // departed device can't read anything sent afterwards
+                            group.members.retain(|m| m.peer_id != peer_id);
+                            let is_coordinator = group
+                                .coordinator()
+                                .is_some_and(|m| m.peer_id == local_peer_id);
+                            if !is_coordinator {
+                                continue;
+                            }
+
+                            group.group_key = security::generate_key();
+                            group.epoch += 1;
+                            let info = commands::GroupInfo::from(&group);
+                            if let Err(e) = state.apply_pairing_group(group, &local_peer_id).await {
+                                error!("Failed to re-key pairing group: {}", e);
+                                continue;
+                            }
+                            state.broadcast_group_state(&local_peer_id).await;
+                            info!("{} left pairing group {}, re-keyed", peer_id, group_id);
+                            let _ = app_handle_network.emit("group-updated", info);
+                        }
+                    }
+                }
+
+                // Unpair Events
+                NetworkEvent::UnpairReceived { peer_id, proof } => {
+                    let local_peer_id = match state.local_peer_id.read().await.clone() {
+                        Some(id) => id,
+                        None => continue,
+                    };
+
+                    // Only the device holding the shared secret can produce the proof
+                    let peer = {
+                        let peers = state.paired_peers.read().await;
+                        peers
+                            .iter()
+                            .find(|p| {
+                                p.peer_id == peer_id
+                                    && security::verify_unpair_proof(
+                                        &p.shared_secret,
+                                        &peer_id,
+                                        &local_peer_id,
+                                        &proof,
+                                    )
+                            })
+                            .cloned()
+                    };
+                    let peer = match peer {
+                        Some(peer) => peer,
+                        None => {
+                            warn!(
+                                "Ignoring unpair from {}: not paired or invalid proof",
+                                peer_id
+                            );
+                            continue;
+                        }
+                    };
+
+                    {
+                        let mut peers = state.paired_peers.write().await;
+                        peers.retain(|p| p.peer_id != peer_id);
+                    }
+                    if let Err(e) = state.flush_paired_peers().await {
+                        warn!("Failed to flush paired peers: {}", e);
+                    }
+                    state.remove_message_buffer(&peer_id).await;
+
+                    info!("{} ({}) removed our pairing", peer.device_name, peer_id);
+                    let _ = app_handle_network.emit(
+                        "peer-unpaired",
+                        serde_json::json!({
+                            "peerId": peer_id,
+                            "deviceName": peer.device_name,
+                        }),
+                    );
+
+                    // Show the device as discoverable again
+                    let _ = network_cmd_tx
+                        .send(NetworkCommand::RefreshPeer { peer_id })
+                        .await;
+                }
+
+                NetworkEvent::UnpairAcknowledged { peer_id } => {
+                    debug!("Unpair acknowledged by {}", peer_id);
+                    if let Err(e) = state.remove_pending_unpair(&peer_id).await {
+                        warn!("Failed to drop pending unpair for {}: {}", peer_id, e);
+                    }
+                }
+
+                // Identity Rotation Events
+                NetworkEvent::IdentityRotationReceived {
+                    peer_id,
+                    old_peer_id,
+                    encrypted_payload,
+                } => {
+                    // Only ack once the rotation is applied; otherwise the peer keeps
+                    // retrying. A retry after a lost ack no longer decrypts, so it is
+                    // recognised by its digest and acked again.
+                    if state
+                        .is_rotation_applied(&peer_id, &encrypted_payload)
+                        .await
+                    {
+                        debug!("Identity rotation from {} already applied", peer_id);
+                        let _ = network_cmd_tx
+                            .send(NetworkCommand::AnswerIdentityRotation {
+                                peer_id,
+                                applied: true,
+                            })
+                            .await;
+                        continue;
+                    }
+                    let result = state
+                        .apply_identity_rotation(&peer_id, &old_peer_id, &encrypted_payload)
+                        .await;
+                    let _ = network_cmd_tx
+                        .send(NetworkCommand::AnswerIdentityRotation {
+                            peer_id: peer_id.clone(),
+                            applied: result.is_ok(),
+                        })
+                        .await;
+                    let peer = match result {
+                        Ok(peer) => peer,
+                        Err(e) => {
+                            warn!("Ignoring identity rotation from {}: {}", peer_id, e);
+                            continue;
+                        }
+                    };
+
+                    // A new PeerId was discovered as an unpaired device
+                    {
+                        let mut discovered = state.discovered_peers.write().await;
+                        discovered.retain(|p| p.peer_id != peer_id);
+                    }
+
+                    info!(
+                        "{} rotated its identity ({} -> {})",
+                        peer.device_name, old_peer_id, peer_id
+                    );
+                    let _ = app_handle_network.emit(
+                        "peer-identity-rotated",
+                        serde_json::json!({
+                            "oldPeerId": old_peer_id,
+                            "peerId": peer_id,
+                            "deviceName": peer.device_name,
+                        }),
+                    );
+                }
+
+                NetworkEvent::IdentityRotationAcknowledged { peer_id } => {
+                    debug!("Identity rotation acknowledged by {}", peer_id);
+                    match state.remove_pending_rotation(&peer_id).await {
+                        Ok(true) => {
+                            // Every peer knows the new key, so announcing it is safe again
+                            let public_key = state
+                                .device_identity
+                                .read()
+                                .await
+                                .as_ref()
+                                .map(|id| id.public_key.clone());
+                            let _ = network_cmd_tx
+                                .send(NetworkCommand::SetAnnouncedIdentityKey { public_key })
+                                .await;
+                        }
+                        Ok(false) => {}
+                        Err(e) => {
+                            warn!("Failed to drop pending rotation for {}: {}", peer_id, e);
+                        }
+                    }
+                }
+
                 // Sync Protocol Events - For offline message delivery
                 NetworkEvent::SyncRequestReceived { peer_id } => {
                     // A peer requested sync from us - respond with our buffered hashes.
@@ -1123,6 +2133,7 @@ pub async fn start_network_services(
                         .map(|msg| network::protocol::MessageHash {
                             hash: msg.content_hash.clone(),
                             timestamp: msg.timestamp,
+                            hlc: msg.hlc,
                         })
                         .collect();
 
@@ -1187,6 +2198,29 @@ pub async fn start_network_services(
                     }
                 }
 
+                NetworkEvent::SyncRelayReceived { peer_id, message } => {
+                    // A paired peer wants us to hold a message for another device.
+                    // We can't read it; it is handed over when the recipient syncs.
+                    let held = match state.hold_relayed_message(&peer_id, message).await {
+                        Some(message) => message,
+                        None => {
+                            debug!("Not holding relayed message from {}", peer_id);
+                            continue;
+                        }
+                    };
+                    let recipient = held
+                        .relay
+                        .as_ref()
+                        .map(|relay| relay.recipient_peer_id.clone())
+                        .unwrap_or_default();
+                    debug!("Holding message from {} for {}", peer_id, recipient);
+
+                    // Pass it on while the recipient is offline (and hops are left)
+                    if !state.ready_peers.read().await.contains(&recipient) {
+                        state.send_to_relay(held, &[peer_id.as_str()]).await;
+                    }
+                }
+
                 NetworkEvent::SyncHashListReceived { peer_id, hashes } => {
                     // We received a list of hashes available from a peer.
                     // Compare against our clipboard history and request missing content.
@@ -1205,7 +2239,7 @@ pub async fn start_network_services(
                         continue;
                     }
 
-                    // Find hashes we don't have in our history
+                    // Find hashes we don't have in our history, oldest (by HLC) first
                     let needed_hashes: Vec<String> = {
                         let history = state.clipboard_history.read().await;
                         let our_hashes: std::collections::HashSet<&str> = history
@@ -1213,11 +2247,12 @@ pub async fn start_network_services(
                             .map(|entry| entry.content_hash.as_str())
                             .collect();
 
-                        hashes
+                        let mut needed: Vec<_> = hashes
                             .into_iter()
                             .filter(|h| !our_hashes.contains(h.hash.as_str()))
-                            .map(|h| h.hash)
-                            .collect()
+                            .collect();
+                        needed.sort_by_key(|h| h.hlc);
+                        needed.into_iter().map(|h| h.hash).collect()
                     };
 
                     if !needed_hashes.is_empty() {
@@ -1247,89 +2282,263 @@ pub async fn start_network_services(
                     }
                 }
 
-                NetworkEvent::SyncContentReceived { peer_id, message } => {
+                NetworkEvent::DeliveryReportReceived {
+                    peer_id,
+                    encrypted_payload,
+                } => {
+                    let opened = state
+                        .open_delivery_report(&peer_id, &encrypted_payload)
+                        .await;
+                    let report = match opened {
+                        Ok(report) => report,
+                        Err(e) => {
+                            warn!("Ignoring delivery report from {}: {}", peer_id, e);
+                            continue;
+                        }
+                    };
+                    if !matches!(
+                        report.status,
+                        DeliveryStatus::Delivered | DeliveryStatus::Applied
+                    ) {
+                        continue;
+                    }
+
+                    // The peer has it: no need to keep it queued for sync
+                    state
+                        .remove_buffered_message_for_peer(&peer_id, &report.content_hash)
+                        .await;
+
+                    if let Some(entry_id) = state
+                        .update_delivery_status(&report.content_hash, &peer_id, report.status)
+                        .await
+                    {
+                        debug!(
+                            "Message {} {:?} at {}",
+                            report.message_id, report.status, peer_id
+                        );
+                        let _ = app_handle_network.emit(
+                            "delivery-status",
+                            serde_json::json!({
+                                "entryId": entry_id,
+                                "peerId": peer_id,
+                                "status": report.status,
+                            }),
+                        );
+                    }
+                }
+
+                NetworkEvent::HistorySyncReceived {
+                    peer_id,
+                    encrypted_payload,
+                } => {
+                    handle_history_sync(&app_handle_network, peer_id, encrypted_payload).await;
+                }
+
+                NetworkEvent::NearbyMessageReceived { peer_id, message } => {
+                    handle_nearby_message(
+                        &app_handle_network,
+                        &clipboard_monitor,
+                        peer_id,
+                        message,
+                    )
+                    .await;
+                }
+
+                NetworkEvent::PullMessageReceived { peer_id, message } => {
+                    handle_pull_message(&app_handle_network, &clipboard_monitor, peer_id, message)
+                        .await;
+                }
+
+                NetworkEvent::NearbyAcknowledged {
+                    peer_id,
+                    transfer_id,
+                } => {
+                    // Only the ack for our Content finishes a transfer
+                    let sent = state.nearby_transfers.read().await.iter().any(|t| {
+                        t.transfer_id == transfer_id
+                            && t.peer_id == peer_id
+                            && t.state == security::NearbyTransferState::Sent
+                    });
+                    if sent {
+                        state.take_nearby_transfer(&transfer_id, &peer_id).await;
+                        info!("Nearby send {} delivered to {}", transfer_id, peer_id);
+                        let _ = app_handle_network.emit(
+                            "nearby-send-complete",
+                            serde_json::json!({ "transferId": transfer_id }),
+                        );
+                    }
+                }
+
+                NetworkEvent::SyncContentReceived {
+                    peer_id: from_peer,
+                    message,
+                } => {
                     // We received full content for a hash we requested.
                     // Decrypt, verify, and add to clipboard history.
 
-                    // Security: verify peer is paired
-                    if !state.is_peer_paired(&peer_id).await {
-                        warn!(
-                            "Ignoring SyncContentReceived from unpaired peer: {}",
+                    // A relayed message comes from its origin, not from the relay
+                    let peer_id = match message.relay {
+                        Some(ref relay) => {
+                            let local_peer_id = state.local_peer_id.read().await.clone();
+                            if local_peer_id.as_ref() != Some(&relay.recipient_peer_id)
+                                || !state.is_peer_paired(&from_peer).await
+                            {
+                                warn!(
+                                    "Ignoring relayed message from {} not meant for us",
+                                    from_peer
+                                );
+                                continue;
+                            }
+                            debug!(
+                                "Received message from {} relayed by {}",
+                                relay.origin_peer_id, from_peer
+                            );
+                            relay.origin_peer_id.clone()
+                        }
+                        None => from_peer,
+                    };
+
+                    // Security: verify peer is paired (and that we accept its content)
+                    let policy = match state.peer_sync_policy(&peer_id).await {
+                        Some(policy) => policy,
+                        None => {
+                            warn!(
+                                "Ignoring SyncContentReceived from unpaired peer: {}",
+                                peer_id
+                            );
+                            continue;
+                        }
+                    };
+                    if !policy.receive {
+                        debug!(
+                            "Ignoring synced content from {}: receiving disabled",
                             peer_id
                         );
                         continue;
                     }
 
-                    // Try to decrypt with paired peer's shared secret
-                    let paired_peers = state.paired_peers.read().await;
+                    // Decrypt with the peer's shared secret (or our group key), verify the hash
+                    // and reject replays. Buffered messages may be up to MAX_SYNC_BUFFER_TTL_SECS old.
+                    let max_age = crate::state::MAX_CLIPBOARD_MESSAGE_AGE_SECS;
+                    let (content, ttl_secs, selection, retention) = match state
+                        .decrypt_clipboard_message(&message, Some(peer_id.as_str()), max_age)
+                        .await
+                    {
+                        Some(sealed) => (
+                            sealed.content,
+                            sealed.ttl_secs,
+                            sealed.selection,
+                            sealed.history,
+                        ),
+                        None => {
+                            warn!("Failed to decrypt synced content from {}", peer_id);
+                            continue;
+                        }
+                    };
 
-                    // Find the peer and decrypt
-                    if let Some(peer) = paired_peers.iter().find(|p| p.peer_id == peer_id) {
-                        match security::decrypt_content(
-                            &message.encrypted_content,
-                            &peer.shared_secret,
-                        ) {
-                            Ok(decrypted) => {
-                                if let Ok(content) = String::from_utf8(decrypted) {
-                                    // Verify hash
-                                    let hash = security::hash_content(&content);
-                                    if hash == message.content_hash {
-                                        // Check deduplication - don't apply if already in history
-                                        let already_has = {
-                                            let history = state.clipboard_history.read().await;
-                                            history.iter().any(|entry| entry.content_hash == hash)
-                                        };
-
-                                        if !already_has {
-                                            // Set clipboard
-                                            if let Err(e) =
-                                                clipboard::monitor::set_clipboard_content(
-                                                    &app_handle_network,
-                                                    &content,
-                                                )
-                                            {
-                                                error!("Failed to set synced clipboard: {}", e);
-                                            }
-
-                                            // Prevent echo
-                                            clipboard_monitor.set_last_hash(hash.clone()).await;
-
-                                            // Add to history with correct timestamp
-                                            let entry = ClipboardEntry::new_remote(
-                                                content,
-                                                message.content_hash.clone(),
-                                                message.timestamp,
-                                                &message.origin_device_id,
-                                                &message.origin_device_name,
-                                            );
-                                            // Use add_clipboard_entry which handles chronological insertion
-                                            state.add_clipboard_entry(entry.clone()).await;
-
-                                            // Emit to frontend (use same event as regular clipboard-received)
-                                            let _ = app_handle_network
-                                                .emit("clipboard-received", entry);
-
-                                            info!(
-                                                "Synced clipboard from {} (hash: {})",
-                                                message.origin_device_name,
-                                                &hash[..8.min(hash.len())]
-                                            );
-                                        } else {
-                                            debug!(
-                                                "Synced message already in history (deduplicated)"
-                                            );
-                                        }
-                                    } else {
-                                        warn!("Hash mismatch in synced message from {}", peer_id);
-                                    }
-                                }
-                            }
-                            Err(e) => {
-                                error!("Failed to decrypt synced content from {}: {}", peer_id, e);
+                    // Self-destructing and no-history items skip history
+                    let skipped = if let Some(ttl_secs) = ttl_secs {
+                        Some(
+                            apply_self_destructing(
+                                &app_handle_network,
+                                &clipboard_monitor,
+                                &policy,
+                                &message,
+                                &content,
+                                ttl_secs,
+                            )
+                            .await,
+                        )
+                    } else if retention == HistoryRetention::Skip {
+                        Some(
+                            apply_without_history(
+                                &app_handle_network,
+                                &clipboard_monitor,
+                                &policy,
+                                &message,
+                                &content,
+                                selection,
+                            )
+                            .await,
+                        )
+                    } else {
+                        None
+                    };
+                    if let Some(status) = skipped {
+                        if let Err(e) = state.send_delivery_report(&peer_id, &message, status).await
+                        {
+                            debug!("Failed to send delivery report to {}: {}", peer_id, e);
+                        }
+                        if let Err(e) = state.count_pairing_messages(&[peer_id]).await {
+                            warn!("Failed to update pairing message count: {}", e);
+                        }
+                        expire_temporary_pairings(&app_handle_network).await;
+                        continue;
+                    }
+                    let hash = message.content_hash.clone();
+
+                    // Check deduplication - don't apply if already in history
+                    let already_has = {
+                        let history = state.clipboard_history.read().await;
+                        history.iter().any(|entry| entry.content_hash == hash)
+                    };
+
+                    let mut status = DeliveryStatus::Delivered;
+                    if !already_has {
+                        // Add to history at its HLC position
+                        let mut entry = ClipboardEntry::new_remote(
+                            content.clone(),
+                            message.content_hash.clone(),
+                            message.timestamp,
+                            message.hlc,
+                            &message.origin_device_id,
+                            &message.origin_device_name,
+                        );
+                        entry.expires_at = retention_expiry(retention);
+                        // Use add_clipboard_entry which handles ordered insertion
+                        let is_latest = state.add_clipboard_entry(entry.clone()).await;
+
+                        // Latest wins: older synced items only go to history
+                        let history_only = state.settings.read().await.receive_history_only;
+                        if !policy.applies_to_clipboard(history_only) {
+                            let _ = app_handle_network.emit(
+                                "clipboard-held",
+                                serde_json::json!({
+                                    "entryId": entry.id,
+                                    "deviceName": message.origin_device_name,
+                                }),
+                            );
+                        } else if is_latest {
+                            // Set clipboard (echo-safe, undoable)
+                            let target = receive_target(&state, selection).await;
+                            match clipboard_monitor
+                                .apply_remote_to(&app_handle_network, &content, target)
+                                .await
+                            {
+                                Ok(()) => status = DeliveryStatus::Applied,
+                                Err(e) => error!("Failed to set synced clipboard: {}", e),
                             }
                         }
+
+                        // Emit to frontend (use same event as regular clipboard-received)
+                        let _ = app_handle_network.emit("clipboard-received", entry);
+
+                        if let Err(e) = state.count_pairing_messages(&[peer_id.clone()]).await {
+                            warn!("Failed to update pairing message count: {}", e);
+                        }
+                        expire_temporary_pairings(&app_handle_network).await;
+
+                        info!(
+                            "Synced clipboard from {} (hash: {})",
+                            message.origin_device_name,
+                            &hash[..8.min(hash.len())]
+                        );
                     } else {
-                        warn!("Received sync content from unknown peer: {}", peer_id);
+                        debug!("Synced message already in history (deduplicated)");
+                    }
+
+                    if let Err(e) = state.send_delivery_report(&peer_id, &message, status).await {
+                        debug!("Failed to send delivery report to {}: {}", peer_id, e);
                     }
                 }
             }
@@ -1339,3 +2548,909 @@ pub async fn start_network_services(
     info!("Network services started successfully");
     Ok(())
 }
+
+/// Keep a group state that would add us to a group until our user answers it
+/// (`respond_to_group_invite`). A newer state for the same group replaces the held
+/// one silently; the first one for a group is announced with `group-invite`.
+async fn hold_group_invite(app_handle: &AppHandle, from_peer_id: &str, group: PairingGroup) {
+    let state = app_handle.state::<AppState>();
+    let is_new = {
+        let mut pending = state.pending_group_invite.write().await;
+        let is_new = match pending.as_ref() {
+            Some(invite) if invite.group.group_id == group.group_id => {
+                if !group.supersedes(&invite.group) {
+                    return;
+                }
+                false
+            }
+            _ => true,
+        };
+        *pending = Some(GroupInvite {
+            from_peer_id: from_peer_id.to_string(),
+            group: group.clone(),
+        });
+        is_new
+    };
+    if !is_new {
+        return;
+    }
+
+    let device_name = state
+        .paired_peers
+        .read()
+        .await
+        .iter()
+        .find(|p| p.peer_id == from_peer_id)
+        .map(|p| p.device_name.clone())
+        .unwrap_or_default();
+    info!(
+        "{} invited us to pairing group {}",
+        from_peer_id, group.group_id
+    );
+    let _ = app_handle.emit(
+        "group-invite",
+        serde_json::json!({
+            "groupId": group.group_id,
+            "name": group.name,
+            "peerId": from_peer_id,
+            "deviceName": device_name,
+            "memberCount": group.members.len(),
+        }),
+    );
+}
+
+/// Where a received item is written: items selected on another device (PRIMARY)
+/// follow `primary_receive_target`, everything else goes to the clipboard.
+async fn receive_target(state: &AppState, source: ClipboardSelection) -> ClipboardSelection {
+    match source {
+        ClipboardSelection::Primary => state.settings.read().await.primary_receive_target,
+        ClipboardSelection::Clipboard => ClipboardSelection::Clipboard,
+    }
+}
+
+/// Put a self-destructing item on the clipboard without adding it to history, and
+/// clear it again `ttl_secs` after it was received if the clipboard still holds it.
+/// Returns the status to acknowledge.
+///
+/// The timer starts on receipt, not at the sender's timestamp, so clock skew can't
+/// shorten it; queued copies are dropped by the sender and relays at `expires_at`.
+/// There is no history entry to hold the item in, so it is refused in history-only
+/// mode.
+async fn apply_self_destructing(
+    app_handle: &AppHandle,
+    clipboard_monitor: &ClipboardMonitor,
+    policy: &storage::SyncPolicy,
+    message: &ClipboardMessage,
+    content: &str,
+    ttl_secs: u64,
+) -> DeliveryStatus {
+    let state = app_handle.state::<AppState>();
+    let history_only = state.settings.read().await.receive_history_only;
+    if !policy.applies_to_clipboard(history_only) {
+        info!(
+            "Refusing self-destructing item from {}: receiving into history only",
+            message.origin_device_name
+        );
+        let _ = app_handle.emit(
+            "clipboard-self-destruct-refused",
+            serde_json::json!({ "deviceName": message.origin_device_name }),
+        );
+        return DeliveryStatus::Delivered;
+    }
+    if let Err(e) = clipboard_monitor.apply_remote(app_handle, content).await {
+        error!("Failed to set clipboard: {}", e);
+        return DeliveryStatus::Delivered;
+    }
+    info!(
+        "Applied self-destructing item from {} ({}s)",
+        message.origin_device_name, ttl_secs
+    );
+    let _ = app_handle.emit(
+        "clipboard-self-destruct",
+        serde_json::json!({
+            "deviceName": message.origin_device_name,
+            "ttlSecs": ttl_secs,
+        }),
+    );
+
+    let app_handle = app_handle.clone();
+    let hash = message.content_hash.clone();
+    let device_name = message.origin_device_name.clone();
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_secs(ttl_secs)).await;
+        let state = app_handle.state::<AppState>();
+        let monitor = state.clipboard_monitor.read().await.clone();
+        if let Some(monitor) = monitor {
+            match monitor.clear_if_current(&app_handle, &hash).await {
+                Ok(true) => {
+                    info!("Cleared self-destructing item from {}", device_name);
+                    let _ = app_handle.emit(
+                        "clipboard-self-destructed",
+                        serde_json::json!({ "deviceName": device_name }),
+                    );
+                }
+                Ok(false) => debug!("Clipboard changed before the self-destruct timer"),
+                Err(e) => warn!("Failed to clear self-destructing item: {}", e),
+            }
+        }
+    });
+    DeliveryStatus::Applied
+}
+
+/// Handle a nearby send message from a (possibly unpaired) peer.
+async fn handle_nearby_message(
+    app_handle: &AppHandle,
+    clipboard_monitor: &ClipboardMonitor,
+    peer_id: String,
+    message: network::protocol::NearbyMessage,
+) {
+    use network::protocol::NearbyMessage;
+    use security::{NearbyTransfer, NearbyTransferState};
+
+    let state = app_handle.state::<AppState>();
+    match message {
+        NearbyMessage::Offer {
+            transfer_id,
+            device_name,
+            public_key,
+        } => {
+            let mut transfer = NearbyTransfer::new(transfer_id.clone(), peer_id.clone(), None);
+            transfer.peer_name = Some(device_name.clone());
+            transfer.peer_public_key = Some(public_key);
+
+            if !state.add_nearby_offer(transfer).await {
+                warn!(
+                    "Too many pending nearby offers, ignoring one from {}",
+                    peer_id
+                );
+                return;
+            }
+            info!("Nearby send offer {} from {}", transfer_id, device_name);
+            let _ = app_handle.emit(
+                "nearby-send-request",
+                serde_json::json!({
+                    "transferId": transfer_id,
+                    "peerId": peer_id,
+                    "deviceName": device_name,
+                }),
+            );
+        }
+
+        NearbyMessage::Accept {
+            transfer_id,
+            public_key,
+        } => {
+            let code = {
+                let mut transfers = state.nearby_transfers.write().await;
+                let transfer = transfers.iter_mut().find(|t| {
+                    t.transfer_id == transfer_id
+                        && t.peer_id == peer_id
+                        && t.is_sender
+                        && t.state == NearbyTransferState::Offered
+                });
+                match transfer {
+                    Some(transfer) => match transfer.agree(&public_key) {
+                        Ok(()) => transfer.confirmation_code.clone(),
+                        Err(e) => {
+                            warn!("Nearby send {} key agreement failed: {}", transfer_id, e);
+                            None
+                        }
+                    },
+                    None => {
+                        debug!("Ignoring accept for unknown nearby send {}", transfer_id);
+                        return;
+                    }
+                }
+            };
+
+            match code {
+                Some(code) => {
+                    let _ = app_handle.emit(
+                        "nearby-send-code",
+                        serde_json::json!({ "transferId": transfer_id, "code": code }),
+                    );
+                }
+                None => {
+                    state.take_nearby_transfer(&transfer_id, &peer_id).await;
+                    let _ = app_handle.emit(
+                        "nearby-send-failed",
+                        serde_json::json!({
+                            "transferId": transfer_id,
+                            "error": "Key agreement failed",
+                        }),
+                    );
+                }
+            }
+        }
+
+        NearbyMessage::Content {
+            transfer_id,
+            encrypted_content,
+            content_hash,
+        } => {
+            let transfer = match state.take_nearby_transfer(&transfer_id, &peer_id).await {
+                Some(t) if !t.is_sender && t.state == NearbyTransferState::AwaitingConfirmation => {
+                    t
+                }
+                _ => {
+                    debug!("Ignoring content for unknown nearby send {}", transfer_id);
+                    return;
+                }
+            };
+
+            let content = transfer
+                .transfer_key()
+                .and_then(|key| security::decrypt_content(&encrypted_content, key).ok())
+                .and_then(|bytes| String::from_utf8(bytes).ok())
+                .filter(|content| security::hash_content(content) == content_hash);
+            let content = match content {
+                Some(content) => content,
+                None => {
+                    warn!(
+                        "Failed to decrypt nearby send {} from {}",
+                        transfer_id, peer_id
+                    );
+                    let _ = app_handle.emit(
+                        "nearby-send-failed",
+                        serde_json::json!({
+                            "transferId": transfer_id,
+                            "error": "Could not decrypt the received item",
+                        }),
+                    );
+                    return;
+                }
+            };
+
+            if let Err(e) = clipboard_monitor.apply_remote(app_handle, &content).await {
+                error!("Failed to set nearby clipboard: {}", e);
+            }
+
+            let device_name = transfer.peer_name.clone().unwrap_or_default();
+            let entry = ClipboardEntry::new_remote(
+                content,
+                content_hash,
+                Utc::now(),
+                state.hlc.now(),
+                &peer_id,
+                &device_name,
+            );
+            state.add_clipboard_entry(entry.clone()).await;
+            let _ = app_handle.emit("clipboard-received", entry);
+            info!("Received nearby send {} from {}", transfer_id, device_name);
+        }
+
+        NearbyMessage::Cancel { transfer_id } => {
+            if state
+                .take_nearby_transfer(&transfer_id, &peer_id)
+                .await
+                .is_some()
+            {
+                let _ = app_handle.emit(
+                    "nearby-send-failed",
+                    serde_json::json!({
+                        "transferId": transfer_id,
+                        "error": "Cancelled by the other device",
+                    }),
+                );
+            }
+        }
+
+        // Acks arrive as responses, see NetworkEvent::NearbyAcknowledged
+        NearbyMessage::Ack { .. } => {}
+    }
+}
+
+/// When a received item leaves history: `HistoryRetention::Expire` counts from now,
+/// not from the send time, so clock skew and queueing don't shorten it.
+fn retention_expiry(retention: HistoryRetention) -> Option<chrono::DateTime<Utc>> {
+    match retention {
+        HistoryRetention::Expire { secs } => {
+            Some(Utc::now() + chrono::Duration::seconds(secs as i64))
+        }
+        HistoryRetention::Keep | HistoryRetention::Skip => None,
+    }
+}
+
+/// Put an item the sender marked `HistoryRetention::Skip` on the clipboard without
+/// adding it to history. It's applied only where a history entry would have been
+/// (the latest item, and not held by history-only mode); otherwise it's dropped,
+/// since there is nowhere to keep it. Returns the status to acknowledge.
+async fn apply_without_history(
+    app_handle: &AppHandle,
+    clipboard_monitor: &ClipboardMonitor,
+    policy: &storage::SyncPolicy,
+    message: &ClipboardMessage,
+    content: &str,
+    selection: ClipboardSelection,
+) -> DeliveryStatus {
+    let state = app_handle.state::<AppState>();
+    let history_only = state.settings.read().await.receive_history_only;
+    if !policy.applies_to_clipboard(history_only) {
+        debug!(
+            "Dropping no-history item from {}: receiving into history only",
+            message.origin_device_name
+        );
+        return DeliveryStatus::Delivered;
+    }
+    if !state
+        .is_latest_clipboard(message.hlc, &message.origin_device_id)
+        .await
+    {
+        debug!(
+            "Dropping older no-history item from {}",
+            message.origin_device_name
+        );
+        return DeliveryStatus::Delivered;
+    }
+
+    let target = receive_target(&state, selection).await;
+    match clipboard_monitor
+        .apply_remote_to(app_handle, content, target)
+        .await
+    {
+        Ok(()) => {
+            info!(
+                "Applied no-history item from {}",
+                message.origin_device_name
+            );
+            DeliveryStatus::Applied
+        }
+        Err(e) => {
+            error!("Failed to set clipboard: {}", e);
+            DeliveryStatus::Delivered
+        }
+    }
+}
+
+/// Handle a clipboard pull message from a peer.
+async fn handle_pull_message(
+    app_handle: &AppHandle,
+    clipboard_monitor: &ClipboardMonitor,
+    peer_id: String,
+    message: network::protocol::PullMessage,
+) {
+    use network::protocol::PullMessage;
+    use state::ClipboardPull;
+
+    let state = app_handle.state::<AppState>();
+    let device_name = {
+        let peers = state.paired_peers.read().await;
+        match peers.iter().find(|p| p.peer_id == peer_id) {
+            Some(peer) => peer.device_name.clone(),
+            None => {
+                debug!(
+                    "Ignoring clipboard pull message from unpaired peer {}",
+                    peer_id
+                );
+                return;
+            }
+        }
+    };
+
+    match message {
+        PullMessage::Request { request_id } => {
+            if !state.settings.read().await.pull_requires_approval {
+                answer_clipboard_pull(app_handle, &peer_id, request_id, true).await;
+                return;
+            }
+
+            let pull = ClipboardPull {
+                request_id: request_id.clone(),
+                peer_id: peer_id.clone(),
+                incoming: true,
+                created_at: Utc::now(),
+            };
+            if !state.add_clipboard_pull(pull).await {
+                warn!(
+                    "Too many pending clipboard pulls, declining one from {}",
+                    peer_id
+                );
+                answer_clipboard_pull(app_handle, &peer_id, request_id, false).await;
+                return;
+            }
+            info!("Clipboard pull {} from {}", request_id, device_name);
+            let _ = app_handle.emit(
+                "clipboard-pull-request",
+                serde_json::json!({
+                    "requestId": request_id,
+                    "peerId": peer_id,
+                    "deviceName": device_name,
+                }),
+            );
+        }
+
+        PullMessage::Reply {
+            request_id,
+            message: msg,
+        } => {
+            if state
+                .take_clipboard_pull(&request_id, Some(&peer_id), false)
+                .await
+                .is_none()
+            {
+                debug!("Ignoring reply to unknown clipboard pull {}", request_id);
+                return;
+            }
+
+            let decrypted = state
+                .decrypt_clipboard_message(&msg, Some(&peer_id), security::MAX_CLOCK_SKEW_SECS)
+                .await;
+            let (content, retention) = match decrypted {
+                Some(sealed) => (sealed.content, sealed.history),
+                None => {
+                    warn!("Failed to decrypt clipboard pull reply from {}", peer_id);
+                    let _ = app_handle.emit(
+                        "clipboard-pull-failed",
+                        serde_json::json!({
+                            "requestId": request_id,
+                            "peerId": peer_id,
+                            "error": "Could not decrypt the received item",
+                        }),
+                    );
+                    return;
+                }
+            };
+
+            // We asked for it, so it replaces the clipboard even if it is older
+            if let Err(e) = clipboard_monitor.apply_remote(app_handle, &content).await {
+                error!("Failed to set pulled clipboard: {}", e);
+            }
+
+            if retention != HistoryRetention::Skip {
+                let mut entry = ClipboardEntry::new_remote(
+                    content,
+                    msg.content_hash.clone(),
+                    msg.timestamp,
+                    msg.hlc,
+                    &msg.origin_device_id,
+                    &msg.origin_device_name,
+                );
+                entry.expires_at = retention_expiry(retention);
+                state.add_clipboard_entry(entry.clone()).await;
+                let _ = app_handle.emit("clipboard-received", entry);
+            }
+            info!("Pulled clipboard from {}", device_name);
+
+            if let Err(e) = state.count_pairing_messages(&[peer_id]).await {
+                warn!("Failed to update pairing message count: {}", e);
+            }
+            expire_temporary_pairings(app_handle).await;
+        }
+
+        PullMessage::Declined { request_id, reason } => {
+            if state
+                .take_clipboard_pull(&request_id, Some(&peer_id), false)
+                .await
+                .is_some()
+            {
+                let _ = app_handle.emit(
+                    "clipboard-pull-failed",
+                    serde_json::json!({
+                        "requestId": request_id,
+                        "peerId": peer_id,
+                        "error": reason,
+                    }),
+                );
+            }
+        }
+
+        // Acks arrive as responses and are only logged
+        PullMessage::Ack { .. } => {}
+    }
+}
+
+/// Answer a clipboard pull from `peer_id` with our latest clipboard entry.
+///
+/// The pull is declined if `accept` is false, the vault is locked, our policy
+/// doesn't allow sending to the peer, or the history is empty.
+pub(crate) async fn answer_clipboard_pull(
+    app_handle: &AppHandle,
+    peer_id: &str,
+    request_id: String,
+    accept: bool,
+) {
+    use network::protocol::PullMessage;
+
+    let state = app_handle.state::<AppState>();
+    let refusal = if !accept {
+        Some("Declined by the other device")
+    } else if *state.vault_status.read().await != VaultStatus::Unlocked {
+        Some("The other device is locked")
+    } else if !state
+        .peer_sync_policy(peer_id)
+        .await
+        .is_some_and(|policy| policy.send)
+    {
+        Some("The other device doesn't share its clipboard with this one")
+    } else {
+        None
+    };
+
+    let message = match refusal {
+        Some(reason) => PullMessage::Declined {
+            request_id,
+            reason: reason.to_string(),
+        },
+        None => match state.latest_clipboard_message_for_peer(peer_id).await {
+            Ok(Some(message)) => PullMessage::Reply {
+                request_id,
+                message,
+            },
+            Ok(None) => PullMessage::Declined {
+                request_id,
+                reason: "The other device's clipboard history is empty".to_string(),
+            },
+            Err(e) => {
+                warn!("Failed to answer clipboard pull from {}: {}", peer_id, e);
+                PullMessage::Declined {
+                    request_id,
+                    reason: "The other device could not send its clipboard".to_string(),
+                }
+            }
+        },
+    };
+    let replied = matches!(message, PullMessage::Reply { .. });
+
+    if let Some(tx) = state.network_command_tx.read().await.as_ref() {
+        let _ = tx
+            .send(NetworkCommand::SendPullMessage {
+                peer_id: peer_id.to_string(),
+                message,
+            })
+            .await;
+    }
+
+    if replied {
+        if let Err(e) = state.count_pairing_messages(&[peer_id.to_string()]).await {
+            warn!("Failed to update pairing message count: {}", e);
+        }
+        expire_temporary_pairings(app_handle).await;
+    }
+}
+
+/// Put a history entry on the system clipboard without sending it to peers again.
+///
+/// `entry_id` defaults to the latest entry received from another device.
+pub(crate) async fn apply_history_entry(
+    app_handle: &AppHandle,
+    entry_id: Option<&str>,
+) -> error::Result<ClipboardEntry> {
+    use error::DecentPasteError;
+
+    let state = app_handle.state::<AppState>();
+    let entry = {
+        let history = state.clipboard_history.read().await;
+        match entry_id {
+            Some(id) => history.iter().find(|e| e.id == id).cloned(),
+            None => history.iter().find(|e| !e.is_local).cloned(),
+        }
+    };
+    let entry =
+        entry.ok_or_else(|| DecentPasteError::InvalidInput("No such history entry".into()))?;
+
+    // Prevent echo: the monitor must not broadcast it as a local copy
+    if let Some(monitor) = state.clipboard_monitor.read().await.as_ref() {
+        monitor.set_last_hash(entry.content_hash.clone()).await;
+    }
+    clipboard::monitor::set_clipboard_content(app_handle, &entry.content)
+        .map_err(DecentPasteError::Clipboard)?;
+    info!("Applied history entry from {}", entry.origin_device_name);
+    Ok(entry)
+}
+
+/// Put back the clipboard content replaced by the last item received from another
+/// device. Returns `false` if there is nothing to undo.
+pub(crate) async fn undo_last_remote_apply(app_handle: &AppHandle) -> error::Result<bool> {
+    let state = app_handle.state::<AppState>();
+    let monitor = state.clipboard_monitor.read().await.clone();
+    let restored = match monitor {
+        Some(monitor) => monitor
+            .undo_remote_apply(app_handle)
+            .await
+            .map_err(error::DecentPasteError::Clipboard)?,
+        None => false,
+    };
+    if restored {
+        info!("Restored clipboard overwritten by a remote item");
+        let _ = app_handle.emit("clipboard-restored", ());
+    }
+    Ok(restored)
+}
+
+/// Remove temporary pairings that reached their expiry or message limit, notify the
+/// peers and tell the frontend (`pairing-expired`).
+pub(crate) async fn expire_temporary_pairings(app_handle: &AppHandle) {
+    let state = app_handle.state::<AppState>();
+    for peer_id in state.expired_pairings().await {
+        match state.unpair_peer(&peer_id).await {
+            Ok(Some(peer)) => {
+                info!(
+                    "Temporary pairing with {} ({}) expired",
+                    peer.device_name, peer_id
+                );
+                let _ = app_handle.emit(
+                    "pairing-expired",
+                    serde_json::json!({
+                        "peerId": peer_id,
+                        "deviceName": peer.device_name,
+                    }),
+                );
+
+                // Show the device as discoverable again
+                if let Some(tx) = state.network_command_tx.read().await.as_ref() {
+                    let _ = tx.send(NetworkCommand::RefreshPeer { peer_id }).await;
+                }
+            }
+            Ok(None) => {}
+            Err(e) => warn!("Failed to remove expired pairing {}: {}", peer_id, e),
+        }
+    }
+}
+
+/// Handle a history reconciliation payload from a paired peer.
+///
+/// Entries received this way only go into the history; the system clipboard is
+/// never changed.
+async fn handle_history_sync(app_handle: &AppHandle, peer_id: String, encrypted_payload: Vec<u8>) {
+    use clipboard::reconcile::{bucket_digests, bucket_of, diff_summary, mismatched_buckets};
+    use network::protocol::HistoryPayload;
+
+    let state = app_handle.state::<AppState>();
+    if !state.history_sync_allowed(&peer_id).await {
+        debug!(
+            "Ignoring history sync from {}: not enabled for this peer",
+            peer_id
+        );
+        return;
+    }
+    let payload = match state
+        .open_history_payload(&peer_id, &encrypted_payload)
+        .await
+    {
+        Ok(payload) => payload,
+        Err(e) => {
+            warn!("Ignoring history sync from {}: {}", peer_id, e);
+            return;
+        }
+    };
+    let own_limit = state.settings.read().await.clipboard_history_limit;
+
+    match payload {
+        HistoryPayload::Digest { limit, buckets } => {
+            let limit = limit.min(own_limit);
+            let items = state.history_items(limit).await;
+            let mismatched = mismatched_buckets(&bucket_digests(&items), &buckets);
+            if mismatched.is_empty() {
+                debug!("History already in sync with {}", peer_id);
+                return;
+            }
+
+            let items = items
+                .into_iter()
+                .filter(|item| mismatched.contains(&bucket_of(&item.hash)))
+                .collect();
+            let reply = HistoryPayload::Summary {
+                limit,
+                buckets: mismatched,
+                items,
+            };
+            if let Err(e) = state.send_history_payload(&peer_id, &reply).await {
+                warn!("Failed to send history summary to {}: {}", peer_id, e);
+            }
+        }
+
+        HistoryPayload::Summary {
+            limit,
+            buckets,
+            items,
+        } => {
+            let limit = limit.min(own_limit);
+            let ours = state.history_items(limit).await;
+            let diff = diff_summary(&ours, &buckets, &items);
+
+            if state
+                .merge_history(Vec::new(), &diff.their_tombstones)
+                .await
+            {
+                let _ = app_handle.emit("history-synced", serde_json::json!({ "peerId": peer_id }));
+            }
+
+            if !diff.fetch.is_empty() {
+                let request = HistoryPayload::Fetch { hashes: diff.fetch };
+                if let Err(e) = state.send_history_payload(&peer_id, &request).await {
+                    warn!("Failed to request history entries from {}: {}", peer_id, e);
+                }
+            }
+            if !diff.push.is_empty() || !diff.push_tombstones.is_empty() {
+                if let Err(e) = state
+                    .send_history_entries(&peer_id, &diff.push, diff.push_tombstones)
+                    .await
+                {
+                    warn!("Failed to send history entries to {}: {}", peer_id, e);
+                }
+            }
+        }
+
+        HistoryPayload::Fetch { hashes } => {
+            if let Err(e) = state
+                .send_history_entries(&peer_id, &hashes, Vec::new())
+                .await
+            {
+                warn!("Failed to send history entries to {}: {}", peer_id, e);
+            }
+        }
+
+        HistoryPayload::Entries {
+            entries,
+            tombstones,
+        } => {
+            let entries: Vec<ClipboardEntry> = entries
+                .into_iter()
+                .filter(|e| security::hash_content(&e.content) == e.content_hash)
+                .map(|e| {
+                    ClipboardEntry::new_remote(
+                        e.content,
+                        e.content_hash,
+                        e.timestamp,
+                        e.hlc,
+                        &e.origin_device_id,
+                        &e.origin_device_name,
+                    )
+                })
+                .collect();
+            let count = entries.len();
+            if state.merge_history(entries, &tombstones).await {
+                info!(
+                    "Merged {} history entries and {} deletions from {}",
+                    count,
+                    tombstones.len(),
+                    peer_id
+                );
+                let _ = app_handle.emit("history-synced", serde_json::json!({ "peerId": peer_id }));
+            }
+        }
+    }
+}
+
+/// Compare a paired peer's presented identity key with the recorded one and warn the
+/// user (`peer-key-changed`) if it differs.
+async fn check_peer_identity_key(
+    app_handle: &AppHandle,
+    state: &AppState,
+    peer_id: &str,
+    public_key: &[u8],
+) {
+    match state.check_peer_identity_key(peer_id, public_key).await {
+        Ok(Some(peer)) => {
+            warn!(
+                "Paired peer {} ({}) presented a different identity key",
+                peer.device_name, peer_id
+            );
+            let _ = app_handle.emit(
+                "peer-key-changed",
+                serde_json::json!({
+                    "peerId": peer_id,
+                    "deviceName": peer.device_name,
+                }),
+            );
+        }
+        Ok(None) => {}
+        Err(e) => warn!("Failed to check identity key of {}: {}", peer_id, e),
+    }
+}
+
+/// Periodically move pairing sessions past `pairing_timeout_secs` to `Expired`,
+/// release their response channels, notify the peer and tell the frontend.
+/// Also removes temporary pairings whose time is up.
+fn spawn_pairing_session_sweeper(
+    app_handle: AppHandle,
+    network_cmd_tx: mpsc::Sender<NetworkCommand>,
+) {
+    tokio::spawn(async move {
+        let state = app_handle.state::<AppState>();
+        let mut interval = tokio::time::interval(PAIRING_SWEEP_INTERVAL);
+
+        loop {
+            interval.tick().await;
+
+            for session in state.expire_pairing_sessions().await {
+                info!(
+                    "Pairing session {} with {} timed out",
+                    session.session_id, session.peer_id
+                );
+
+                if let Err(e) = network_cmd_tx
+                    .send(NetworkCommand::ExpirePairing {
+                        peer_id: session.peer_id.clone(),
+                        session_id: session.session_id.clone(),
+                    })
+                    .await
+                {
+                    warn!("Failed to notify peer of pairing timeout: {}", e);
+                }
+
+                let _ = app_handle.emit(
+                    "pairing-failed",
+                    serde_json::json!({
+                        "sessionId": session.session_id,
+                        "error": PAIRING_TIMEOUT_ERROR,
+                        "reason": "timeout",
+                    }),
+                );
+            }
+
+            for (transfer_id, peer_id) in state.expire_nearby_transfers().await {
+                let _ = network_cmd_tx
+                    .send(NetworkCommand::SendNearbyMessage {
+                        peer_id,
+                        message: network::protocol::NearbyMessage::Cancel {
+                            transfer_id: transfer_id.clone(),
+                        },
+                    })
+                    .await;
+                let _ = app_handle.emit(
+                    "nearby-send-failed",
+                    serde_json::json!({
+                        "transferId": transfer_id,
+                        "error": "Nearby send timed out",
+                    }),
+                );
+            }
+
+            for pull in state.expire_clipboard_pulls().await {
+                if pull.incoming {
+                    // Nobody answered the approval prompt
+                    answer_clipboard_pull(&app_handle, &pull.peer_id, pull.request_id, false).await;
+                } else {
+                    let _ = app_handle.emit(
+                        "clipboard-pull-failed",
+                        serde_json::json!({
+                            "requestId": pull.request_id,
+                            "peerId": pull.peer_id,
+                            "error": "The other device didn't answer",
+                        }),
+                    );
+                }
+            }
+
+            state.purge_expired_buffered_messages().await;
+
+            let expired = state.expire_clipboard_entries().await;
+            if !expired.is_empty() {
+                // Don't leave an expired item on the clipboard either
+                if let Some(monitor) = state.clipboard_monitor.read().await.as_ref() {
+                    for entry in &expired {
+                        if let Err(e) = monitor
+                            .clear_if_current(&app_handle, &entry.content_hash)
+                            .await
+                        {
+                            warn!("Failed to clear expired clipboard item: {}", e);
+                        }
+                    }
+                }
+                if let Err(e) = state.record_history_deletions(&expired).await {
+                    warn!("Failed to record history deletions: {}", e);
+                }
+                let entry_ids: Vec<&str> = expired.iter().map(|e| e.id.as_str()).collect();
+                let _ = app_handle.emit(
+                    "clipboard-entries-expired",
+                    serde_json::json!({ "entryIds": entry_ids }),
+                );
+            }
+
+            for (entry_id, peer_id) in state.expire_pending_deliveries().await {
+                debug!("Delivery of {} to {} failed", entry_id, peer_id);
+                let _ = app_handle.emit(
+                    "delivery-status",
+                    serde_json::json!({
+                        "entryId": entry_id,
+                        "peerId": peer_id,
+                        "status": DeliveryStatus::Failed,
+                    }),
+                );
+            }
+
+            expire_temporary_pairings(&app_handle).await;
+        }
+    });
+}
@@ -1,7 +1,9 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
-use super::protocol::{ClipboardMessage, MessageHash, PairingRequest};
+use super::protocol::{
+    ClipboardMessage, MessageHash, PairingRequest, PakeConfirm, PakeReply, PakeStart,
+};
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum NetworkStatus {
@@ -37,6 +39,11 @@ pub enum NetworkEvent {
         peer_id: String,
         device_name: String,
     },
+    /// A peer announced its identity key in a signed DeviceAnnounce message
+    PeerIdentityAnnounced {
+        peer_id: String,
+        public_key: Vec<u8>,
+    },
 
     // Connection events
     PeerConnected(ConnectedPeer),
@@ -64,6 +71,7 @@ pub enum NetworkEvent {
         pin: String,
         peer_device_name: String, // Responder's device name (for initiator to display)
         peer_public_key: Vec<u8>, // Responder's X25519 public key for ECDH
+        invite_proof: Option<Vec<u8>>, // Set when pairing via a pairing URI
     },
     PairingComplete {
         session_id: String,
@@ -74,6 +82,21 @@ pub enum NetworkEvent {
         session_id: String,
         error: String,
     },
+    /// Passphrase pairing: an initiator sent its SPAKE2 message (we are responder)
+    PakeStartReceived {
+        peer_id: String,
+        start: PakeStart,
+    },
+    /// Passphrase pairing: the responder answered our PakeStart (we are initiator)
+    PakeReplyReceived {
+        peer_id: String,
+        reply: PakeReply,
+    },
+    /// Passphrase pairing: the initiator sent its key confirmation (we are responder)
+    PakeConfirmReceived {
+        peer_id: String,
+        confirm: PakeConfirm,
+    },
     /// Outbound pairing-related request failed at the network level
     OutboundPairingFailed {
         peer_id: String,
@@ -81,7 +104,12 @@ pub enum NetworkEvent {
     },
 
     // Clipboard events
-    ClipboardReceived(ClipboardMessage),
+    ClipboardReceived {
+        /// Publisher of the gossipsub message (authenticated by its signature),
+        /// or the peer that sent it directly
+        peer_id: Option<String>,
+        message: ClipboardMessage,
+    },
     ClipboardSent {
         id: String,
         peer_count: usize,
@@ -89,9 +117,77 @@ pub enum NetworkEvent {
 
     // Status events
     StatusChanged(NetworkStatus),
+    /// We started listening on a new address (used to build pairing URIs)
+    ListenAddressAdded(String),
+    /// A listen address is no longer available
+    ListenAddressRemoved(String),
     #[allow(dead_code)]
     Error(String),
 
+    // Pairing group events
+    /// A peer sent us an encrypted `GroupPayload` (not yet authenticated).
+    GroupMessageReceived {
+        peer_id: String,
+        encrypted_payload: Vec<u8>,
+    },
+
+    // History reconciliation events
+    /// A peer sent us an encrypted `HistoryPayload` (not yet authenticated).
+    HistorySyncReceived {
+        peer_id: String,
+        encrypted_payload: Vec<u8>,
+    },
+
+    // Delivery events
+    /// A recipient acknowledged one of our clipboard messages (not yet authenticated).
+    DeliveryReportReceived {
+        peer_id: String,
+        encrypted_payload: Vec<u8>,
+    },
+
+    // Unpair events
+    /// A peer told us it removed our pairing (proof not yet verified).
+    UnpairReceived {
+        peer_id: String,
+        proof: Vec<u8>,
+    },
+    /// A peer acknowledged our `Unpair` notification.
+    UnpairAcknowledged {
+        peer_id: String,
+    },
+
+    // Nearby send events
+    /// A nearby send message (anything but `Ack`) from a possibly unpaired peer.
+    NearbyMessageReceived {
+        peer_id: String,
+        message: super::protocol::NearbyMessage,
+    },
+    /// A peer acknowledged one of our nearby send messages.
+    NearbyAcknowledged {
+        peer_id: String,
+        transfer_id: String,
+    },
+
+    // Clipboard pull events
+    /// A clipboard pull message (anything but `Ack`) from a peer.
+    PullMessageReceived {
+        peer_id: String,
+        message: super::protocol::PullMessage,
+    },
+
+    // Identity rotation events
+    /// A peer announced new identity keys (not yet decrypted or verified).
+    /// Answered with `NetworkCommand::AnswerIdentityRotation`.
+    IdentityRotationReceived {
+        peer_id: String,
+        old_peer_id: String,
+        encrypted_payload: Vec<u8>,
+    },
+    /// A peer acknowledged our identity rotation.
+    IdentityRotationAcknowledged {
+        peer_id: String,
+    },
+
     // Sync events (for offline message delivery)
     /// A peer requested sync from us - we should send them our buffered hashes.
     SyncRequestReceived {
@@ -108,6 +204,11 @@ pub enum NetworkEvent {
         peer_id: String,
         hashes: Vec<MessageHash>,
     },
+    /// A paired peer asked us to hold a message for another device.
+    SyncRelayReceived {
+        peer_id: String,
+        message: ClipboardMessage,
+    },
     /// Received full message content after requesting via ContentRequest.
     /// Contains the clipboard message to be decrypted and added to history.
     SyncContentReceived {
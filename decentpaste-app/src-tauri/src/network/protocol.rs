@@ -1,6 +1,13 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
+use crate::clipboard::{
+    ClipboardEntry, ClipboardSelection, DeliveryStatus, HistoryItemRef, HistoryTombstone,
+    HlcTimestamp,
+};
+use crate::security::IdentityRotation;
+use crate::storage::PairingGroup;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum ProtocolMessage {
     Pairing(PairingMessage),
@@ -12,6 +19,22 @@ pub enum ProtocolMessage {
     /// Sync protocol messages for clipboard history synchronization.
     /// Used to deliver missed clipboard messages to peers who were offline.
     Sync(SyncMessage),
+    /// Pairing group control messages (membership changes, re-keying).
+    Group(GroupMessage),
+    /// The sender removed its pairing with the recipient.
+    Unpair(UnpairMessage),
+    /// The sender replaced its identity keys (and possibly its PeerId).
+    Rotation(RotationMessage),
+    /// One-shot send to a device we are not paired with.
+    Nearby(NearbyMessage),
+    /// Full history reconciliation between paired devices (opt-in).
+    History(HistoryMessage),
+    /// Acknowledges a clipboard message to its sender.
+    Delivery(DeliveryMessage),
+    /// Clipboard message sent to one paired peer over request-response.
+    Direct(DirectMessage),
+    /// Asks a paired peer for its latest clipboard entry.
+    Pull(PullMessage),
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -32,6 +55,189 @@ pub enum SyncMessage {
     /// Response containing full clipboard message content.
     /// The message is already encrypted for the requesting peer.
     ContentResponse { message: ClipboardMessage },
+    /// Hold `message` for `message.relay.recipient_peer_id` and hand it over when
+    /// the recipient syncs with us.
+    Relay { message: ClipboardMessage },
+    /// Reply to `Relay` (request-response always needs a response).
+    RelayAck,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum GroupMessage {
+    /// A `GroupPayload` (JSON) encrypted with the pairwise shared secret of sender
+    /// and recipient. Successful decryption authenticates the sender.
+    Update { encrypted_payload: Vec<u8> },
+    /// Reply to `Update` (request-response always needs a response).
+    Ack,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum HistoryMessage {
+    /// A `HistoryPayload` (JSON) encrypted with the pairwise shared secret.
+    Sync { encrypted_payload: Vec<u8> },
+    /// Reply to `Sync` (request-response always needs a response).
+    Ack,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum DirectMessage {
+    /// Encrypted with the pairwise shared secret; handled like a gossipsub message.
+    Clipboard(ClipboardMessage),
+    /// Reply to `Clipboard`: the message reached the peer (see `DeliveryReport`
+    /// for what it did with it).
+    Ack { message_id: String },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum PullMessage {
+    /// Ask for the peer's latest clipboard entry.
+    Request { request_id: String },
+    /// The entry, encrypted with the pairwise shared secret like a `Direct` message.
+    Reply {
+        request_id: String,
+        message: ClipboardMessage,
+    },
+    /// The peer refused the request (policy, locked vault, declined by its user)
+    /// or had nothing to send.
+    Declined { request_id: String, reason: String },
+    /// Reply to any of the above (request-response always needs a response).
+    Ack { request_id: String },
+}
+
+impl PullMessage {
+    pub fn request_id(&self) -> &str {
+        match self {
+            Self::Request { request_id }
+            | Self::Reply { request_id, .. }
+            | Self::Declined { request_id, .. }
+            | Self::Ack { request_id } => request_id,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum DeliveryMessage {
+    /// A `DeliveryReport` (JSON) encrypted with the pairwise shared secret.
+    Report { encrypted_payload: Vec<u8> },
+    /// Reply to `Report` (request-response always needs a response).
+    Ack,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum UnpairMessage {
+    /// `proof` is an HMAC keyed by the (now deleted) shared secret,
+    /// see `security::unpair_proof`.
+    Request { proof: Vec<u8> },
+    /// Reply to `Request`. The sender keeps retrying until it sees this.
+    Ack,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum RotationMessage {
+    /// A `RotationPayload` (JSON) encrypted with the shared secret the recipient
+    /// holds for `old_peer_id`, the sender's PeerId before the rotation.
+    Announce {
+        old_peer_id: String,
+        encrypted_payload: Vec<u8>,
+    },
+    /// Reply to `Announce`. The sender keeps retrying until it sees this.
+    Ack,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum NearbyMessage {
+    /// The sender offers one clipboard item. `public_key` is an ephemeral X25519 key.
+    Offer {
+        transfer_id: String,
+        device_name: String,
+        public_key: Vec<u8>,
+    },
+    /// The receiver accepted; its ephemeral key completes the ECDH.
+    Accept {
+        transfer_id: String,
+        public_key: Vec<u8>,
+    },
+    /// The item, encrypted with the transfer key. Only sent after the sender's
+    /// user confirmed that both devices show the same code.
+    Content {
+        transfer_id: String,
+        encrypted_content: Vec<u8>,
+        content_hash: String,
+    },
+    /// Either side declined or cancelled the transfer.
+    Cancel { transfer_id: String },
+    /// Reply to any of the above (request-response always needs a response).
+    Ack { transfer_id: String },
+}
+
+impl NearbyMessage {
+    pub fn transfer_id(&self) -> &str {
+        match self {
+            Self::Offer { transfer_id, .. }
+            | Self::Accept { transfer_id, .. }
+            | Self::Content { transfer_id, .. }
+            | Self::Cancel { transfer_id }
+            | Self::Ack { transfer_id } => transfer_id,
+        }
+    }
+}
+
+/// Decrypted contents of `RotationMessage::Announce`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RotationPayload {
+    pub rotation: IdentityRotation,
+    /// Whether the recipient should replace the shared secret with one derived
+    /// from the new X25519 key. False for group pairings, whose secret comes
+    /// from the group key.
+    pub rekey: bool,
+}
+
+/// Decrypted contents of `GroupMessage::Update`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum GroupPayload {
+    /// Full group state. Sent on every membership change and whenever a member
+    /// reconnects, so members that were offline catch up.
+    State(PairingGroup),
+    /// The recipient was removed from the group.
+    Removed { group_id: String },
+    /// The sender left the group.
+    Left { group_id: String },
+    /// The sender declined our invitation to the group.
+    Declined { group_id: String },
+}
+
+/// Decrypted contents of `HistoryMessage::Sync`.
+///
+/// The initiator sends `Digest`; the other side answers with a `Summary` of the
+/// buckets that differ. The initiator then fetches what it lacks and pushes
+/// what the other side lacks, both as `Entries`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum HistoryPayload {
+    /// Bucket digests over the sender's newest `limit` entries and its tombstones.
+    Digest { limit: usize, buckets: Vec<String> },
+    /// Items of the mismatched `buckets`, over the newest `limit` entries
+    /// (the smaller of both history limits).
+    Summary {
+        limit: usize,
+        buckets: Vec<usize>,
+        items: Vec<HistoryItemRef>,
+    },
+    /// Ask for the entries with these content hashes.
+    Fetch { hashes: Vec<String> },
+    /// History entries and tombstones for the recipient to merge.
+    Entries {
+        entries: Vec<ClipboardEntry>,
+        tombstones: Vec<HistoryTombstone>,
+    },
+}
+
+/// Decrypted contents of `DeliveryMessage::Report`: what the recipient did
+/// with a clipboard message (`Delivered` or `Applied`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeliveryReport {
+    pub message_id: String,
+    pub content_hash: String,
+    pub status: DeliveryStatus,
 }
 
 /// Represents a hash of a buffered message with its timestamp.
@@ -40,8 +246,11 @@ pub enum SyncMessage {
 pub struct MessageHash {
     /// content_hash from ClipboardMessage
     pub hash: String,
-    /// Original message timestamp for chronological sorting
+    /// Original message timestamp (display and TTL only)
     pub timestamp: DateTime<Utc>,
+    /// Original message HLC, for ordering
+    #[serde(default)]
+    pub hlc: HlcTimestamp,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -50,6 +259,14 @@ pub enum PairingMessage {
     Challenge(PairingChallenge),
     Response(PairingResponse),
     Confirm(PairingConfirm),
+    /// Passphrase pairing (SPAKE2): initiator's first message.
+    /// Answered with `PakeReply`.
+    PakeStart(PakeStart),
+    /// Passphrase pairing: responder's SPAKE2 message and key confirmation.
+    PakeReply(PakeReply),
+    /// Passphrase pairing: initiator's key confirmation.
+    /// Answered with a regular `PairingConfirm`.
+    PakeConfirm(PakeConfirm),
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -58,6 +275,9 @@ pub struct PairingRequest {
     pub device_name: String,
     pub device_id: String,
     pub public_key: Vec<u8>,
+    /// Proof of the one-time secret from a pairing URI; replaces the PIN when present
+    #[serde(default)]
+    pub invite_proof: Option<Vec<u8>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -66,6 +286,9 @@ pub struct PairingChallenge {
     pub pin: String,
     pub device_name: String, // Responder's device name
     pub public_key: Vec<u8>, // Responder's X25519 public key for ECDH
+    /// Responder's proof of the pairing URI secret (PIN is empty when present)
+    #[serde(default)]
+    pub invite_proof: Option<Vec<u8>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -75,6 +298,34 @@ pub struct PairingResponse {
     pub accepted: bool,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PakeStart {
+    pub session_id: String,
+    pub device_name: String,
+    pub message: Vec<u8>, // SPAKE2 message (side A)
+    /// Initiator's X25519 identity key, bound into the agreed key
+    pub public_key: Vec<u8>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PakeReply {
+    pub session_id: String,
+    pub device_name: String,
+    pub message: Vec<u8>, // SPAKE2 message (side B)
+    /// Responder's X25519 identity key, bound into the agreed key
+    pub public_key: Vec<u8>,
+    pub confirmation: Vec<u8>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PakeConfirm {
+    pub session_id: String,
+    pub confirmation: Vec<u8>,
+}
+
+/// Error carried in a failed `PairingConfirm` when a session timed out.
+pub const PAIRING_TIMEOUT_ERROR: &str = "Pairing timed out";
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PairingConfirm {
     pub session_id: String,
@@ -88,9 +339,123 @@ pub struct ClipboardMessage {
     pub id: String,
     pub content_hash: String,
     pub encrypted_content: Vec<u8>,
+    /// Wall-clock send time (display, TTL and clock-skew checks)
     pub timestamp: DateTime<Utc>,
+    /// Sender's hybrid logical clock, used for history ordering and "latest wins"
+    #[serde(default)]
+    pub hlc: HlcTimestamp,
     pub origin_device_id: String,
     pub origin_device_name: String,
+    /// Set when `encrypted_content` is encrypted with a pairing group key
+    /// instead of a pairwise shared secret.
+    #[serde(default)]
+    pub group_id: Option<String>,
+    /// Set while the message is held by a relay instead of its sender.
+    #[serde(default)]
+    pub relay: Option<RelayInfo>,
+    /// Set for self-destructing items: queued copies (ours or a relay's) are dropped
+    /// after this. The TTL the receiver acts on is sealed in `SealedClipboard`.
+    #[serde(default)]
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+/// Store-and-forward metadata of a relayed clipboard message.
+///
+/// Not sealed: the message is encrypted with the pairwise secret of its origin and
+/// recipient, so a relay can drop it or change these fields but can't read or
+/// forge it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RelayInfo {
+    /// Device that encrypted the message (its shared secret decrypts it)
+    pub origin_peer_id: String,
+    /// Device the message is for
+    pub recipient_peer_id: String,
+    /// How many more relays may pass the message on
+    pub hops_left: u8,
+}
+
+/// Current `SealedClipboard::version`. Bump it when older receivers must not
+/// read the payload; fields they can safely ignore don't need a bump.
+///
+/// Payloads are sealed with the oldest version that reads them correctly (see
+/// `SealedClipboard::required_version`), so ordinary items still reach older devices.
+pub const SEALED_CLIPBOARD_VERSION: u8 = 2;
+
+/// What the receiver may do with a received item in its clipboard history.
+///
+/// Set by the sender's sensitive-content rules (`SensitiveAction::NoHistory` and
+/// `SensitiveAction::AutoExpire`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum HistoryRetention {
+    /// Add the item to history as usual
+    #[default]
+    Keep,
+    /// Apply the item to the clipboard, but never add it to history
+    Skip,
+    /// Add the item to history and remove it `secs` after it was received
+    Expire { secs: u64 },
+}
+
+/// Plaintext of `ClipboardMessage::encrypted_content`.
+///
+/// The outer message's ID, timestamp and origin are repeated here so the
+/// encryption authenticates them; the ID feeds the receiver's replay window.
+#[derive(Serialize, Deserialize)]
+pub struct SealedClipboard {
+    /// Payload format (`SEALED_CLIPBOARD_VERSION` when sealed by this build)
+    #[serde(default)]
+    pub version: u8,
+    pub message_id: String,
+    pub timestamp: DateTime<Utc>,
+    pub hlc: HlcTimestamp,
+    pub origin_device_id: String,
+    pub content: String,
+    /// Self-destruct: the receiver clears the item from its clipboard this many
+    /// seconds after applying it, and never adds it to history.
+    #[serde(default)]
+    pub ttl_secs: Option<u64>,
+    /// Where the sender copied the item from; receivers write PRIMARY items to
+    /// their `primary_receive_target`.
+    #[serde(default)]
+    pub selection: ClipboardSelection,
+    /// Whether the receiver may keep the item in history (sensitive content)
+    #[serde(default)]
+    pub history: HistoryRetention,
+}
+
+impl SealedClipboard {
+    /// Oldest version that handles this payload correctly. Self-destructing items
+    /// and a `history` other than `Keep` need 2: version 1 receivers could keep the
+    /// item in history, so they must refuse it.
+    pub fn required_version(&self) -> u8 {
+        if self.ttl_secs.is_some() || self.history != HistoryRetention::Keep {
+            2
+        } else {
+            1
+        }
+    }
+
+    /// Parse a decrypted payload, explaining why it can't be read.
+    ///
+    /// Versions before sealed payloads encrypted the clipboard text itself; those
+    /// messages are refused rather than shown without replay protection.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
+        #[derive(Deserialize)]
+        struct Header {
+            #[serde(default)]
+            version: u8,
+        }
+
+        match serde_json::from_slice::<Header>(bytes) {
+            Ok(header) if header.version > SEALED_CLIPBOARD_VERSION => Err(format!(
+                "sealed with format {} (this version reads up to {}); update this device",
+                header.version, SEALED_CLIPBOARD_VERSION
+            )),
+            Ok(_) => serde_json::from_slice(bytes)
+                .map_err(|e| format!("malformed sealed payload: {}", e)),
+            Err(_) => Err("not sealed; the sender runs an older version".to_string()),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -107,6 +472,9 @@ pub struct DeviceAnnounceMessage {
     pub peer_id: String,
     pub device_name: String,
     pub timestamp: DateTime<Utc>,
+    /// Sender's X25519 identity key, checked against the key recorded at pairing time
+    #[serde(default)]
+    pub public_key: Option<Vec<u8>>,
 }
 
 impl ProtocolMessage {
@@ -16,7 +16,9 @@ use super::behaviour::{
     PairingResponse as ReqPairingResponse,
 };
 use super::events::{ConnectedPeer, DiscoveredPeer, NetworkEvent, NetworkStatus};
-use super::protocol::{ClipboardMessage, DeviceAnnounceMessage, PairingMessage, ProtocolMessage};
+use super::protocol::{
+    ClipboardMessage, DeviceAnnounceMessage, PairingMessage, ProtocolMessage, PAIRING_TIMEOUT_ERROR,
+};
 
 #[derive(Debug)]
 pub enum NetworkCommand {
@@ -33,12 +35,27 @@ pub enum NetworkCommand {
         session_id: String,
         pin: String,
         device_name: String,
-        public_key: Vec<u8>, // Our X25519 public key for ECDH
+        public_key: Vec<u8>,           // Our X25519 public key for ECDH
+        invite_proof: Option<Vec<u8>>, // Set when answering a pairing URI request
+    },
+    /// Answer a passphrase pairing `PakeStart` with our SPAKE2 message.
+    SendPakeReply {
+        peer_id: String,
+        reply: super::protocol::PakeReply,
+    },
+    /// Answer the initiator's `PakeConfirm` once its key confirmation was checked.
+    /// Completes (or fails) pairing on our side like an inbound `PairingConfirm`.
+    AnswerPakeConfirm {
+        peer_id: String,
+        session_id: String,
+        success: bool,
+        device_name: String, // Initiator's device name (for PairingComplete)
     },
     /// Reject a pairing request
     RejectPairing {
         peer_id: String,
         session_id: String,
+        reason: Option<String>, // Defaults to "Pairing rejected by user"
     },
     /// Send pairing confirmation (after PIN verification on initiator side)
     SendPairingConfirm {
@@ -47,10 +64,22 @@ pub enum NetworkCommand {
         success: bool,
         device_name: String,
     },
+    /// Tell a peer that a pairing session timed out locally.
+    /// Drops any ResponseChannel still held for that session.
+    ExpirePairing {
+        peer_id: String,
+        session_id: String,
+    },
     BroadcastClipboard {
         message: ClipboardMessage,
     },
     GetPeers,
+    /// Dial a peer at explicit addresses (e.g. from a pairing URI) that
+    /// mDNS may never discover.
+    DialPeer {
+        peer_id: String,
+        addresses: Vec<String>,
+    },
     /// Force reconnection to all discovered peers (used after app resume from background).
     /// Also attempts to reconnect to paired peers using their last-known addresses
     /// as fallback when mDNS hasn't rediscovered them yet.
@@ -62,7 +91,6 @@ pub enum NetworkCommand {
     },
     /// Re-emit PeerDiscovered event for a specific peer (used after unpairing to make peer
     /// appear in discovered list again)
-    #[allow(dead_code)]
     RefreshPeer {
         peer_id: String,
     },
@@ -73,6 +101,73 @@ pub enum NetworkCommand {
         device_name: String,
     },
 
+    /// Send an encrypted pairing group update to a peer.
+    SendGroupMessage {
+        peer_id: String,
+        encrypted_payload: Vec<u8>,
+    },
+
+    /// Send an encrypted history reconciliation payload to a paired peer.
+    SendHistorySync {
+        peer_id: String,
+        encrypted_payload: Vec<u8>,
+    },
+
+    /// Send a clipboard message to one peer over request-response.
+    SendDirectClipboard {
+        peer_id: String,
+        message: ClipboardMessage,
+    },
+
+    /// Acknowledge a clipboard message to its sender.
+    SendDeliveryReport {
+        peer_id: String,
+        encrypted_payload: Vec<u8>,
+    },
+
+    /// Tell a peer we removed our pairing with it.
+    SendUnpair {
+        peer_id: String,
+        proof: Vec<u8>,
+    },
+
+    /// Tell a paired peer we rotated our identity keys.
+    SendIdentityRotation {
+        peer_id: String,
+        old_peer_id: String,
+        encrypted_payload: Vec<u8>,
+    },
+
+    /// Answer a peer's identity rotation once lib.rs has applied it (or found it
+    /// already applied). Without `applied` no ack is sent, so the peer retries.
+    AnswerIdentityRotation {
+        peer_id: String,
+        applied: bool,
+    },
+
+    /// Send a nearby send message to a (possibly unpaired) peer.
+    SendNearbyMessage {
+        peer_id: String,
+        message: super::protocol::NearbyMessage,
+    },
+
+    /// Send a clipboard pull message to a paired peer.
+    SendPullMessage {
+        peer_id: String,
+        message: super::protocol::PullMessage,
+    },
+
+    /// Change the identity key included in DeviceAnnounce (`None` omits it).
+    SetAnnouncedIdentityKey {
+        public_key: Option<Vec<u8>>,
+    },
+
+    /// Ask a paired peer to hold a message for another device (store-and-forward).
+    SendRelayMessage {
+        peer_id: String,
+        message: ClipboardMessage,
+    },
+
     /// Request sync from a specific peer (hash-first protocol).
     /// Sent when we reconnect after being offline to get missed messages.
     /// Peer will respond with HashListResponse containing available message hashes.
@@ -109,11 +204,19 @@ pub struct NetworkManager {
     discovered_peers: HashMap<PeerId, DiscoveredPeer>,
     connected_peers: HashMap<PeerId, ConnectedPeer>,
     pending_responses: HashMap<PeerId, ResponseChannel<ReqPairingResponse>>,
+    /// Session IDs of pairing requests whose ResponseChannel is held in pending_responses.
+    /// Lets an expiring session answer (and drop) only its own channel.
+    pending_pairing_channels: HashMap<PeerId, String>,
+    /// ResponseChannels of identity rotation announcements waiting for lib.rs.
+    pending_rotation_acks: HashMap<PeerId, ResponseChannel<ReqPairingResponse>>,
     /// Peers confirmed ready for broadcast (currently: subscribed to clipboard topic).
     /// This is protocol-specific tracking; the protocol-agnostic state is in AppState.ready_peers.
     ready_peers: HashMap<PeerId, Instant>,
     /// Current device name (updated when settings change)
     device_name: String,
+    /// Our X25519 identity key, included in DeviceAnnounce so peers can detect key changes.
+    /// `None` while an identity rotation is still being delivered.
+    identity_public_key: Option<Vec<u8>>,
     /// Track outbound pairing request IDs to correlate OutboundFailure events
     pending_pairing_requests: HashMap<OutboundRequestId, String>,
     /// Cache device names that arrive (via Identify or DeviceAnnounce) before mDNS creates the peer entry
@@ -126,6 +229,7 @@ impl NetworkManager {
         event_tx: mpsc::Sender<NetworkEvent>,
         local_key: libp2p::identity::Keypair,
         device_name: String,
+        identity_public_key: Option<Vec<u8>>,
     ) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
         let local_peer_id = PeerId::from(local_key.public());
         info!("Local peer ID: {}", local_peer_id);
@@ -152,8 +256,11 @@ impl NetworkManager {
             discovered_peers: HashMap::new(),
             connected_peers: HashMap::new(),
             pending_responses: HashMap::new(),
+            pending_pairing_channels: HashMap::new(),
+            pending_rotation_acks: HashMap::new(),
             ready_peers: HashMap::new(),
             device_name,
+            identity_public_key,
             pending_pairing_requests: HashMap::new(),
             pending_device_names: HashMap::new(),
         })
@@ -211,12 +318,24 @@ impl NetworkManager {
         match event {
             SwarmEvent::NewListenAddr { address, .. } => {
                 info!("Listening on {}", address);
+                let _ = self
+                    .event_tx
+                    .send(NetworkEvent::ListenAddressAdded(address.to_string()))
+                    .await;
                 let _ = self
                     .event_tx
                     .send(NetworkEvent::StatusChanged(NetworkStatus::Connected))
                     .await;
             }
 
+            SwarmEvent::ExpiredListenAddr { address, .. } => {
+                debug!("No longer listening on {}", address);
+                let _ = self
+                    .event_tx
+                    .send(NetworkEvent::ListenAddressRemoved(address.to_string()))
+                    .await;
+            }
+
             SwarmEvent::Behaviour(super::behaviour::DecentPasteBehaviourEvent::Mdns(event)) => {
                 match event {
                     mdns::Event::Discovered(peers) => {
@@ -283,7 +402,10 @@ impl NetworkManager {
                             );
                             let _ = self
                                 .event_tx
-                                .send(NetworkEvent::ClipboardReceived(clipboard_msg))
+                                .send(NetworkEvent::ClipboardReceived {
+                                    peer_id: message.source.map(|p| p.to_string()),
+                                    message: clipboard_msg,
+                                })
                                 .await;
                         }
                         Ok(ProtocolMessage::DeviceAnnounce(announce_msg)) => {
@@ -303,6 +425,22 @@ impl NetworkManager {
                                 })
                                 .await;
 
+                            // Gossipsub messages are signed (strict validation), so `source`
+                            // is authenticated. Only trust the key if it matches the claim.
+                            if let Some(ref public_key) = announce_msg.public_key {
+                                if message.source.map(|s| s.to_string()).as_ref()
+                                    == Some(&announce_msg.peer_id)
+                                {
+                                    let _ = self
+                                        .event_tx
+                                        .send(NetworkEvent::PeerIdentityAnnounced {
+                                            peer_id: announce_msg.peer_id.clone(),
+                                            public_key: public_key.clone(),
+                                        })
+                                        .await;
+                                }
+                            }
+
                             // Also update our local discovered_peers cache
                             if let Ok(pid) = announce_msg.peer_id.parse::<PeerId>() {
                                 if let Some(discovered) = self.discovered_peers.get_mut(&pid) {
@@ -365,6 +503,7 @@ impl NetworkManager {
                         peer_id: local_peer_id,
                         device_name: self.device_name.clone(),
                         timestamp: Utc::now(),
+                        public_key: self.identity_public_key.clone(),
                     };
                     let protocol_msg = ProtocolMessage::DeviceAnnounce(announce_msg);
                     if let Err(e) = self.swarm.behaviour_mut().publish_clipboard(&protocol_msg) {
@@ -427,6 +566,8 @@ impl NetworkManager {
                                             // Store channel for later response (remove any existing to prevent accumulation)
                                             self.pending_responses.remove(&peer);
                                             self.pending_responses.insert(peer, channel);
+                                            self.pending_pairing_channels
+                                                .insert(peer, req.session_id.clone());
 
                                             // Use the session_id from the initiator's request
                                             let session_id = req.session_id.clone();
@@ -511,6 +652,253 @@ impl NetworkManager {
                                                     .await;
                                             }
                                         }
+                                        ProtocolMessage::Pairing(PairingMessage::PakeStart(
+                                            start,
+                                        )) => {
+                                            // Passphrase pairing: hold the channel for our PakeReply
+                                            self.pending_responses.remove(&peer);
+                                            self.pending_responses.insert(peer, channel);
+                                            self.pending_pairing_channels
+                                                .insert(peer, start.session_id.clone());
+
+                                            let _ = self
+                                                .event_tx
+                                                .send(NetworkEvent::PakeStartReceived {
+                                                    peer_id: peer.to_string(),
+                                                    start,
+                                                })
+                                                .await;
+                                        }
+                                        ProtocolMessage::Pairing(PairingMessage::PakeConfirm(
+                                            confirm,
+                                        )) => {
+                                            // Key confirmation is verified in lib.rs, which answers
+                                            // via NetworkCommand::AnswerPakeConfirm
+                                            self.pending_responses.remove(&peer);
+                                            self.pending_responses.insert(peer, channel);
+                                            self.pending_pairing_channels
+                                                .insert(peer, confirm.session_id.clone());
+
+                                            let _ = self
+                                                .event_tx
+                                                .send(NetworkEvent::PakeConfirmReceived {
+                                                    peer_id: peer.to_string(),
+                                                    confirm,
+                                                })
+                                                .await;
+                                        }
+                                        ProtocolMessage::Group(
+                                            super::protocol::GroupMessage::Update {
+                                                encrypted_payload,
+                                            },
+                                        ) => {
+                                            // NOTE: Authentication (decrypting with the pairwise
+                                            // secret) happens in lib.rs. Ack right away.
+                                            let ack = ProtocolMessage::Group(
+                                                super::protocol::GroupMessage::Ack,
+                                            );
+                                            if let Ok(message) = ack.to_bytes() {
+                                                let response = ReqPairingResponse { message };
+                                                let _ = self
+                                                    .swarm
+                                                    .behaviour_mut()
+                                                    .request_response
+                                                    .send_response(channel, response);
+                                            }
+
+                                            let _ = self
+                                                .event_tx
+                                                .send(NetworkEvent::GroupMessageReceived {
+                                                    peer_id: peer.to_string(),
+                                                    encrypted_payload,
+                                                })
+                                                .await;
+                                        }
+                                        ProtocolMessage::History(
+                                            super::protocol::HistoryMessage::Sync {
+                                                encrypted_payload,
+                                            },
+                                        ) => {
+                                            // NOTE: Decrypted (and so authenticated) in lib.rs
+                                            let ack = ProtocolMessage::History(
+                                                super::protocol::HistoryMessage::Ack,
+                                            );
+                                            if let Ok(message) = ack.to_bytes() {
+                                                let response = ReqPairingResponse { message };
+                                                let _ = self
+                                                    .swarm
+                                                    .behaviour_mut()
+                                                    .request_response
+                                                    .send_response(channel, response);
+                                            }
+
+                                            let _ = self
+                                                .event_tx
+                                                .send(NetworkEvent::HistorySyncReceived {
+                                                    peer_id: peer.to_string(),
+                                                    encrypted_payload,
+                                                })
+                                                .await;
+                                        }
+                                        ProtocolMessage::Direct(
+                                            super::protocol::DirectMessage::Clipboard(message),
+                                        ) => {
+                                            // NOTE: Decrypted (and so authenticated) in lib.rs,
+                                            // like a gossipsub clipboard message
+                                            let ack = ProtocolMessage::Direct(
+                                                super::protocol::DirectMessage::Ack {
+                                                    message_id: message.id.clone(),
+                                                },
+                                            );
+                                            if let Ok(bytes) = ack.to_bytes() {
+                                                let response =
+                                                    ReqPairingResponse { message: bytes };
+                                                let _ = self
+                                                    .swarm
+                                                    .behaviour_mut()
+                                                    .request_response
+                                                    .send_response(channel, response);
+                                            }
+
+                                            let _ = self
+                                                .event_tx
+                                                .send(NetworkEvent::ClipboardReceived {
+                                                    peer_id: Some(peer.to_string()),
+                                                    message,
+                                                })
+                                                .await;
+                                        }
+                                        ProtocolMessage::Delivery(
+                                            super::protocol::DeliveryMessage::Report {
+                                                encrypted_payload,
+                                            },
+                                        ) => {
+                                            // NOTE: Decrypted (and so authenticated) in lib.rs
+                                            let ack = ProtocolMessage::Delivery(
+                                                super::protocol::DeliveryMessage::Ack,
+                                            );
+                                            if let Ok(message) = ack.to_bytes() {
+                                                let response = ReqPairingResponse { message };
+                                                let _ = self
+                                                    .swarm
+                                                    .behaviour_mut()
+                                                    .request_response
+                                                    .send_response(channel, response);
+                                            }
+
+                                            let _ = self
+                                                .event_tx
+                                                .send(NetworkEvent::DeliveryReportReceived {
+                                                    peer_id: peer.to_string(),
+                                                    encrypted_payload,
+                                                })
+                                                .await;
+                                        }
+                                        ProtocolMessage::Unpair(
+                                            super::protocol::UnpairMessage::Request { proof },
+                                        ) => {
+                                            // NOTE: The proof is verified in lib.rs. Ack right away
+                                            // so the sender stops retrying either way.
+                                            let ack = ProtocolMessage::Unpair(
+                                                super::protocol::UnpairMessage::Ack,
+                                            );
+                                            if let Ok(message) = ack.to_bytes() {
+                                                let response = ReqPairingResponse { message };
+                                                let _ = self
+                                                    .swarm
+                                                    .behaviour_mut()
+                                                    .request_response
+                                                    .send_response(channel, response);
+                                            }
+
+                                            let _ = self
+                                                .event_tx
+                                                .send(NetworkEvent::UnpairReceived {
+                                                    peer_id: peer.to_string(),
+                                                    proof,
+                                                })
+                                                .await;
+                                        }
+                                        ProtocolMessage::Pull(
+                                            super::protocol::PullMessage::Ack { .. },
+                                        ) => {
+                                            debug!("Ignoring clipboard pull ack sent as a request");
+                                        }
+                                        ProtocolMessage::Pull(pull_msg) => {
+                                            // NOTE: Pairing, policy and requests are checked in lib.rs
+                                            let ack = ProtocolMessage::Pull(
+                                                super::protocol::PullMessage::Ack {
+                                                    request_id: pull_msg.request_id().to_string(),
+                                                },
+                                            );
+                                            if let Ok(message) = ack.to_bytes() {
+                                                let response = ReqPairingResponse { message };
+                                                let _ = self
+                                                    .swarm
+                                                    .behaviour_mut()
+                                                    .request_response
+                                                    .send_response(channel, response);
+                                            }
+
+                                            let _ = self
+                                                .event_tx
+                                                .send(NetworkEvent::PullMessageReceived {
+                                                    peer_id: peer.to_string(),
+                                                    message: pull_msg,
+                                                })
+                                                .await;
+                                        }
+                                        ProtocolMessage::Nearby(
+                                            super::protocol::NearbyMessage::Ack { .. },
+                                        ) => {
+                                            debug!("Ignoring nearby ack sent as a request");
+                                        }
+                                        ProtocolMessage::Nearby(nearby_msg) => {
+                                            // NOTE: Transfers are matched and checked in lib.rs
+                                            let ack = ProtocolMessage::Nearby(
+                                                super::protocol::NearbyMessage::Ack {
+                                                    transfer_id: nearby_msg
+                                                        .transfer_id()
+                                                        .to_string(),
+                                                },
+                                            );
+                                            if let Ok(message) = ack.to_bytes() {
+                                                let response = ReqPairingResponse { message };
+                                                let _ = self
+                                                    .swarm
+                                                    .behaviour_mut()
+                                                    .request_response
+                                                    .send_response(channel, response);
+                                            }
+
+                                            let _ = self
+                                                .event_tx
+                                                .send(NetworkEvent::NearbyMessageReceived {
+                                                    peer_id: peer.to_string(),
+                                                    message: nearby_msg,
+                                                })
+                                                .await;
+                                        }
+                                        ProtocolMessage::Rotation(
+                                            super::protocol::RotationMessage::Announce {
+                                                old_peer_id,
+                                                encrypted_payload,
+                                            },
+                                        ) => {
+                                            // NOTE: Decrypted and verified in lib.rs, which answers via
+                                            // NetworkCommand::AnswerIdentityRotation. The sender keeps
+                                            // retrying until the rotation is actually applied.
+                                            self.pending_rotation_acks.insert(peer, channel);
+
+                                            let _ = self
+                                                .event_tx
+                                                .send(NetworkEvent::IdentityRotationReceived {
+                                                    peer_id: peer.to_string(),
+                                                    old_peer_id,
+                                                    encrypted_payload,
+                                                })
+                                                .await;
+                                        }
                                         // Sync protocol handlers
                                         ProtocolMessage::Sync(sync_msg) => {
                                             match sync_msg {
@@ -523,6 +911,7 @@ impl NetworkManager {
                                                     // Store channel for response
                                                     self.pending_responses.remove(&peer);
                                                     self.pending_responses.insert(peer, channel);
+                                                    self.pending_pairing_channels.remove(&peer);
 
                                                     // Emit dedicated sync request event
                                                     let _ = self
@@ -544,6 +933,7 @@ impl NetworkManager {
                                                     // Store channel for response
                                                     self.pending_responses.remove(&peer);
                                                     self.pending_responses.insert(peer, channel);
+                                                    self.pending_pairing_channels.remove(&peer);
 
                                                     // Emit dedicated content request event
                                                     let _ = self
@@ -554,6 +944,29 @@ impl NetworkManager {
                                                         })
                                                         .await;
                                                 }
+                                                super::protocol::SyncMessage::Relay { message } => {
+                                                    // NOTE: Relay settings and pairings are checked in lib.rs
+                                                    let ack = ProtocolMessage::Sync(
+                                                        super::protocol::SyncMessage::RelayAck,
+                                                    );
+                                                    if let Ok(bytes) = ack.to_bytes() {
+                                                        let response =
+                                                            ReqPairingResponse { message: bytes };
+                                                        let _ = self
+                                                            .swarm
+                                                            .behaviour_mut()
+                                                            .request_response
+                                                            .send_response(channel, response);
+                                                    }
+
+                                                    let _ = self
+                                                        .event_tx
+                                                        .send(NetworkEvent::SyncRelayReceived {
+                                                            peer_id: peer.to_string(),
+                                                            message,
+                                                        })
+                                                        .await;
+                                                }
                                                 _ => {
                                                     debug!("Received unexpected sync message type as request: {:?}", sync_msg);
                                                 }
@@ -589,6 +1002,16 @@ impl NetworkManager {
                                                             pin: challenge.pin,
                                                             peer_device_name: challenge.device_name,
                                                             peer_public_key: challenge.public_key,
+                                                            invite_proof: challenge.invite_proof,
+                                                        })
+                                                        .await;
+                                                }
+                                                PairingMessage::PakeReply(reply) => {
+                                                    let _ = self
+                                                        .event_tx
+                                                        .send(NetworkEvent::PakeReplyReceived {
+                                                            peer_id: peer.to_string(),
+                                                            reply,
                                                         })
                                                         .await;
                                                 }
@@ -620,6 +1043,76 @@ impl NetworkManager {
                                             }
                                         }
 
+                                        ProtocolMessage::Group(
+                                            super::protocol::GroupMessage::Ack,
+                                        ) => {
+                                            debug!("Group update acknowledged by {}", peer);
+                                        }
+
+                                        ProtocolMessage::History(
+                                            super::protocol::HistoryMessage::Ack,
+                                        ) => {
+                                            debug!("History sync acknowledged by {}", peer);
+                                        }
+
+                                        ProtocolMessage::Delivery(
+                                            super::protocol::DeliveryMessage::Ack,
+                                        ) => {
+                                            debug!("Delivery report acknowledged by {}", peer);
+                                        }
+
+                                        ProtocolMessage::Direct(
+                                            super::protocol::DirectMessage::Ack { message_id },
+                                        ) => {
+                                            debug!(
+                                                "Direct clipboard {} received by {}",
+                                                message_id, peer
+                                            );
+                                        }
+
+                                        ProtocolMessage::Unpair(
+                                            super::protocol::UnpairMessage::Ack,
+                                        ) => {
+                                            let _ = self
+                                                .event_tx
+                                                .send(NetworkEvent::UnpairAcknowledged {
+                                                    peer_id: peer.to_string(),
+                                                })
+                                                .await;
+                                        }
+
+                                        ProtocolMessage::Pull(
+                                            super::protocol::PullMessage::Ack { request_id },
+                                        ) => {
+                                            debug!(
+                                                "Clipboard pull {} acknowledged by {}",
+                                                request_id, peer
+                                            );
+                                        }
+
+                                        ProtocolMessage::Nearby(
+                                            super::protocol::NearbyMessage::Ack { transfer_id },
+                                        ) => {
+                                            let _ = self
+                                                .event_tx
+                                                .send(NetworkEvent::NearbyAcknowledged {
+                                                    peer_id: peer.to_string(),
+                                                    transfer_id,
+                                                })
+                                                .await;
+                                        }
+
+                                        ProtocolMessage::Rotation(
+                                            super::protocol::RotationMessage::Ack,
+                                        ) => {
+                                            let _ = self
+                                                .event_tx
+                                                .send(NetworkEvent::IdentityRotationAcknowledged {
+                                                    peer_id: peer.to_string(),
+                                                })
+                                                .await;
+                                        }
+
                                         // Handle sync responses
                                         ProtocolMessage::Sync(sync_msg) => {
                                             match sync_msg {
@@ -653,6 +1146,9 @@ impl NetworkManager {
                                                         })
                                                         .await;
                                                 }
+                                                super::protocol::SyncMessage::RelayAck => {
+                                                    debug!("Relayed message accepted by {}", peer);
+                                                }
                                                 _ => {
                                                     debug!("Received unexpected sync message type as response: {:?}", sync_msg);
                                                 }
@@ -862,14 +1358,17 @@ impl NetworkManager {
                 pin,
                 device_name,
                 public_key,
+                invite_proof,
             } => {
                 if let Ok(peer) = peer_id.parse::<PeerId>() {
+                    self.pending_pairing_channels.remove(&peer);
                     if let Some(channel) = self.pending_responses.remove(&peer) {
                         let challenge = super::protocol::PairingChallenge {
                             session_id: session_id.clone(),
                             pin,
                             device_name,
                             public_key,
+                            invite_proof,
                         };
                         let protocol_msg =
                             ProtocolMessage::Pairing(PairingMessage::Challenge(challenge));
@@ -893,16 +1392,88 @@ impl NetworkManager {
                 }
             }
 
+            NetworkCommand::SendPakeReply { peer_id, reply } => {
+                if let Ok(peer) = peer_id.parse::<PeerId>() {
+                    self.pending_pairing_channels.remove(&peer);
+                    if let Some(channel) = self.pending_responses.remove(&peer) {
+                        let protocol_msg =
+                            ProtocolMessage::Pairing(PairingMessage::PakeReply(reply));
+                        if let Ok(message) = protocol_msg.to_bytes() {
+                            let response = ReqPairingResponse { message };
+                            if self
+                                .swarm
+                                .behaviour_mut()
+                                .request_response
+                                .send_response(channel, response)
+                                .is_ok()
+                            {
+                                debug!("Sent passphrase pairing reply to {}", peer_id);
+                            } else {
+                                warn!("Failed to send passphrase pairing reply to {}", peer_id);
+                            }
+                        }
+                    } else {
+                        warn!("No pending response channel for peer {}", peer_id);
+                    }
+                }
+            }
+
+            NetworkCommand::AnswerPakeConfirm {
+                peer_id,
+                session_id,
+                success,
+                device_name,
+            } => {
+                if let Ok(peer) = peer_id.parse::<PeerId>() {
+                    self.pending_pairing_channels.remove(&peer);
+                    if let Some(channel) = self.pending_responses.remove(&peer) {
+                        let ack = super::protocol::PairingConfirm {
+                            session_id: session_id.clone(),
+                            success,
+                            error: (!success).then(|| "Passphrase mismatch".to_string()),
+                            device_name: None,
+                        };
+                        let protocol_msg = ProtocolMessage::Pairing(PairingMessage::Confirm(ack));
+                        if let Ok(message) = protocol_msg.to_bytes() {
+                            let response = ReqPairingResponse { message };
+                            let _ = self
+                                .swarm
+                                .behaviour_mut()
+                                .request_response
+                                .send_response(channel, response);
+                        }
+                    }
+
+                    let event = if success {
+                        NetworkEvent::PairingComplete {
+                            session_id,
+                            peer_id,
+                            device_name,
+                        }
+                    } else {
+                        NetworkEvent::PairingFailed {
+                            session_id,
+                            error: "Passphrase mismatch".to_string(),
+                        }
+                    };
+                    let _ = self.event_tx.send(event).await;
+                }
+            }
+
             NetworkCommand::RejectPairing {
                 peer_id,
                 session_id,
+                reason,
             } => {
                 if let Ok(peer) = peer_id.parse::<PeerId>() {
+                    self.pending_pairing_channels.remove(&peer);
                     if let Some(channel) = self.pending_responses.remove(&peer) {
                         let confirm = super::protocol::PairingConfirm {
                             session_id,
                             success: false,
-                            error: Some("Pairing rejected by user".to_string()),
+                            error: Some(
+                                reason.unwrap_or_else(|| "Pairing rejected by user".to_string()),
+                            ),
                             device_name: None,
                         };
                         let protocol_msg =
@@ -949,6 +1520,53 @@ impl NetworkManager {
                 }
             }
 
+            NetworkCommand::ExpirePairing {
+                peer_id,
+                session_id,
+            } => {
+                if let Ok(peer) = peer_id.parse::<PeerId>() {
+                    let confirm = super::protocol::PairingConfirm {
+                        session_id: session_id.clone(),
+                        success: false,
+                        error: Some(PAIRING_TIMEOUT_ERROR.to_string()),
+                        device_name: None,
+                    };
+                    let protocol_msg = ProtocolMessage::Pairing(PairingMessage::Confirm(confirm));
+                    if let Ok(message) = protocol_msg.to_bytes() {
+                        // If we still hold the initiator's channel for this session, answer it
+                        // with the timeout. Otherwise (or if the channel is already dead),
+                        // notify the peer with a fresh request.
+                        let mut notified = false;
+                        if self.pending_pairing_channels.get(&peer) == Some(&session_id) {
+                            self.pending_pairing_channels.remove(&peer);
+                            if let Some(channel) = self.pending_responses.remove(&peer) {
+                                let response = ReqPairingResponse {
+                                    message: message.clone(),
+                                };
+                                notified = self
+                                    .swarm
+                                    .behaviour_mut()
+                                    .request_response
+                                    .send_response(channel, response)
+                                    .is_ok();
+                            }
+                        }
+
+                        if !notified {
+                            let request = ReqPairingRequest { message };
+                            self.swarm
+                                .behaviour_mut()
+                                .request_response
+                                .send_request(&peer, request);
+                        }
+                        debug!(
+                            "Notified {} that pairing session {} expired",
+                            peer_id, session_id
+                        );
+                    }
+                }
+            }
+
             NetworkCommand::GetPeers => {
                 // Re-emit current discovered peers and dial any that aren't connected
                 let connected: std::collections::HashSet<PeerId> = self
@@ -1077,6 +1695,28 @@ impl NetworkManager {
                 );
             }
 
+            NetworkCommand::DialPeer { peer_id, addresses } => {
+                if let Ok(peer) = peer_id.parse::<PeerId>() {
+                    for addr_str in &addresses {
+                        if let Ok(addr) = addr_str.parse::<Multiaddr>() {
+                            self.swarm.add_peer_address(peer, addr);
+                        }
+                    }
+                    if self.connected_peers.contains_key(&peer) {
+                        debug!("Peer {} already connected, skipping dial", peer_id);
+                    } else {
+                        info!(
+                            "Dialing peer {} at {} address(es)",
+                            peer_id,
+                            addresses.len()
+                        );
+                        if let Err(e) = self.swarm.dial(peer) {
+                            warn!("Failed to dial peer {}: {}", peer_id, e);
+                        }
+                    }
+                }
+            }
+
             NetworkCommand::StartListening | NetworkCommand::StopListening => {
                 // Already handled during initialization
             }
@@ -1108,6 +1748,7 @@ impl NetworkManager {
                     peer_id: local_peer_id,
                     device_name,
                     timestamp: Utc::now(),
+                    public_key: self.identity_public_key.clone(),
                 };
                 let protocol_msg = ProtocolMessage::DeviceAnnounce(announce_msg);
                 match self.swarm.behaviour_mut().publish_clipboard(&protocol_msg) {
@@ -1120,6 +1761,183 @@ impl NetworkManager {
                 }
             }
 
+            NetworkCommand::SendGroupMessage {
+                peer_id,
+                encrypted_payload,
+            } => {
+                if let Ok(peer) = peer_id.parse::<PeerId>() {
+                    let protocol_msg =
+                        ProtocolMessage::Group(super::protocol::GroupMessage::Update {
+                            encrypted_payload,
+                        });
+                    if let Ok(message) = protocol_msg.to_bytes() {
+                        let request = ReqPairingRequest { message };
+                        self.swarm
+                            .behaviour_mut()
+                            .request_response
+                            .send_request(&peer, request);
+                        debug!("Sent group update to {}", peer_id);
+                    }
+                }
+            }
+
+            NetworkCommand::SendHistorySync {
+                peer_id,
+                encrypted_payload,
+            } => {
+                if let Ok(peer) = peer_id.parse::<PeerId>() {
+                    let protocol_msg =
+                        ProtocolMessage::History(super::protocol::HistoryMessage::Sync {
+                            encrypted_payload,
+                        });
+                    if let Ok(message) = protocol_msg.to_bytes() {
+                        let request = ReqPairingRequest { message };
+                        self.swarm
+                            .behaviour_mut()
+                            .request_response
+                            .send_request(&peer, request);
+                        debug!("Sent history sync to {}", peer_id);
+                    }
+                }
+            }
+
+            NetworkCommand::SendDirectClipboard { peer_id, message } => {
+                if let Ok(peer) = peer_id.parse::<PeerId>() {
+                    let protocol_msg =
+                        ProtocolMessage::Direct(super::protocol::DirectMessage::Clipboard(message));
+                    if let Ok(message) = protocol_msg.to_bytes() {
+                        let request = ReqPairingRequest { message };
+                        self.swarm
+                            .behaviour_mut()
+                            .request_response
+                            .send_request(&peer, request);
+                        debug!("Sent direct clipboard to {}", peer_id);
+                    }
+                }
+            }
+
+            NetworkCommand::SendDeliveryReport {
+                peer_id,
+                encrypted_payload,
+            } => {
+                if let Ok(peer) = peer_id.parse::<PeerId>() {
+                    let protocol_msg =
+                        ProtocolMessage::Delivery(super::protocol::DeliveryMessage::Report {
+                            encrypted_payload,
+                        });
+                    if let Ok(message) = protocol_msg.to_bytes() {
+                        let request = ReqPairingRequest { message };
+                        self.swarm
+                            .behaviour_mut()
+                            .request_response
+                            .send_request(&peer, request);
+                        debug!("Sent delivery report to {}", peer_id);
+                    }
+                }
+            }
+
+            NetworkCommand::SendUnpair { peer_id, proof } => {
+                if let Ok(peer) = peer_id.parse::<PeerId>() {
+                    let protocol_msg =
+                        ProtocolMessage::Unpair(super::protocol::UnpairMessage::Request { proof });
+                    if let Ok(message) = protocol_msg.to_bytes() {
+                        let request = ReqPairingRequest { message };
+                        self.swarm
+                            .behaviour_mut()
+                            .request_response
+                            .send_request(&peer, request);
+                        debug!("Sent unpair notification to {}", peer_id);
+                    }
+                }
+            }
+
+            NetworkCommand::SendIdentityRotation {
+                peer_id,
+                old_peer_id,
+                encrypted_payload,
+            } => {
+                if let Ok(peer) = peer_id.parse::<PeerId>() {
+                    let protocol_msg =
+                        ProtocolMessage::Rotation(super::protocol::RotationMessage::Announce {
+                            old_peer_id,
+                            encrypted_payload,
+                        });
+                    if let Ok(message) = protocol_msg.to_bytes() {
+                        let request = ReqPairingRequest { message };
+                        self.swarm
+                            .behaviour_mut()
+                            .request_response
+                            .send_request(&peer, request);
+                        debug!("Sent identity rotation to {}", peer_id);
+                    }
+                }
+            }
+
+            NetworkCommand::AnswerIdentityRotation { peer_id, applied } => {
+                if let Ok(peer) = peer_id.parse::<PeerId>() {
+                    // Dropping the channel unanswered fails the peer's request
+                    let channel = self.pending_rotation_acks.remove(&peer);
+                    if let (Some(channel), true) = (channel, applied) {
+                        let ack = ProtocolMessage::Rotation(super::protocol::RotationMessage::Ack);
+                        if let Ok(message) = ack.to_bytes() {
+                            let response = ReqPairingResponse { message };
+                            let _ = self
+                                .swarm
+                                .behaviour_mut()
+                                .request_response
+                                .send_response(channel, response);
+                        }
+                    }
+                }
+            }
+
+            NetworkCommand::SendNearbyMessage { peer_id, message } => {
+                if let Ok(peer) = peer_id.parse::<PeerId>() {
+                    let protocol_msg = ProtocolMessage::Nearby(message);
+                    if let Ok(message) = protocol_msg.to_bytes() {
+                        let request = ReqPairingRequest { message };
+                        self.swarm
+                            .behaviour_mut()
+                            .request_response
+                            .send_request(&peer, request);
+                        debug!("Sent nearby send message to {}", peer_id);
+                    }
+                }
+            }
+
+            NetworkCommand::SendPullMessage { peer_id, message } => {
+                if let Ok(peer) = peer_id.parse::<PeerId>() {
+                    let protocol_msg = ProtocolMessage::Pull(message);
+                    if let Ok(message) = protocol_msg.to_bytes() {
+                        let request = ReqPairingRequest { message };
+                        self.swarm
+                            .behaviour_mut()
+                            .request_response
+                            .send_request(&peer, request);
+                        debug!("Sent clipboard pull message to {}", peer_id);
+                    }
+                }
+            }
+
+            NetworkCommand::SetAnnouncedIdentityKey { public_key } => {
+                self.identity_public_key = public_key;
+            }
+
+            NetworkCommand::SendRelayMessage { peer_id, message } => {
+                if let Ok(peer) = peer_id.parse::<PeerId>() {
+                    let protocol_msg =
+                        ProtocolMessage::Sync(super::protocol::SyncMessage::Relay { message });
+                    if let Ok(message) = protocol_msg.to_bytes() {
+                        let request = ReqPairingRequest { message };
+                        self.swarm
+                            .behaviour_mut()
+                            .request_response
+                            .send_request(&peer, request);
+                        debug!("Sent message to relay {}", peer_id);
+                    }
+                }
+            }
+
             NetworkCommand::RequestSync { peer_id } => {
                 // Send a SyncRequest to a peer to get hashes of messages we missed
                 if let Ok(peer) = peer_id.parse::<PeerId>() {
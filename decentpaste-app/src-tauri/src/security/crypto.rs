@@ -2,11 +2,14 @@ use aes_gcm::{
     aead::{rand_core::RngCore, Aead, KeyInit, OsRng},
     Aes256Gcm, Nonce,
 };
+use hmac::{Hmac, Mac};
 use sha2::{Digest, Sha256};
 
 use crate::error::{DecentPasteError, Result};
 
 const NONCE_SIZE: usize = 12;
+const GROUP_PAIR_LABEL: &[u8] = b"decentpaste-group-pair";
+const UNPAIR_LABEL: &[u8] = b"decentpaste-unpair";
 
 pub fn hash_content(content: &str) -> String {
     let mut hasher = Sha256::new();
@@ -64,10 +67,105 @@ pub fn decrypt_content(encrypted: &[u8], shared_secret: &[u8]) -> Result<Vec<u8>
         .map_err(|e| DecentPasteError::Encryption(e.to_string()))
 }
 
+/// Generate a random 256-bit symmetric key (used for pairing group keys).
+pub fn generate_key() -> Vec<u8> {
+    let mut key = vec![0u8; 32];
+    OsRng.fill_bytes(&mut key);
+    key
+}
+
+/// Derive the pairwise secret for two members of a pairing group from the group key.
+///
+/// Each member is a peer ID and the identity key the group lists for it (empty if
+/// none). Binding the keys means both members only agree on a secret if they were
+/// given the same keys. The derivation is symmetric so both members get the same secret.
+pub fn derive_group_pair_secret(
+    group_key: &[u8],
+    member_a: (&str, &[u8]),
+    member_b: (&str, &[u8]),
+) -> Vec<u8> {
+    let (first, second) = if member_a.0 <= member_b.0 {
+        (member_a, member_b)
+    } else {
+        (member_b, member_a)
+    };
+    let mut mac = Hmac::<Sha256>::new_from_slice(group_key).expect("HMAC accepts any key length");
+    mac.update(GROUP_PAIR_LABEL);
+    mac.update(first.0.as_bytes());
+    mac.update(b"|");
+    mac.update(second.0.as_bytes());
+    for identity_key in [first.1, second.1] {
+        mac.update(&(identity_key.len() as u64).to_be_bytes());
+        mac.update(identity_key);
+    }
+    mac.finalize().into_bytes().to_vec()
+}
+
+fn unpair_mac(shared_secret: &[u8], from_peer: &str, to_peer: &str) -> Hmac<Sha256> {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(shared_secret).expect("HMAC accepts any key length");
+    mac.update(UNPAIR_LABEL);
+    mac.update(from_peer.as_bytes());
+    mac.update(b"|");
+    mac.update(to_peer.as_bytes());
+    mac
+}
+
+/// Proof that an `Unpair` message comes from the device we share `shared_secret` with.
+/// Bound to the direction, so a peer can't reflect our own unpair back at us.
+pub fn unpair_proof(shared_secret: &[u8], from_peer: &str, to_peer: &str) -> Vec<u8> {
+    unpair_mac(shared_secret, from_peer, to_peer)
+        .finalize()
+        .into_bytes()
+        .to_vec()
+}
+
+pub fn verify_unpair_proof(
+    shared_secret: &[u8],
+    from_peer: &str,
+    to_peer: &str,
+    proof: &[u8],
+) -> bool {
+    unpair_mac(shared_secret, from_peer, to_peer)
+        .verify_slice(proof)
+        .is_ok()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_group_pair_secret_is_symmetric() {
+        let key = generate_key();
+        let a = ("peer-a", &[1u8; 32][..]);
+        let b = ("peer-b", &[2u8; 32][..]);
+        let c = ("peer-c", &[2u8; 32][..]);
+        let ab = derive_group_pair_secret(&key, a, b);
+        assert_eq!(ab, derive_group_pair_secret(&key, b, a));
+        assert_ne!(ab, derive_group_pair_secret(&key, a, c));
+        assert_ne!(ab, derive_group_pair_secret(&generate_key(), a, b));
+        // A member given a different key for its peer derives a different secret
+        let substituted = ("peer-b", &[3u8; 32][..]);
+        assert_ne!(ab, derive_group_pair_secret(&key, a, substituted));
+        assert_ne!(ab, derive_group_pair_secret(&key, a, ("peer-b", &[][..])));
+        assert_eq!(ab.len(), 32);
+    }
+
+    #[test]
+    fn test_unpair_proof() {
+        let secret = generate_key();
+        let proof = unpair_proof(&secret, "peer-a", "peer-b");
+        assert!(verify_unpair_proof(&secret, "peer-a", "peer-b", &proof));
+        assert!(!verify_unpair_proof(&secret, "peer-b", "peer-a", &proof));
+        assert!(!verify_unpair_proof(
+            &generate_key(),
+            "peer-a",
+            "peer-b",
+            &proof
+        ));
+    }
+
     #[test]
     fn test_hash_content() {
         let hash1 = hash_content("test");
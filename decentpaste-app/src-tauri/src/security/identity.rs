@@ -1,5 +1,6 @@
 use aes_gcm::aead::OsRng;
 use chrono::Utc;
+use sha2::{Digest, Sha256};
 use uuid::Uuid;
 use x25519_dalek::{PublicKey, StaticSecret};
 
@@ -27,6 +28,18 @@ pub fn generate_device_identity(device_name: &str) -> DeviceIdentity {
     }
 }
 
+/// Replace the X25519 keypair of `identity`, keeping its device ID, name and creation date.
+pub fn regenerate_identity_keys(identity: &DeviceIdentity) -> DeviceIdentity {
+    let private_key = StaticSecret::random_from_rng(OsRng);
+    let public_key = PublicKey::from(&private_key);
+
+    DeviceIdentity {
+        public_key: public_key.as_bytes().to_vec(),
+        private_key: Some(private_key.as_bytes().to_vec()),
+        ..identity.clone()
+    }
+}
+
 /// Derive a shared secret using X25519 ECDH
 /// Takes our private key and the peer's public key, returns a 32-byte shared secret
 pub fn derive_shared_secret(our_private_key: &[u8], their_public_key: &[u8]) -> Result<Vec<u8>> {
@@ -60,6 +73,33 @@ pub fn derive_shared_secret(our_private_key: &[u8], their_public_key: &[u8]) ->
     Ok(shared_secret.as_bytes().to_vec())
 }
 
+const SAFETY_NUMBER_LABEL: &[u8] = b"decentpaste-safety-number";
+
+/// Safety number for a pairing: 30 digits in groups of five, derived from both
+/// identity keys. Both devices compute the same number, so users can compare them.
+pub fn safety_number(key_a: &[u8], key_b: &[u8]) -> String {
+    let (first, second) = if key_a <= key_b {
+        (key_a, key_b)
+    } else {
+        (key_b, key_a)
+    };
+    let mut hasher = Sha256::new();
+    hasher.update(SAFETY_NUMBER_LABEL);
+    hasher.update(first);
+    hasher.update(second);
+    let digest = hasher.finalize();
+
+    digest
+        .chunks_exact(5)
+        .take(6)
+        .map(|chunk| {
+            let value = chunk.iter().fold(0u64, |acc, b| (acc << 8) | *b as u64);
+            format!("{:05}", value % 100_000)
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -75,6 +115,17 @@ mod tests {
         assert_eq!(identity.device_name, "Test Device");
     }
 
+    #[test]
+    fn test_regenerate_identity_keys() {
+        let identity = generate_device_identity("Test Device");
+        let rotated = regenerate_identity_keys(&identity);
+
+        assert_eq!(rotated.device_id, identity.device_id);
+        assert_eq!(rotated.device_name, identity.device_name);
+        assert_ne!(rotated.public_key, identity.public_key);
+        assert_ne!(rotated.private_key, identity.private_key);
+    }
+
     #[test]
     fn test_ecdh_key_exchange() {
         // Simulate two devices
@@ -109,4 +160,20 @@ mod tests {
         // Different peer pairs should have different shared secrets
         assert_ne!(alice_bob, alice_charlie);
     }
+
+    #[test]
+    fn test_safety_number_is_symmetric() {
+        let alice = generate_device_identity("Alice");
+        let bob = generate_device_identity("Bob");
+        let charlie = generate_device_identity("Charlie");
+
+        let alice_bob = safety_number(&alice.public_key, &bob.public_key);
+        assert_eq!(alice_bob, safety_number(&bob.public_key, &alice.public_key));
+        assert_ne!(
+            alice_bob,
+            safety_number(&alice.public_key, &charlie.public_key)
+        );
+        assert_eq!(alice_bob.len(), 6 * 5 + 5);
+        assert!(alice_bob.chars().all(|c| c.is_ascii_digit() || c == ' '));
+    }
 }
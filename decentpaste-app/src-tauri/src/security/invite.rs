@@ -0,0 +1,305 @@
+use aes_gcm::aead::{rand_core::RngCore, OsRng};
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use qrcode::render::svg;
+use qrcode::QrCode;
+use sha2::{Digest, Sha256};
+use url::Url;
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+use crate::error::{DecentPasteError, Result};
+
+type HmacSha256 = Hmac<Sha256>;
+
+const URI_SCHEME: &str = "decentpaste";
+const URI_ACTION: &str = "pair";
+const INVITE_SECRET_SIZE: usize = 32;
+
+/// Domain separation labels so a request proof can never be replayed as a response proof.
+const REQUEST_PROOF_LABEL: &[u8] = b"decentpaste-invite-request";
+const RESPONSE_PROOF_LABEL: &[u8] = b"decentpaste-invite-response";
+
+/// A one-time pairing invite created by `create_pairing_uri`.
+///
+/// The secret is embedded in the pairing URI and replaces the PIN: whoever
+/// scans the URI proves knowledge of it in their `PairingRequest`.
+#[derive(Clone, Zeroize, ZeroizeOnDrop)]
+pub struct PairingInvite {
+    secret: Vec<u8>,
+    #[zeroize(skip)]
+    pub created_at: DateTime<Utc>,
+}
+
+impl PairingInvite {
+    pub fn new() -> Self {
+        let mut secret = vec![0u8; INVITE_SECRET_SIZE];
+        OsRng.fill_bytes(&mut secret);
+        Self {
+            secret,
+            created_at: Utc::now(),
+        }
+    }
+
+    pub fn secret(&self) -> &[u8] {
+        &self.secret
+    }
+
+    /// Whether the invite is older than `timeout_secs`.
+    pub fn is_expired(&self, timeout_secs: u64) -> bool {
+        let duration = Utc::now().signed_duration_since(self.created_at);
+        duration.num_seconds() > timeout_secs as i64
+    }
+}
+
+impl Default for PairingInvite {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Debug for PairingInvite {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Never log the invite secret
+        f.debug_struct("PairingInvite")
+            .field("secret", &"[REDACTED]")
+            .field("created_at", &self.created_at)
+            .finish()
+    }
+}
+
+/// Contents of a `decentpaste://pair?...` URI.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PairingUri {
+    pub peer_id: String,
+    pub addresses: Vec<String>,
+    pub device_name: String,
+    /// Fingerprint of the inviting device's X25519 public key
+    pub fingerprint: String,
+    pub secret: Vec<u8>,
+}
+
+impl PairingUri {
+    pub fn to_uri(&self) -> String {
+        let mut url = Url::parse(&format!("{}://{}", URI_SCHEME, URI_ACTION))
+            .expect("static pairing URI base is valid");
+        {
+            let mut query = url.query_pairs_mut();
+            query.append_pair("peer", &self.peer_id);
+            for addr in &self.addresses {
+                query.append_pair("addr", addr);
+            }
+            query.append_pair("name", &self.device_name);
+            query.append_pair("fp", &self.fingerprint);
+            query.append_pair("secret", &hex::encode(&self.secret));
+        }
+        url.to_string()
+    }
+
+    pub fn parse(uri: &str) -> Result<Self> {
+        let invalid = |msg: &str| DecentPasteError::InvalidInput(format!("Pairing URI: {}", msg));
+
+        let url = Url::parse(uri.trim()).map_err(|e| invalid(&e.to_string()))?;
+        if url.scheme() != URI_SCHEME || url.host_str() != Some(URI_ACTION) {
+            return Err(invalid("not a decentpaste pairing link"));
+        }
+
+        let mut peer_id = None;
+        let mut addresses = Vec::new();
+        let mut device_name = None;
+        let mut fingerprint = None;
+        let mut secret = None;
+        for (key, value) in url.query_pairs() {
+            match key.as_ref() {
+                "peer" => peer_id = Some(value.into_owned()),
+                "addr" => addresses.push(value.into_owned()),
+                "name" => device_name = Some(value.into_owned()),
+                "fp" => fingerprint = Some(value.into_owned()),
+                "secret" => {
+                    secret = Some(hex::decode(value.as_ref()).map_err(|_| invalid("bad secret"))?)
+                }
+                _ => {}
+            }
+        }
+
+        let secret = secret.ok_or_else(|| invalid("missing secret"))?;
+        if secret.len() != INVITE_SECRET_SIZE {
+            return Err(invalid("bad secret"));
+        }
+        if addresses.is_empty() {
+            return Err(invalid("missing addresses"));
+        }
+
+        Ok(Self {
+            peer_id: peer_id.ok_or_else(|| invalid("missing peer"))?,
+            addresses,
+            device_name: device_name.unwrap_or_else(|| "Unknown Device".to_string()),
+            fingerprint: fingerprint.ok_or_else(|| invalid("missing fingerprint"))?,
+            secret,
+        })
+    }
+
+    /// Render the URI as an SVG QR code.
+    pub fn to_qr_svg(&self) -> Result<String> {
+        let code = QrCode::new(self.to_uri().as_bytes())
+            .map_err(|e| DecentPasteError::InvalidInput(format!("QR code: {}", e)))?;
+        Ok(code
+            .render::<svg::Color>()
+            .min_dimensions(256, 256)
+            .quiet_zone(true)
+            .build())
+    }
+}
+
+/// Fingerprint of an X25519 public key (hex SHA-256).
+pub fn public_key_fingerprint(public_key: &[u8]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(public_key);
+    hex::encode(hasher.finalize())
+}
+
+fn invite_mac(secret: &[u8], label: &[u8], parts: &[&[u8]]) -> HmacSha256 {
+    let mut mac = HmacSha256::new_from_slice(secret).expect("HMAC accepts any key length");
+    mac.update(label);
+    for part in parts {
+        // Length-prefix each field so boundaries can't be shifted
+        mac.update(&(part.len() as u32).to_be_bytes());
+        mac.update(part);
+    }
+    mac
+}
+
+/// Proof sent by the scanning device: binds its public key to the invite secret.
+pub fn invite_request_proof(secret: &[u8], session_id: &str, initiator_key: &[u8]) -> Vec<u8> {
+    invite_mac(
+        secret,
+        REQUEST_PROOF_LABEL,
+        &[session_id.as_bytes(), initiator_key],
+    )
+    .finalize()
+    .into_bytes()
+    .to_vec()
+}
+
+pub fn verify_invite_request_proof(
+    secret: &[u8],
+    session_id: &str,
+    initiator_key: &[u8],
+    proof: &[u8],
+) -> bool {
+    invite_mac(
+        secret,
+        REQUEST_PROOF_LABEL,
+        &[session_id.as_bytes(), initiator_key],
+    )
+    .verify_slice(proof)
+    .is_ok()
+}
+
+/// Proof sent back by the inviting device: binds both public keys to the invite secret.
+pub fn invite_response_proof(
+    secret: &[u8],
+    session_id: &str,
+    responder_key: &[u8],
+    initiator_key: &[u8],
+) -> Vec<u8> {
+    invite_mac(
+        secret,
+        RESPONSE_PROOF_LABEL,
+        &[session_id.as_bytes(), responder_key, initiator_key],
+    )
+    .finalize()
+    .into_bytes()
+    .to_vec()
+}
+
+pub fn verify_invite_response_proof(
+    secret: &[u8],
+    session_id: &str,
+    responder_key: &[u8],
+    initiator_key: &[u8],
+    proof: &[u8],
+) -> bool {
+    invite_mac(
+        secret,
+        RESPONSE_PROOF_LABEL,
+        &[session_id.as_bytes(), responder_key, initiator_key],
+    )
+    .verify_slice(proof)
+    .is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_uri() -> PairingUri {
+        PairingUri {
+            peer_id: "12D3KooWExample".into(),
+            addresses: vec![
+                "/ip4/192.168.1.20/tcp/31773".into(),
+                "/ip6/fe80::1/tcp/31773".into(),
+            ],
+            device_name: "Desk & Lab".into(),
+            fingerprint: public_key_fingerprint(&[7u8; 32]),
+            secret: PairingInvite::new().secret().to_vec(),
+        }
+    }
+
+    #[test]
+    fn test_pairing_uri_roundtrip() {
+        let uri = sample_uri();
+        let encoded = uri.to_uri();
+        assert!(encoded.starts_with("decentpaste://pair?"));
+        assert_eq!(PairingUri::parse(&encoded).unwrap(), uri);
+    }
+
+    #[test]
+    fn test_pairing_uri_rejects_other_schemes() {
+        assert!(PairingUri::parse("https://pair?peer=x").is_err());
+        assert!(PairingUri::parse("decentpaste://pair?peer=x").is_err());
+    }
+
+    #[test]
+    fn test_invite_proofs() {
+        let invite = PairingInvite::new();
+        let other = PairingInvite::new();
+        let (initiator, responder) = ([1u8; 32], [2u8; 32]);
+
+        let proof = invite_request_proof(invite.secret(), "s1", &initiator);
+        assert!(verify_invite_request_proof(
+            invite.secret(),
+            "s1",
+            &initiator,
+            &proof
+        ));
+        assert!(!verify_invite_request_proof(
+            other.secret(),
+            "s1",
+            &initiator,
+            &proof
+        ));
+        assert!(!verify_invite_request_proof(
+            invite.secret(),
+            "s2",
+            &initiator,
+            &proof
+        ));
+
+        let response = invite_response_proof(invite.secret(), "s1", &responder, &initiator);
+        assert!(verify_invite_response_proof(
+            invite.secret(),
+            "s1",
+            &responder,
+            &initiator,
+            &response
+        ));
+        // A request proof must not validate as a response proof
+        assert!(!verify_invite_response_proof(
+            invite.secret(),
+            "s1",
+            &responder,
+            &initiator,
+            &proof
+        ));
+    }
+}
@@ -1,7 +1,28 @@
 mod crypto;
 mod identity;
+mod invite;
+mod nearby;
 mod pairing;
+mod pake;
+mod replay;
+mod rotation;
 
-pub use crypto::{decrypt_content, encrypt_content, hash_content};
-pub use identity::{derive_shared_secret, generate_device_identity};
+pub use crypto::{
+    decrypt_content, derive_group_pair_secret, encrypt_content, generate_key, hash_content,
+    unpair_proof, verify_unpair_proof,
+};
+pub use identity::{
+    derive_shared_secret, generate_device_identity, regenerate_identity_keys, safety_number,
+};
+pub use invite::{
+    invite_request_proof, invite_response_proof, public_key_fingerprint,
+    verify_invite_request_proof, verify_invite_response_proof, PairingInvite, PairingUri,
+};
+pub use nearby::{NearbyTransfer, NearbyTransferState};
 pub use pairing::{generate_pin, PairingSession, PairingState};
+pub use pake::{validate_passphrase, PakeState, PassphraseListener};
+pub use replay::{
+    check_timestamp, ReplayCounters, ReplayRejection, ReplayStats, ReplayWindow,
+    MAX_CLOCK_SKEW_SECS,
+};
+pub use rotation::{announcement_digest, IdentityRotation};
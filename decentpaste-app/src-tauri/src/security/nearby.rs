@@ -0,0 +1,170 @@
+use aes_gcm::aead::OsRng;
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use serde::Serialize;
+use sha2::Sha256;
+use x25519_dalek::{PublicKey, StaticSecret};
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+use super::identity::derive_shared_secret;
+use crate::error::Result;
+
+/// Domain separation labels for the two values derived from the ephemeral ECDH secret.
+const TRANSFER_KEY_LABEL: &[u8] = b"decentpaste-nearby-key";
+const CONFIRMATION_CODE_LABEL: &[u8] = b"decentpaste-nearby-code";
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub enum NearbyTransferState {
+    /// Sender: waiting for the receiver to accept.
+    /// Receiver: waiting for the user to accept.
+    Offered,
+    /// Keys agreed; the sender's user compares confirmation codes.
+    AwaitingConfirmation,
+    /// Sender: content sent, waiting for the receiver's ack.
+    Sent,
+}
+
+/// A one-shot clipboard transfer to (or from) a device we are not paired with.
+///
+/// Keys are ephemeral and the transfer lives in memory only; nothing is stored
+/// once it completes, fails or times out.
+#[derive(Zeroize, ZeroizeOnDrop)]
+pub struct NearbyTransfer {
+    #[zeroize(skip)]
+    pub transfer_id: String,
+    #[zeroize(skip)]
+    pub peer_id: String,
+    #[zeroize(skip)]
+    pub peer_name: Option<String>,
+    #[zeroize(skip)]
+    pub is_sender: bool,
+    #[zeroize(skip)]
+    pub state: NearbyTransferState,
+    #[zeroize(skip)]
+    pub created_at: DateTime<Utc>,
+    private_key: Vec<u8>,
+    pub public_key: Vec<u8>,
+    /// The peer's ephemeral public key (receiver: from the offer)
+    #[zeroize(skip)]
+    pub peer_public_key: Option<Vec<u8>>,
+    /// Key for the content, once both ephemeral keys are known
+    transfer_key: Option<Vec<u8>>,
+    /// Six digits shown on both devices, once both ephemeral keys are known
+    pub confirmation_code: Option<String>,
+    /// The item to send (sender only)
+    content: Option<String>,
+}
+
+impl NearbyTransfer {
+    /// Start a transfer with a fresh ephemeral X25519 keypair.
+    pub fn new(transfer_id: String, peer_id: String, content: Option<String>) -> Self {
+        let private_key = StaticSecret::random_from_rng(OsRng);
+        let public_key = PublicKey::from(&private_key);
+        Self {
+            transfer_id,
+            peer_id,
+            peer_name: None,
+            is_sender: content.is_some(),
+            state: NearbyTransferState::Offered,
+            created_at: Utc::now(),
+            private_key: private_key.as_bytes().to_vec(),
+            public_key: public_key.as_bytes().to_vec(),
+            peer_public_key: None,
+            transfer_key: None,
+            confirmation_code: None,
+            content,
+        }
+    }
+
+    /// Complete the ECDH with the peer's ephemeral key and derive the transfer
+    /// key and confirmation code.
+    pub fn agree(&mut self, peer_public_key: &[u8]) -> Result<()> {
+        let mut shared = derive_shared_secret(&self.private_key, peer_public_key)?;
+        let (sender_key, receiver_key) = if self.is_sender {
+            (self.public_key.as_slice(), peer_public_key)
+        } else {
+            (peer_public_key, self.public_key.as_slice())
+        };
+        let transfer_key = derive(&shared, TRANSFER_KEY_LABEL, sender_key, receiver_key);
+        let code = derive(&shared, CONFIRMATION_CODE_LABEL, sender_key, receiver_key);
+        shared.zeroize();
+
+        self.peer_public_key = Some(peer_public_key.to_vec());
+        let number = u32::from_be_bytes([code[0], code[1], code[2], code[3]]) % 1_000_000;
+        self.confirmation_code = Some(format!("{:06}", number));
+        self.transfer_key = Some(transfer_key);
+        self.state = NearbyTransferState::AwaitingConfirmation;
+        Ok(())
+    }
+
+    pub fn transfer_key(&self) -> Option<&[u8]> {
+        self.transfer_key.as_deref()
+    }
+
+    pub fn content(&self) -> Option<&str> {
+        self.content.as_deref()
+    }
+
+    /// Whether the transfer is older than `timeout_secs`.
+    pub fn is_expired(&self, timeout_secs: u64) -> bool {
+        let duration = Utc::now().signed_duration_since(self.created_at);
+        duration.num_seconds() > timeout_secs as i64
+    }
+}
+
+impl std::fmt::Debug for NearbyTransfer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Never log keys or content
+        f.debug_struct("NearbyTransfer")
+            .field("transfer_id", &self.transfer_id)
+            .field("peer_id", &self.peer_id)
+            .field("is_sender", &self.is_sender)
+            .field("state", &self.state)
+            .finish()
+    }
+}
+
+fn derive(shared: &[u8], label: &[u8], sender_key: &[u8], receiver_key: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(shared).expect("HMAC accepts any key length");
+    mac.update(label);
+    mac.update(sender_key);
+    mac.update(receiver_key);
+    mac.finalize().into_bytes().to_vec()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_both_sides_agree() {
+        let mut sender = NearbyTransfer::new("t".into(), "receiver".into(), Some("hi".into()));
+        let mut receiver = NearbyTransfer::new("t".into(), "sender".into(), None);
+
+        let sender_key = sender.public_key.clone();
+        let receiver_key = receiver.public_key.clone();
+        sender.agree(&receiver_key).unwrap();
+        receiver.agree(&sender_key).unwrap();
+
+        assert_eq!(sender.transfer_key(), receiver.transfer_key());
+        assert_eq!(sender.confirmation_code, receiver.confirmation_code);
+        assert_eq!(sender.confirmation_code.as_ref().unwrap().len(), 6);
+        assert_ne!(
+            sender.transfer_key().unwrap(),
+            sender.confirmation_code.as_ref().unwrap().as_bytes()
+        );
+    }
+
+    #[test]
+    fn test_substituted_key_changes_code() {
+        let mut sender = NearbyTransfer::new("t".into(), "receiver".into(), Some("hi".into()));
+        let mut receiver = NearbyTransfer::new("t".into(), "sender".into(), None);
+        let attacker = NearbyTransfer::new("t".into(), "sender".into(), None);
+
+        let sender_key = sender.public_key.clone();
+        sender.agree(&attacker.public_key).unwrap();
+        receiver.agree(&sender_key).unwrap();
+
+        assert_ne!(sender.transfer_key(), receiver.transfer_key());
+    }
+}
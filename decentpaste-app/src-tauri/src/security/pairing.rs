@@ -2,6 +2,8 @@ use chrono::{DateTime, Utc};
 use rand::Rng;
 use serde::{Deserialize, Serialize};
 
+use super::pake::PakeKey;
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum PairingState {
     Initiated,
@@ -9,6 +11,8 @@ pub enum PairingState {
     AwaitingPeerConfirmation,
     Completed,
     Failed(String),
+    /// The session was not completed within the pairing timeout.
+    Expired,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -22,6 +26,15 @@ pub struct PairingSession {
     pub state: PairingState,
     pub is_initiator: bool,
     pub created_at: DateTime<Utc>,
+    /// Secret from a scanned pairing URI; when set the PIN step is skipped
+    #[serde(skip)]
+    pub invite_secret: Option<Vec<u8>>,
+    /// Public key fingerprint the responder must match (from the pairing URI)
+    #[serde(skip)]
+    pub expected_fingerprint: Option<String>,
+    /// Key agreed by passphrase pairing (SPAKE2); replaces ECDH when set
+    #[serde(skip)]
+    pub pake_key: Option<PakeKey>,
 }
 
 impl PairingSession {
@@ -36,6 +49,9 @@ impl PairingSession {
             state: PairingState::Initiated,
             is_initiator,
             created_at: Utc::now(),
+            invite_secret: None,
+            expected_fingerprint: None,
+            pake_key: None,
         }
     }
 
@@ -54,9 +70,28 @@ impl PairingSession {
         self
     }
 
-    pub fn is_expired(&self) -> bool {
+    /// Authenticate this session with a pairing URI instead of a PIN.
+    pub fn with_invite(mut self, secret: Vec<u8>, fingerprint: String) -> Self {
+        self.invite_secret = Some(secret);
+        self.expected_fingerprint = Some(fingerprint);
+        self
+    }
+
+    /// Whether the session is still waiting on either side of the pairing flow.
+    /// Completed, failed and expired sessions are terminal.
+    pub fn is_active(&self) -> bool {
+        matches!(
+            self.state,
+            PairingState::Initiated
+                | PairingState::AwaitingPinConfirmation
+                | PairingState::AwaitingPeerConfirmation
+        )
+    }
+
+    /// Whether the session is older than `timeout_secs`.
+    pub fn is_expired(&self, timeout_secs: u64) -> bool {
         let duration = Utc::now().signed_duration_since(self.created_at);
-        duration.num_minutes() > 5 // 5 minute timeout
+        duration.num_seconds() > timeout_secs as i64
     }
 }
 
@@ -76,4 +111,18 @@ mod tests {
         assert_eq!(pin.len(), 6);
         assert!(pin.chars().all(|c| c.is_ascii_digit()));
     }
+
+    #[test]
+    fn test_session_expiry() {
+        let mut session = PairingSession::new("session".into(), "peer".into(), true);
+        assert!(session.is_active());
+        assert!(!session.is_expired(300));
+
+        session.created_at = Utc::now() - chrono::Duration::seconds(301);
+        assert!(session.is_expired(300));
+        assert!(!session.is_expired(600));
+
+        session.state = PairingState::Expired;
+        assert!(!session.is_active());
+    }
 }
@@ -0,0 +1,239 @@
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use spake2::{Ed25519Group, Identity, Password, Spake2};
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+use crate::error::{DecentPasteError, Result};
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Minimum passphrase length accepted for passphrase pairing.
+pub const MIN_PASSPHRASE_LEN: usize = 6;
+
+const INITIATOR_CONFIRM_LABEL: &[u8] = b"decentpaste-pake-confirm-initiator";
+const RESPONDER_CONFIRM_LABEL: &[u8] = b"decentpaste-pake-confirm-responder";
+const SHARED_SECRET_LABEL: &[u8] = b"decentpaste-pake-shared-secret";
+const IDENTITY_BINDING_LABEL: &[u8] = b"decentpaste-pake-identity-keys";
+
+/// Passphrase armed on the accepting device by `enable_passphrase_pairing`.
+///
+/// Consumed by the first `PakeStart` it answers, so each arming allows
+/// exactly one online guess.
+#[derive(Clone, Zeroize, ZeroizeOnDrop)]
+pub struct PassphraseListener {
+    passphrase: String,
+    #[zeroize(skip)]
+    pub created_at: DateTime<Utc>,
+}
+
+impl PassphraseListener {
+    pub fn new(passphrase: String) -> Result<Self> {
+        validate_passphrase(&passphrase)?;
+        Ok(Self {
+            passphrase,
+            created_at: Utc::now(),
+        })
+    }
+
+    pub fn passphrase(&self) -> &str {
+        &self.passphrase
+    }
+
+    /// Whether the listener is older than `timeout_secs`.
+    pub fn is_expired(&self, timeout_secs: u64) -> bool {
+        let duration = Utc::now().signed_duration_since(self.created_at);
+        duration.num_seconds() > timeout_secs as i64
+    }
+}
+
+impl std::fmt::Debug for PassphraseListener {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PassphraseListener")
+            .field("passphrase", &"[REDACTED]")
+            .field("created_at", &self.created_at)
+            .finish()
+    }
+}
+
+pub fn validate_passphrase(passphrase: &str) -> Result<()> {
+    if passphrase.chars().count() < MIN_PASSPHRASE_LEN {
+        return Err(DecentPasteError::InvalidInput(format!(
+            "Passphrase must be at least {} characters",
+            MIN_PASSPHRASE_LEN
+        )));
+    }
+    Ok(())
+}
+
+/// In-progress SPAKE2 exchange (initiator side, waiting for the responder's message).
+pub struct PakeState(Spake2<Ed25519Group>);
+
+impl PakeState {
+    /// Start the exchange as the initiator. Returns the state and the message to send.
+    ///
+    /// Identities are the libp2p PeerIds of both devices, so a transcript can't be
+    /// redirected to a different peer.
+    pub fn start_initiator(
+        passphrase: &str,
+        initiator_id: &str,
+        responder_id: &str,
+    ) -> (Self, Vec<u8>) {
+        let (state, message) = Spake2::<Ed25519Group>::start_a(
+            &Password::new(passphrase.as_bytes()),
+            &Identity::new(initiator_id.as_bytes()),
+            &Identity::new(responder_id.as_bytes()),
+        );
+        (Self(state), message)
+    }
+
+    /// Start the exchange as the responder. Returns the state and the message to send.
+    pub fn start_responder(
+        passphrase: &str,
+        initiator_id: &str,
+        responder_id: &str,
+    ) -> (Self, Vec<u8>) {
+        let (state, message) = Spake2::<Ed25519Group>::start_b(
+            &Password::new(passphrase.as_bytes()),
+            &Identity::new(initiator_id.as_bytes()),
+            &Identity::new(responder_id.as_bytes()),
+        );
+        (Self(state), message)
+    }
+
+    /// Complete the exchange with the peer's message.
+    ///
+    /// Both devices' X25519 identity keys are mixed into the agreed key, so a key
+    /// substituted in transit fails the confirmation just like a wrong passphrase,
+    /// and the key recorded for the peer is as trustworthy as the passphrase.
+    /// This only fails on malformed input.
+    pub fn finish(
+        self,
+        peer_message: &[u8],
+        initiator_key: &[u8],
+        responder_key: &[u8],
+    ) -> Result<PakeKey> {
+        let key = self.0.finish(peer_message).map_err(|e| {
+            DecentPasteError::Pairing(format!("Passphrase exchange failed: {:?}", e))
+        })?;
+        let mut mac = HmacSha256::new_from_slice(&key).expect("HMAC accepts any key length");
+        mac.update(IDENTITY_BINDING_LABEL);
+        for identity_key in [initiator_key, responder_key] {
+            mac.update(&(identity_key.len() as u64).to_be_bytes());
+            mac.update(identity_key);
+        }
+        Ok(PakeKey(mac.finalize().into_bytes().to_vec()))
+    }
+}
+
+/// Key agreed by SPAKE2. Both sides hold the same key only if the passphrases matched.
+#[derive(Clone, Zeroize, ZeroizeOnDrop)]
+pub struct PakeKey(Vec<u8>);
+
+impl std::fmt::Debug for PakeKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("PakeKey([REDACTED])")
+    }
+}
+
+impl PakeKey {
+    fn mac(&self, label: &[u8], session_id: &str) -> HmacSha256 {
+        let mut mac = HmacSha256::new_from_slice(&self.0).expect("HMAC accepts any key length");
+        mac.update(label);
+        mac.update(session_id.as_bytes());
+        mac
+    }
+
+    fn label(is_initiator: bool) -> &'static [u8] {
+        if is_initiator {
+            INITIATOR_CONFIRM_LABEL
+        } else {
+            RESPONDER_CONFIRM_LABEL
+        }
+    }
+
+    /// Key confirmation tag proving to the peer that we derived the same key.
+    pub fn confirmation(&self, is_initiator: bool, session_id: &str) -> Vec<u8> {
+        self.mac(Self::label(is_initiator), session_id)
+            .finalize()
+            .into_bytes()
+            .to_vec()
+    }
+
+    /// Verify the peer's confirmation tag (`peer_is_initiator` is the peer's role).
+    pub fn verify_confirmation(
+        &self,
+        peer_is_initiator: bool,
+        session_id: &str,
+        tag: &[u8],
+    ) -> bool {
+        self.mac(Self::label(peer_is_initiator), session_id)
+            .verify_slice(tag)
+            .is_ok()
+    }
+
+    /// 32-byte shared secret stored in `PairedPeer`.
+    pub fn shared_secret(&self) -> Vec<u8> {
+        self.mac(SHARED_SECRET_LABEL, "")
+            .finalize()
+            .into_bytes()
+            .to_vec()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY_A: &[u8] = &[1; 32];
+    const KEY_B: &[u8] = &[2; 32];
+
+    fn exchange(a_pass: &str, b_pass: &str) -> (PakeKey, PakeKey) {
+        let (a, msg_a) = PakeState::start_initiator(a_pass, "peer-a", "peer-b");
+        let (b, msg_b) = PakeState::start_responder(b_pass, "peer-a", "peer-b");
+        (
+            a.finish(&msg_b, KEY_A, KEY_B).unwrap(),
+            b.finish(&msg_a, KEY_A, KEY_B).unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_matching_passphrase() {
+        let (a, b) = exchange("correct horse", "correct horse");
+        assert_eq!(a.shared_secret(), b.shared_secret());
+        assert_eq!(a.shared_secret().len(), 32);
+
+        let tag_b = b.confirmation(false, "s1");
+        assert!(a.verify_confirmation(false, "s1", &tag_b));
+        let tag_a = a.confirmation(true, "s1");
+        assert!(b.verify_confirmation(true, "s1", &tag_a));
+        // Tags are role-bound
+        assert!(!b.verify_confirmation(true, "s1", &tag_b));
+    }
+
+    #[test]
+    fn test_mismatched_passphrase() {
+        let (a, b) = exchange("correct horse", "battery staple");
+        assert_ne!(a.shared_secret(), b.shared_secret());
+        let tag_b = b.confirmation(false, "s1");
+        assert!(!a.verify_confirmation(false, "s1", &tag_b));
+    }
+
+    #[test]
+    fn test_substituted_identity_key() {
+        // A relay swapping the initiator's identity key for its own
+        let (a, msg_a) = PakeState::start_initiator("correct horse", "peer-a", "peer-b");
+        let (b, msg_b) = PakeState::start_responder("correct horse", "peer-a", "peer-b");
+        let a = a.finish(&msg_b, KEY_A, KEY_B).unwrap();
+        let b = b.finish(&msg_a, &[3; 32], KEY_B).unwrap();
+        assert_ne!(a.shared_secret(), b.shared_secret());
+        let tag_b = b.confirmation(false, "s1");
+        assert!(!a.verify_confirmation(false, "s1", &tag_b));
+    }
+
+    #[test]
+    fn test_passphrase_length() {
+        assert!(PassphraseListener::new("short".into()).is_err());
+        assert!(PassphraseListener::new("long enough".into()).is_ok());
+    }
+}
@@ -0,0 +1,200 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+
+/// How far a message timestamp may lie in the future, and how old a live
+/// (gossipsub) message may be.
+pub const MAX_CLOCK_SKEW_SECS: i64 = 120;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReplayRejection {
+    /// Message ID was already accepted
+    Replayed,
+    /// Sent before the window's retention, so a replay couldn't be detected
+    TooOld,
+    /// Timestamp too far in the future or past the allowed age
+    ClockSkew,
+    /// Authenticated fields don't match the message they arrived in
+    Tampered,
+}
+
+/// Message IDs accepted from one sender, with the time each was sent.
+///
+/// IDs are kept for `retention_secs`, which must cover the oldest message any
+/// path accepts, so order doesn't matter: a message queued for sync is still
+/// accepted after newer live ones.
+#[derive(Debug, Clone, Default)]
+pub struct ReplayWindow {
+    seen: HashMap<String, DateTime<Utc>>,
+}
+
+impl ReplayWindow {
+    /// Accept `message_id`, sent at `timestamp`, if it wasn't seen before.
+    pub fn check(
+        &mut self,
+        message_id: &str,
+        timestamp: DateTime<Utc>,
+        now: DateTime<Utc>,
+        retention_secs: i64,
+    ) -> Result<(), ReplayRejection> {
+        let cutoff = now - Duration::seconds(retention_secs);
+        self.seen.retain(|_, sent| *sent >= cutoff);
+        if timestamp < cutoff {
+            return Err(ReplayRejection::TooOld);
+        }
+        if self.seen.contains_key(message_id) {
+            return Err(ReplayRejection::Replayed);
+        }
+        self.seen.insert(message_id.to_string(), timestamp);
+        Ok(())
+    }
+}
+
+/// Reject timestamps more than `MAX_CLOCK_SKEW_SECS` in the future or older
+/// than `max_age_secs`.
+pub fn check_timestamp(
+    timestamp: DateTime<Utc>,
+    now: DateTime<Utc>,
+    max_age_secs: i64,
+) -> Result<(), ReplayRejection> {
+    let age = now.signed_duration_since(timestamp).num_seconds();
+    if age < -MAX_CLOCK_SKEW_SECS || age > max_age_secs {
+        return Err(ReplayRejection::ClockSkew);
+    }
+    Ok(())
+}
+
+/// Counts of clipboard messages rejected by replay protection.
+#[derive(Debug, Default)]
+pub struct ReplayCounters {
+    replayed: AtomicU64,
+    too_old: AtomicU64,
+    clock_skew: AtomicU64,
+    tampered: AtomicU64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ReplayStats {
+    pub replayed: u64,
+    pub too_old: u64,
+    pub clock_skew: u64,
+    pub tampered: u64,
+}
+
+impl ReplayCounters {
+    pub fn record(&self, rejection: ReplayRejection) {
+        let counter = match rejection {
+            ReplayRejection::Replayed => &self.replayed,
+            ReplayRejection::TooOld => &self.too_old,
+            ReplayRejection::ClockSkew => &self.clock_skew,
+            ReplayRejection::Tampered => &self.tampered,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn snapshot(&self) -> ReplayStats {
+        ReplayStats {
+            replayed: self.replayed.load(Ordering::Relaxed),
+            too_old: self.too_old.load(Ordering::Relaxed),
+            clock_skew: self.clock_skew.load(Ordering::Relaxed),
+            tampered: self.tampered.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RETENTION: i64 = 60 * 60;
+
+    fn ago(now: DateTime<Utc>, secs: i64) -> DateTime<Utc> {
+        now - Duration::seconds(secs)
+    }
+
+    #[test]
+    fn test_window_rejects_replays() {
+        let mut window = ReplayWindow::default();
+        let now = Utc::now();
+        assert!(window.check("a", now, now, RETENTION).is_ok());
+        assert_eq!(
+            window.check("a", now, now, RETENTION),
+            Err(ReplayRejection::Replayed)
+        );
+        assert!(window.check("b", now, now, RETENTION).is_ok());
+        assert_eq!(
+            window.check("a", now, now, RETENTION),
+            Err(ReplayRejection::Replayed)
+        );
+    }
+
+    #[test]
+    fn test_window_accepts_out_of_order_once() {
+        let mut window = ReplayWindow::default();
+        let now = Utc::now();
+        assert!(window.check("b", ago(now, 10), now, RETENTION).is_ok());
+        assert!(window.check("c", ago(now, 5), now, RETENTION).is_ok());
+        assert!(window.check("a", ago(now, 20), now, RETENTION).is_ok());
+        assert_eq!(
+            window.check("a", ago(now, 20), now, RETENTION),
+            Err(ReplayRejection::Replayed)
+        );
+        assert_eq!(
+            window.check("z", ago(now, RETENTION + 1), now, RETENTION),
+            Err(ReplayRejection::TooOld)
+        );
+    }
+
+    #[test]
+    fn test_window_accepts_buffered_after_live() {
+        // A live message arrives, then the sync queue delivers older ones,
+        // far more of them than arrived live
+        let mut window = ReplayWindow::default();
+        let now = Utc::now();
+        assert!(window.check("live", now, now, RETENTION).is_ok());
+        for i in 0..200 {
+            let id = format!("queued-{}", i);
+            let sent = ago(now, RETENTION - 1 - i);
+            assert!(window.check(&id, sent, now, RETENTION).is_ok());
+        }
+        assert_eq!(
+            window.check("queued-0", ago(now, RETENTION - 1), now, RETENTION),
+            Err(ReplayRejection::Replayed)
+        );
+    }
+
+    #[test]
+    fn test_window_forgets_after_retention() {
+        let mut window = ReplayWindow::default();
+        let sent = Utc::now();
+        assert!(window.check("a", sent, sent, RETENTION).is_ok());
+        let later = sent + Duration::seconds(RETENTION + 1);
+        // Pruned, and now rejected by age instead of by ID
+        assert_eq!(
+            window.check("a", sent, later, RETENTION),
+            Err(ReplayRejection::TooOld)
+        );
+        assert!(window.seen.is_empty());
+    }
+
+    #[test]
+    fn test_timestamp_skew() {
+        let now = Utc::now();
+        assert!(check_timestamp(now, now, 60).is_ok());
+        assert!(check_timestamp(now - chrono::Duration::seconds(30), now, 60).is_ok());
+        assert_eq!(
+            check_timestamp(now - chrono::Duration::seconds(90), now, 60),
+            Err(ReplayRejection::ClockSkew)
+        );
+        assert_eq!(
+            check_timestamp(
+                now + chrono::Duration::seconds(MAX_CLOCK_SKEW_SECS + 1),
+                now,
+                60
+            ),
+            Err(ReplayRejection::ClockSkew)
+        );
+    }
+}
@@ -0,0 +1,150 @@
+use chrono::{DateTime, Utc};
+use libp2p::identity::{Keypair, PublicKey};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::error::{DecentPasteError, Result};
+
+const ROTATION_LABEL: &[u8] = b"decentpaste-identity-rotation";
+
+/// Announcement that a device replaced its identity keys.
+///
+/// Signed with the device's old libp2p key, so only the holder of the old
+/// identity can vouch for the new one. `new_peer_id` equals `old_peer_id`
+/// when only the X25519 key was rotated.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct IdentityRotation {
+    pub old_peer_id: String,
+    pub new_peer_id: String,
+    /// Old X25519 public key
+    pub old_public_key: Vec<u8>,
+    /// New X25519 public key
+    pub new_public_key: Vec<u8>,
+    /// Old libp2p public key (protobuf encoding), used to check `signature`
+    pub signing_key: Vec<u8>,
+    pub timestamp: DateTime<Utc>,
+    pub signature: Vec<u8>,
+}
+
+impl IdentityRotation {
+    /// Create a rotation record signed with the outgoing libp2p keypair.
+    pub fn sign(
+        old_keypair: &Keypair,
+        new_peer_id: String,
+        old_public_key: Vec<u8>,
+        new_public_key: Vec<u8>,
+    ) -> Result<Self> {
+        let mut rotation = Self {
+            old_peer_id: old_keypair.public().to_peer_id().to_string(),
+            new_peer_id,
+            old_public_key,
+            new_public_key,
+            signing_key: old_keypair.public().encode_protobuf(),
+            timestamp: Utc::now(),
+            signature: Vec::new(),
+        };
+        rotation.signature = old_keypair
+            .sign(&rotation.signing_bytes())
+            .map_err(|e| DecentPasteError::Encryption(format!("Failed to sign rotation: {}", e)))?;
+        Ok(rotation)
+    }
+
+    /// Check that the record was signed by the key behind `old_peer_id`.
+    pub fn verify(&self) -> Result<()> {
+        let invalid =
+            |msg: &str| DecentPasteError::Encryption(format!("Identity rotation: {}", msg));
+
+        let signing_key =
+            PublicKey::try_decode_protobuf(&self.signing_key).map_err(|_| invalid("bad key"))?;
+        if signing_key.to_peer_id().to_string() != self.old_peer_id {
+            return Err(invalid("signing key does not match old peer ID"));
+        }
+        if self.new_public_key.len() != 32 {
+            return Err(invalid("new public key must be 32 bytes"));
+        }
+        if !signing_key.verify(&self.signing_bytes(), &self.signature) {
+            return Err(invalid("bad signature"));
+        }
+        Ok(())
+    }
+
+    pub fn peer_id_changed(&self) -> bool {
+        self.old_peer_id != self.new_peer_id
+    }
+
+    fn signing_bytes(&self) -> Vec<u8> {
+        let mut bytes = ROTATION_LABEL.to_vec();
+        for field in [
+            self.old_peer_id.as_bytes(),
+            self.new_peer_id.as_bytes(),
+            &self.old_public_key,
+            &self.new_public_key,
+            self.timestamp.to_rfc3339().as_bytes(),
+        ] {
+            // Length-prefix every field so boundaries can't shift
+            bytes.extend_from_slice(&(field.len() as u32).to_be_bytes());
+            bytes.extend_from_slice(field);
+        }
+        bytes
+    }
+}
+
+/// Identifies an encrypted rotation announcement, so one that was already applied
+/// can be recognised after its secret was replaced.
+pub fn announcement_digest(encrypted_payload: &[u8]) -> String {
+    hex::encode(Sha256::digest(encrypted_payload))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(old: &Keypair, new: &Keypair) -> IdentityRotation {
+        IdentityRotation::sign(
+            old,
+            new.public().to_peer_id().to_string(),
+            vec![1u8; 32],
+            vec![2u8; 32],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_rotation_roundtrip() {
+        let old = Keypair::generate_ed25519();
+        let new = Keypair::generate_ed25519();
+        let rotation = sample(&old, &new);
+
+        assert!(rotation.verify().is_ok());
+        assert!(rotation.peer_id_changed());
+        assert_eq!(rotation.old_peer_id, old.public().to_peer_id().to_string());
+    }
+
+    #[test]
+    fn test_rotation_rejects_tampering() {
+        let old = Keypair::generate_ed25519();
+        let new = Keypair::generate_ed25519();
+
+        let mut rotation = sample(&old, &new);
+        rotation.new_public_key = vec![3u8; 32];
+        assert!(rotation.verify().is_err());
+
+        let mut rotation = sample(&old, &new);
+        rotation.new_peer_id = Keypair::generate_ed25519()
+            .public()
+            .to_peer_id()
+            .to_string();
+        assert!(rotation.verify().is_err());
+    }
+
+    #[test]
+    fn test_rotation_rejects_foreign_signer() {
+        let old = Keypair::generate_ed25519();
+        let new = Keypair::generate_ed25519();
+
+        // Signed by `new` but claiming to rotate away from `old`
+        let mut rotation = sample(&new, &new);
+        rotation.old_peer_id = old.public().to_peer_id().to_string();
+        assert!(rotation.verify().is_err());
+    }
+}
@@ -2,26 +2,102 @@ use std::collections::{HashMap, HashSet};
 use std::sync::atomic::{AtomicBool, AtomicUsize};
 use std::sync::Arc;
 
-use chrono::{Duration, Utc};
+use chrono::{DateTime, Duration, Utc};
 use tokio::sync::{mpsc, Notify, RwLock};
-use tracing::{debug, warn};
+use tracing::{debug, error, warn};
+use zeroize::Zeroize;
 
-use crate::clipboard::ClipboardEntry;
-use crate::error::Result;
-use crate::network::protocol::ClipboardMessage;
+use crate::clipboard::reconcile;
+use crate::clipboard::{
+    ClipboardEntry, ClipboardMonitor, ClipboardSelection, DeliveryStatus, HistoryItemRef,
+    HistoryTombstone, HlcTimestamp, HybridClock, SensitiveFilter,
+};
+use crate::error::{DecentPasteError, Result};
+use crate::network::protocol::{
+    ClipboardMessage, DeliveryReport, GroupPayload, HistoryPayload, HistoryRetention, RelayInfo,
+    RotationPayload, SealedClipboard, SEALED_CLIPBOARD_VERSION,
+};
 use crate::network::{DiscoveredPeer, NetworkCommand, NetworkStatus};
-use crate::security::PairingSession;
-use crate::storage::{AppSettings, DeviceIdentity, PairedPeer};
+use crate::security::{
+    announcement_digest, check_timestamp, decrypt_content, derive_group_pair_secret,
+    derive_shared_secret, encrypt_content, hash_content, unpair_proof, verify_invite_request_proof,
+    IdentityRotation, NearbyTransfer, PairingInvite, PairingSession, PairingState, PakeState,
+    PassphraseListener, ReplayCounters, ReplayRejection, ReplayWindow, MAX_CLOCK_SKEW_SECS,
+};
+use crate::storage::{
+    AppSettings, DeviceIdentity, PairedPeer, PairingExpiry, PairingGroup, PendingRotation,
+    PendingUnpair, SyncPolicy,
+};
 use crate::vault::{VaultManager, VaultStatus};
 
-/// Maximum number of messages to buffer per peer.
-/// Set to 1 for "latest only" behavior - sync only delivers the most recent message.
-pub const SYNC_MAX_BUFFER_SIZE: usize = 1;
+/// Bounds for `AppSettings::pairing_timeout_secs`. Also the lifetime of invites
+/// and nearby transfers, so 0 would expire them all on the next sweep.
+pub const MIN_PAIRING_TIMEOUT_SECS: u64 = 30;
+pub const MAX_PAIRING_TIMEOUT_SECS: u64 = 60 * 60;
 
-/// Time-to-live for buffered messages in seconds.
-/// Messages older than this are filtered out during sync.
-/// 5 minutes is sufficient for typical offline durations (app restart, mobile background).
-pub const SYNC_TTL_SECONDS: i64 = 60 * 5;
+/// Upper limit for `AppSettings::sync_buffer_size` (messages queued per peer).
+pub const MAX_SYNC_BUFFER_SIZE: usize = 500;
+
+/// Upper limit for `AppSettings::sync_buffer_ttl_secs`.
+/// Receivers accept synced messages up to this old, whatever the sender's setting.
+pub const MAX_SYNC_BUFFER_TTL_SECS: u64 = 60 * 60 * 24 * 7;
+
+/// Age of the oldest clipboard message any path accepts (a synced message at the
+/// queue TTL limit). Replay windows remember message IDs for this long.
+pub const MAX_CLIPBOARD_MESSAGE_AGE_SECS: i64 =
+    MAX_SYNC_BUFFER_TTL_SECS as i64 + MAX_CLOCK_SKEW_SECS;
+
+/// Maximum number of unanswered incoming nearby send offers.
+/// Anyone on the network can send offers, so they are capped.
+pub const MAX_INCOMING_NEARBY_OFFERS: usize = 4;
+
+/// How many relays a message for an offline peer may pass through.
+pub const MAX_RELAY_HOPS: u8 = 2;
+
+/// Messages we hold as a relay per origin device; its oldest are dropped beyond this.
+pub const MAX_RELAYED_PER_ORIGIN: usize = 20;
+
+/// Upper limit for the TTL of a self-destructing item.
+pub const MAX_SELF_DESTRUCT_SECS: u64 = 60 * 60;
+
+/// Maximum number of incoming clipboard pulls waiting for the user's approval.
+pub const MAX_INCOMING_CLIPBOARD_PULLS: usize = 4;
+
+/// A clipboard pull unanswered after this long (approval included) is dropped.
+const CLIPBOARD_PULL_TIMEOUT_SECS: i64 = 120;
+
+/// A delivery still pending after this long is marked failed once the message
+/// is no longer queued for the recipient.
+const DELIVERY_ACK_TIMEOUT_SECS: i64 = 60;
+
+/// Content bytes per `HistoryPayload::Entries` message; larger transfers are split
+/// to stay well below the request size limit.
+const MAX_HISTORY_BATCH_BYTES: usize = 256 * 1024;
+
+/// A clipboard message ready to broadcast, with the paired peers able to decrypt it.
+#[derive(Debug, Clone)]
+pub struct OutgoingClipboard {
+    pub message: ClipboardMessage,
+    pub recipients: Vec<String>,
+}
+
+/// A clipboard pull waiting for an answer (memory only).
+#[derive(Debug, Clone)]
+pub struct ClipboardPull {
+    pub request_id: String,
+    pub peer_id: String,
+    /// A peer's request waiting for our user's approval, rather than our own request
+    pub incoming: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A group state from a paired peer that would add us to its group, waiting for
+/// our user to accept it (memory only; the inviter re-sends it on reconnect).
+#[derive(Debug, Clone)]
+pub struct GroupInvite {
+    pub from_peer_id: String,
+    pub group: PairingGroup,
+}
 
 /// Clipboard content received while app was in background (Android)
 #[derive(Debug, Clone)]
@@ -30,166 +106,1755 @@ pub struct PendingClipboard {
     pub from_device: String,
 }
 
-pub struct AppState {
-    pub device_identity: Arc<RwLock<Option<DeviceIdentity>>>,
-    pub settings: Arc<RwLock<AppSettings>>,
-    pub paired_peers: Arc<RwLock<Vec<PairedPeer>>>,
-    pub discovered_peers: Arc<RwLock<Vec<DiscoveredPeer>>>,
-    pub clipboard_history: Arc<RwLock<Vec<ClipboardEntry>>>,
-    pub network_status: Arc<RwLock<NetworkStatus>>,
-    pub pairing_sessions: Arc<RwLock<Vec<PairingSession>>>,
-    pub network_command_tx: Arc<RwLock<Option<mpsc::Sender<NetworkCommand>>>>,
-    /// Clipboard content received while app was in background (mobile only)
-    /// This is processed when app resumes to foreground
-    pub pending_clipboard: Arc<RwLock<Option<PendingClipboard>>>,
-    /// Whether the app is currently in foreground (tracked for mobile)
-    pub is_foreground: Arc<RwLock<bool>>,
-    /// Peers confirmed ready to receive broadcast messages.
-    /// This is protocol-agnostic - the network layer determines what "ready" means.
-    /// Currently: gossipsub topic subscription. Future: could be any protocol.
-    pub ready_peers: Arc<RwLock<HashSet<String>>>,
-    /// Current vault authentication status
-    pub vault_status: Arc<RwLock<VaultStatus>>,
-    /// VaultManager instance for encrypted storage (only present when vault is open)
-    pub vault_manager: Arc<RwLock<Option<VaultManager>>>,
+pub struct AppState {
+    pub device_identity: Arc<RwLock<Option<DeviceIdentity>>>,
+    pub settings: Arc<RwLock<AppSettings>>,
+    /// `settings.sensitive_rules`, compiled
+    pub sensitive_filter: Arc<RwLock<SensitiveFilter>>,
+    pub paired_peers: Arc<RwLock<Vec<PairedPeer>>>,
+    /// Pairing group this device belongs to (at most one)
+    pub pairing_group: Arc<RwLock<Option<PairingGroup>>>,
+    /// Unpair notifications for removed peers, kept until the peer acknowledges them
+    pub pending_unpairs: Arc<RwLock<Vec<PendingUnpair>>>,
+    /// Identity rotation announcements, kept until each paired peer acknowledges them
+    pub pending_rotations: Arc<RwLock<Vec<PendingRotation>>>,
+    pub discovered_peers: Arc<RwLock<Vec<DiscoveredPeer>>>,
+    pub clipboard_history: Arc<RwLock<Vec<ClipboardEntry>>>,
+    pub network_status: Arc<RwLock<NetworkStatus>>,
+    pub pairing_sessions: Arc<RwLock<Vec<PairingSession>>>,
+    /// Outstanding one-time invites from `create_pairing_uri`.
+    /// Each invite is consumed by the first valid request that proves its secret.
+    pub pairing_invites: Arc<RwLock<Vec<PairingInvite>>>,
+    /// Passphrase armed by `enable_passphrase_pairing`, consumed by the next `PakeStart`.
+    pub passphrase_listener: Arc<RwLock<Option<PassphraseListener>>>,
+    /// SPAKE2 exchanges we initiated, keyed by session_id, waiting for a `PakeReply`.
+    pub pake_states: Arc<RwLock<HashMap<String, PakeState>>>,
+    /// One-shot nearby sends in progress (memory only, never persisted).
+    pub nearby_transfers: Arc<RwLock<Vec<NearbyTransfer>>>,
+    /// Clipboard pulls we sent, and peers' pulls waiting for approval
+    pub clipboard_pulls: Arc<RwLock<Vec<ClipboardPull>>>,
+    /// Invitation to a pairing group waiting for our user's answer
+    pub pending_group_invite: Arc<RwLock<Option<GroupInvite>>>,
+    /// Our libp2p PeerId (set once the network starts)
+    pub local_peer_id: Arc<RwLock<Option<String>>>,
+    /// Addresses the swarm is currently listening on (advertised in pairing URIs)
+    pub listen_addresses: Arc<RwLock<Vec<String>>>,
+    pub network_command_tx: Arc<RwLock<Option<mpsc::Sender<NetworkCommand>>>>,
+    /// Running clipboard monitor, so commands that write the clipboard can prevent echo
+    pub clipboard_monitor: Arc<RwLock<Option<Arc<ClipboardMonitor>>>>,
+    /// Clipboard content received while app was in background (mobile only)
+    /// This is processed when app resumes to foreground
+    pub pending_clipboard: Arc<RwLock<Option<PendingClipboard>>>,
+    /// Whether the app is currently in foreground (tracked for mobile)
+    pub is_foreground: Arc<RwLock<bool>>,
+    /// Peers confirmed ready to receive broadcast messages.
+    /// This is protocol-agnostic - the network layer determines what "ready" means.
+    /// Currently: gossipsub topic subscription. Future: could be any protocol.
+    pub ready_peers: Arc<RwLock<HashSet<String>>>,
+    /// Current vault authentication status
+    pub vault_status: Arc<RwLock<VaultStatus>>,
+    /// VaultManager instance for encrypted storage (only present when vault is open)
+    pub vault_manager: Arc<RwLock<Option<VaultManager>>>,
+
+    // =========================================================================
+    // Connection Management State
+    // =========================================================================
+    /// Guard against concurrent reconnection attempts.
+    /// Only one ensure_connected() operation runs at a time.
+    pub reconnect_in_progress: AtomicBool,
+
+    /// Count of pending dial attempts during reconnection.
+    /// Decremented as connections succeed or fail.
+    pub pending_dials: AtomicUsize,
+
+    /// Notified when all pending dials complete.
+    /// Used by ensure_connected() to await completion.
+    pub dials_complete_notify: Arc<Notify>,
+
+    /// Per-recipient buffering: we store messages WE sent that THEY missed.
+    /// Key = peer_id of the recipient (who missed the message)
+    /// Value = messages we sent that they should receive on reconnection
+    ///
+    /// ALWAYS buffer for all paired peers, regardless of online status.
+    /// This handles the race condition where a peer goes offline mid-transmission.
+    /// Persisted in the vault, so the queue survives restarts; depth and TTL come
+    /// from `AppSettings::sync_buffer_size` / `sync_buffer_ttl_secs`.
+    pub message_buffers: Arc<RwLock<HashMap<String, Vec<ClipboardMessage>>>>,
+
+    /// Messages we hold as a relay for other devices, by recipient peer_id. Kept
+    /// apart from `message_buffers` so relayed traffic can't evict our own queue;
+    /// limited to `MAX_RELAYED_PER_ORIGIN` per origin. Persisted in the vault.
+    pub relay_buffers: Arc<RwLock<HashMap<String, Vec<ClipboardMessage>>>>,
+
+    // =========================================================================
+    // Replay Protection
+    // =========================================================================
+    /// Accepted message IDs per sender peer_id (memory only).
+    pub replay_windows: Arc<RwLock<HashMap<String, ReplayWindow>>>,
+
+    /// Clipboard messages rejected by replay protection, by reason.
+    pub replay_counters: ReplayCounters,
+
+    /// Hybrid logical clock for ordering clipboard history across devices.
+    pub hlc: HybridClock,
+
+    /// Deleted history entries, so history reconciliation propagates deletions
+    /// instead of syncing the entries back. Persisted in the vault.
+    pub history_tombstones: Arc<RwLock<Vec<HistoryTombstone>>>,
+}
+
+impl AppState {
+    pub fn new() -> Self {
+        Self {
+            device_identity: Arc::new(RwLock::new(None)),
+            settings: Arc::new(RwLock::new(AppSettings::default())),
+            sensitive_filter: Arc::new(RwLock::new(SensitiveFilter::new(
+                &AppSettings::default().sensitive_rules,
+            ))),
+            paired_peers: Arc::new(RwLock::new(Vec::new())),
+            pairing_group: Arc::new(RwLock::new(None)),
+            pending_unpairs: Arc::new(RwLock::new(Vec::new())),
+            pending_rotations: Arc::new(RwLock::new(Vec::new())),
+            discovered_peers: Arc::new(RwLock::new(Vec::new())),
+            clipboard_history: Arc::new(RwLock::new(Vec::new())),
+            network_status: Arc::new(RwLock::new(NetworkStatus::Disconnected)),
+            pairing_sessions: Arc::new(RwLock::new(Vec::new())),
+            pairing_invites: Arc::new(RwLock::new(Vec::new())),
+            passphrase_listener: Arc::new(RwLock::new(None)),
+            pake_states: Arc::new(RwLock::new(HashMap::new())),
+            nearby_transfers: Arc::new(RwLock::new(Vec::new())),
+            clipboard_pulls: Arc::new(RwLock::new(Vec::new())),
+            pending_group_invite: Arc::new(RwLock::new(None)),
+            local_peer_id: Arc::new(RwLock::new(None)),
+            listen_addresses: Arc::new(RwLock::new(Vec::new())),
+            network_command_tx: Arc::new(RwLock::new(None)),
+            clipboard_monitor: Arc::new(RwLock::new(None)),
+            pending_clipboard: Arc::new(RwLock::new(None)),
+            is_foreground: Arc::new(RwLock::new(true)), // Assume foreground at start
+            ready_peers: Arc::new(RwLock::new(HashSet::new())), // No peers ready initially
+            vault_status: Arc::new(RwLock::new(VaultStatus::NotSetup)), // Vault starts as not setup
+            vault_manager: Arc::new(RwLock::new(None)), // No vault manager until unlocked
+
+            // Connection management
+            reconnect_in_progress: AtomicBool::new(false),
+            pending_dials: AtomicUsize::new(0),
+            dials_complete_notify: Arc::new(Notify::new()),
+
+            // Sync message buffers (per-recipient)
+            message_buffers: Arc::new(RwLock::new(HashMap::new())),
+            relay_buffers: Arc::new(RwLock::new(HashMap::new())),
+            replay_windows: Arc::new(RwLock::new(HashMap::new())),
+            replay_counters: ReplayCounters::default(),
+            hlc: HybridClock::default(),
+            history_tombstones: Arc::new(RwLock::new(Vec::new())),
+        }
+    }
+
+    /// Insert `entry` into the history by HLC order, replacing any entry with the
+    /// same content. Returns `true` if it is now the newest entry ("latest wins":
+    /// only then should it replace the system clipboard).
+    pub async fn add_clipboard_entry(&self, entry: ClipboardEntry) -> bool {
+        let is_latest = {
+            let mut history = self.clipboard_history.write().await;
+
+            // Check for existing entry with the same content hash (deduplication)
+            if let Some(idx) = history
+                .iter()
+                .position(|e| e.content_hash == entry.content_hash)
+            {
+                // Remove existing entry - it will be reinserted at the correct position
+                history.remove(idx);
+                debug!(
+                    "Updated existing clipboard entry (will reinsert): {}",
+                    &entry.content_hash[..8]
+                );
+            }
+
+            // Insert at the correct position by HLC (not wall-clock time, which
+            // differs between devices). History is sorted newest-first, so find the
+            // first entry older than this one. This is important for sync: synced
+            // messages may be older and should appear in the correct position.
+            let insert_pos = history
+                .iter()
+                .position(|e| e.order_key() < entry.order_key())
+                .unwrap_or(history.len());
+
+            history.insert(insert_pos, entry);
+
+            // Trim to max size from settings
+            let max_size = self.settings.read().await.clipboard_history_limit;
+            history.truncate(max_size);
+            insert_pos == 0
+        };
+
+        // Flush-on-write: persist clipboard history immediately
+        if let Err(e) = self.flush_clipboard_history().await {
+            warn!("Failed to flush clipboard history: {}", e);
+        }
+        is_latest
+    }
+
+    /// Whether an item with `hlc` from `origin_device_id` is newer than every history
+    /// entry ("latest wins" for items that aren't added to history).
+    pub async fn is_latest_clipboard(&self, hlc: HlcTimestamp, origin_device_id: &str) -> bool {
+        let history = self.clipboard_history.read().await;
+        !history
+            .first()
+            .is_some_and(|e| e.order_key() >= (hlc, origin_device_id))
+    }
+
+    /// Remove history entries whose `expires_at` has passed and return them.
+    pub async fn expire_clipboard_entries(&self) -> Vec<ClipboardEntry> {
+        let now = Utc::now();
+        let expired: Vec<ClipboardEntry> = {
+            let mut history = self.clipboard_history.write().await;
+            let (expired, kept) = std::mem::take(&mut *history)
+                .into_iter()
+                .partition(|e| e.expires_at.is_some_and(|at| at <= now));
+            *history = kept;
+            expired
+        };
+
+        if !expired.is_empty() {
+            if let Err(e) = self.flush_clipboard_history().await {
+                warn!("Failed to flush clipboard history: {}", e);
+            }
+        }
+        expired
+    }
+
+    /// Store an incoming nearby send offer.
+    ///
+    /// A newer offer from the same peer replaces the older one. Returns `false`
+    /// if too many offers from other peers are already waiting.
+    pub async fn add_nearby_offer(&self, transfer: NearbyTransfer) -> bool {
+        let mut transfers = self.nearby_transfers.write().await;
+        transfers.retain(|t| t.is_sender || t.peer_id != transfer.peer_id);
+
+        let incoming = transfers.iter().filter(|t| !t.is_sender).count();
+        if incoming >= MAX_INCOMING_NEARBY_OFFERS {
+            return false;
+        }
+        transfers.push(transfer);
+        true
+    }
+
+    /// Remove and return a nearby transfer, checking it belongs to `peer_id`.
+    pub async fn take_nearby_transfer(
+        &self,
+        transfer_id: &str,
+        peer_id: &str,
+    ) -> Option<NearbyTransfer> {
+        let mut transfers = self.nearby_transfers.write().await;
+        let index = transfers
+            .iter()
+            .position(|t| t.transfer_id == transfer_id && t.peer_id == peer_id)?;
+        Some(transfers.remove(index))
+    }
+
+    /// Drop nearby transfers older than the pairing timeout and return their
+    /// `(transfer_id, peer_id)` so the caller can notify both sides.
+    pub async fn expire_nearby_transfers(&self) -> Vec<(String, String)> {
+        let timeout_secs = self.settings.read().await.pairing_timeout_secs;
+        let mut transfers = self.nearby_transfers.write().await;
+
+        let expired = transfers
+            .iter()
+            .filter(|t| t.is_expired(timeout_secs))
+            .map(|t| (t.transfer_id.clone(), t.peer_id.clone()))
+            .collect();
+        transfers.retain(|t| !t.is_expired(timeout_secs));
+        expired
+    }
+
+    /// Store a clipboard pull waiting for an answer.
+    ///
+    /// A newer incoming pull from the same peer replaces the older one. Returns
+    /// `false` if too many incoming pulls are already waiting for approval.
+    pub async fn add_clipboard_pull(&self, pull: ClipboardPull) -> bool {
+        let mut pulls = self.clipboard_pulls.write().await;
+        if pull.incoming {
+            pulls.retain(|p| !p.incoming || p.peer_id != pull.peer_id);
+            if pulls.iter().filter(|p| p.incoming).count() >= MAX_INCOMING_CLIPBOARD_PULLS {
+                return false;
+            }
+        }
+        pulls.push(pull);
+        true
+    }
+
+    /// Remove and return a clipboard pull, checking it belongs to `peer_id` if given.
+    pub async fn take_clipboard_pull(
+        &self,
+        request_id: &str,
+        peer_id: Option<&str>,
+        incoming: bool,
+    ) -> Option<ClipboardPull> {
+        let mut pulls = self.clipboard_pulls.write().await;
+        let index = pulls.iter().position(|p| {
+            p.request_id == request_id
+                && p.incoming == incoming
+                && match peer_id {
+                    Some(peer_id) => p.peer_id == peer_id,
+                    None => true,
+                }
+        })?;
+        Some(pulls.remove(index))
+    }
+
+    /// Drop clipboard pulls that went unanswered for too long and return them.
+    pub async fn expire_clipboard_pulls(&self) -> Vec<ClipboardPull> {
+        let now = Utc::now();
+        let mut pulls = self.clipboard_pulls.write().await;
+        let (expired, pending) = pulls.drain(..).partition(|p| {
+            now.signed_duration_since(p.created_at).num_seconds() > CLIPBOARD_PULL_TIMEOUT_SECS
+        });
+        *pulls = pending;
+        expired
+    }
+
+    /// Transition active pairing sessions older than the configured timeout to
+    /// `PairingState::Expired` and return them so the caller can notify the peer.
+    ///
+    /// Sessions that are already terminal are dropped once they are older than
+    /// twice the timeout, giving the UI a window to observe the final state.
+    pub async fn expire_pairing_sessions(&self) -> Vec<PairingSession> {
+        let timeout_secs = self.settings.read().await.pairing_timeout_secs;
+        let mut sessions = self.pairing_sessions.write().await;
+
+        let mut expired = Vec::new();
+        for session in sessions.iter_mut() {
+            if session.is_active() && session.is_expired(timeout_secs) {
+                session.state = PairingState::Expired;
+                expired.push(session.clone());
+            }
+        }
+
+        sessions.retain(|s| s.is_active() || !s.is_expired(timeout_secs.saturating_mul(2)));
+
+        // Drop SPAKE2 state for sessions that are no longer waiting on a reply
+        self.pake_states.write().await.retain(|session_id, _| {
+            sessions
+                .iter()
+                .any(|s| &s.session_id == session_id && s.is_active())
+        });
+        drop(sessions);
+
+        // Invites and armed passphrases share the pairing timeout; unused ones are dropped
+        self.pairing_invites
+            .write()
+            .await
+            .retain(|i| !i.is_expired(timeout_secs));
+        let mut listener = self.passphrase_listener.write().await;
+        if listener
+            .as_ref()
+            .is_some_and(|l| l.is_expired(timeout_secs))
+        {
+            *listener = None;
+        }
+
+        expired
+    }
+
+    /// Find and consume the pairing invite whose secret produced `proof`.
+    /// Returns `None` if no live invite matches.
+    pub async fn take_pairing_invite(
+        &self,
+        session_id: &str,
+        initiator_key: &[u8],
+        proof: &[u8],
+    ) -> Option<PairingInvite> {
+        let timeout_secs = self.settings.read().await.pairing_timeout_secs;
+        let mut invites = self.pairing_invites.write().await;
+        let idx = invites.iter().position(|i| {
+            !i.is_expired(timeout_secs)
+                && verify_invite_request_proof(i.secret(), session_id, initiator_key, proof)
+        })?;
+        Some(invites.remove(idx))
+    }
+
+    pub async fn is_peer_paired(&self, peer_id: &str) -> bool {
+        let peers = self.paired_peers.read().await;
+        peers.iter().any(|p| p.peer_id == peer_id)
+    }
+
+    /// Sync policy of a paired peer (`None` if not paired).
+    pub async fn peer_sync_policy(&self, peer_id: &str) -> Option<SyncPolicy> {
+        let peers = self.paired_peers.read().await;
+        peers
+            .iter()
+            .find(|p| p.peer_id == peer_id)
+            .map(|p| p.sync_policy)
+    }
+
+    /// Check the identity key `peer_id` presented against the one recorded for it.
+    ///
+    /// Records the key if none is known yet (pairings that didn't exchange one),
+    /// except for group-derived pairings: their key must come from the group, so
+    /// one announced over the network can't be passed off as verifiable.
+    /// On a mismatch the recorded key is kept, `verified` is cleared and the
+    /// peer is returned so the caller can warn the user.
+    pub async fn check_peer_identity_key(
+        &self,
+        peer_id: &str,
+        public_key: &[u8],
+    ) -> Result<Option<PairedPeer>> {
+        let (changed, mismatch) = {
+            let mut peers = self.paired_peers.write().await;
+            match peers.iter_mut().find(|p| p.peer_id == peer_id) {
+                None => return Ok(None),
+                Some(peer) => match peer.public_key {
+                    None if peer.group_id.is_some() => (false, None),
+                    None => {
+                        peer.public_key = Some(public_key.to_vec());
+                        (true, None)
+                    }
+                    Some(ref recorded) if recorded.as_slice() == public_key => (false, None),
+                    Some(_) => {
+                        let changed = peer.verified;
+                        peer.verified = false;
+                        (changed, Some(peer.clone()))
+                    }
+                },
+            }
+        };
+        if changed {
+            self.flush_paired_peers().await?;
+        }
+        Ok(mismatch)
+    }
+
+    // =========================================================================
+    // Clipboard Encryption
+    // =========================================================================
+
+    /// Encrypt `content` for every paired peer we send to (see `SyncPolicy::send`).
+    ///
+    /// Members of our pairing group share one message encrypted with the group key;
+    /// every other paired peer gets a message encrypted with its pairwise secret.
+    /// Any member can read group messages, so if we don't send to one of them, all
+    /// members get pairwise messages instead.
+    /// All of them carry `hlc`, the HLC of the local copy, the `selection` it
+    /// was copied from and the `history` retention its receivers must honor.
+    pub async fn prepare_clipboard_messages(
+        &self,
+        identity: &DeviceIdentity,
+        content: &str,
+        content_hash: &str,
+        hlc: HlcTimestamp,
+        selection: ClipboardSelection,
+        history: HistoryRetention,
+    ) -> Vec<OutgoingClipboard> {
+        let new_message = |key: &[u8], group_id: Option<String>| {
+            self.seal_clipboard_message(
+                identity,
+                key,
+                group_id,
+                content,
+                content_hash,
+                hlc,
+                None,
+                selection,
+                history,
+            )
+        };
+
+        let group = self.pairing_group.read().await.clone();
+        let paired_peers = self.paired_peers.read().await;
+        let mut outgoing = Vec::new();
+
+        let mut group_recipients = Vec::new();
+        if let Some(ref group) = group {
+            let opted_out = paired_peers
+                .iter()
+                .any(|p| group.is_member(&p.peer_id) && !p.sync_policy.send);
+            if opted_out {
+                debug!("Not sending to every group member, encrypting pairwise");
+            } else {
+                group_recipients = paired_peers
+                    .iter()
+                    .filter(|p| group.is_member(&p.peer_id) && p.sync_policy.send)
+                    .map(|p| p.peer_id.clone())
+                    .collect();
+            }
+            if !group_recipients.is_empty() {
+                match new_message(&group.group_key, Some(group.group_id.clone())) {
+                    Ok(message) => outgoing.push(OutgoingClipboard {
+                        message,
+                        recipients: group_recipients.clone(),
+                    }),
+                    Err(e) => {
+                        // Fall back to pairwise encryption for the members
+                        error!("Failed to encrypt clipboard for group: {}", e);
+                        group_recipients.clear();
+                    }
+                }
+            }
+        }
+
+        for peer in paired_peers
+            .iter()
+            .filter(|p| p.sync_policy.send && !group_recipients.contains(&p.peer_id))
+        {
+            match new_message(&peer.shared_secret, None) {
+                Ok(message) => outgoing.push(OutgoingClipboard {
+                    message,
+                    recipients: vec![peer.peer_id.clone()],
+                }),
+                Err(e) => {
+                    error!(
+                        "Failed to encrypt clipboard for peer {}: {}",
+                        peer.peer_id, e
+                    );
+                }
+            }
+        }
+
+        outgoing
+    }
+
+    /// Encrypt `content` for `peer_id` alone, with the pairwise secret (targeted send).
+    /// With `ttl_secs`, the item self-destructs on the receiver.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn prepare_clipboard_message_for_peer(
+        &self,
+        identity: &DeviceIdentity,
+        peer_id: &str,
+        content: &str,
+        content_hash: &str,
+        hlc: HlcTimestamp,
+        ttl_secs: Option<u64>,
+        history: HistoryRetention,
+    ) -> Result<ClipboardMessage> {
+        let secret = self.peer_shared_secret(peer_id).await?;
+        self.seal_clipboard_message(
+            identity,
+            &secret,
+            None,
+            content,
+            content_hash,
+            hlc,
+            ttl_secs,
+            ClipboardSelection::Clipboard,
+            history,
+        )
+    }
+
+    /// Our latest history entry sealed for `peer_id` (answer to a clipboard pull),
+    /// or `None` if there is nothing to send.
+    ///
+    /// Items sent to one other device with `send_to_peer` are skipped.
+    pub async fn latest_clipboard_message_for_peer(
+        &self,
+        peer_id: &str,
+    ) -> Result<Option<ClipboardMessage>> {
+        let latest = {
+            let history = self.clipboard_history.read().await;
+            history
+                .iter()
+                .find(|e| {
+                    e.target_peer_id
+                        .as_ref()
+                        .filter(|t| *t != peer_id)
+                        .is_none()
+                })
+                .cloned()
+        };
+        let entry = match latest {
+            Some(entry) => entry,
+            None => return Ok(None),
+        };
+
+        let device_identity = self.device_identity.read().await;
+        let identity = device_identity
+            .as_ref()
+            .ok_or(DecentPasteError::NotInitialized)?;
+        let (hlc, _) = entry.order_key();
+        // An auto-expiring entry expires on the receiver when it does here
+        let history = match entry.expires_at {
+            Some(at) => HistoryRetention::Expire {
+                secs: (at - Utc::now()).num_seconds().max(1) as u64,
+            },
+            None => HistoryRetention::Keep,
+        };
+        self.prepare_clipboard_message_for_peer(
+            identity,
+            peer_id,
+            &entry.content,
+            &entry.content_hash,
+            hlc,
+            None,
+            history,
+        )
+        .await
+        .map(Some)
+    }
+
+    /// Build a clipboard message with `content` sealed under `key`.
+    #[allow(clippy::too_many_arguments)]
+    fn seal_clipboard_message(
+        &self,
+        identity: &DeviceIdentity,
+        key: &[u8],
+        group_id: Option<String>,
+        content: &str,
+        content_hash: &str,
+        hlc: HlcTimestamp,
+        ttl_secs: Option<u64>,
+        selection: ClipboardSelection,
+        history: HistoryRetention,
+    ) -> Result<ClipboardMessage> {
+        let mut sealed = SealedClipboard {
+            version: SEALED_CLIPBOARD_VERSION,
+            message_id: uuid::Uuid::new_v4().to_string(),
+            timestamp: Utc::now(),
+            hlc,
+            origin_device_id: identity.device_id.clone(),
+            content: content.to_string(),
+            ttl_secs,
+            selection,
+            history,
+        };
+        sealed.version = sealed.required_version();
+        // Queued copies go away with the item: self-destructed or expired from history
+        let lifetime_secs = match history {
+            HistoryRetention::Expire { secs } => Some(ttl_secs.map_or(secs, |ttl| ttl.min(secs))),
+            HistoryRetention::Keep | HistoryRetention::Skip => ttl_secs,
+        };
+        let encrypted_content = encrypt_content(&serde_json::to_vec(&sealed)?, key)?;
+        Ok(ClipboardMessage {
+            id: sealed.message_id,
+            content_hash: content_hash.to_string(),
+            encrypted_content,
+            timestamp: sealed.timestamp,
+            hlc,
+            origin_device_id: sealed.origin_device_id,
+            origin_device_name: identity.device_name.clone(),
+            group_id,
+            relay: None,
+            expires_at: lifetime_secs.map(|secs| sealed.timestamp + Duration::seconds(secs as i64)),
+        })
+    }
+
+    /// Decrypt a clipboard message, verify its content hash and check it isn't a replay.
+    /// Returns the sealed payload (content, self-destruct TTL and history retention).
+    ///
+    /// Group messages are decrypted with the group key. Otherwise the pairwise secret
+    /// of `from_peer` is used when the sender is known, or every paired peer's secret
+    /// is tried. Messages older than `max_age_secs` (or too far in the future) and
+    /// message IDs already seen from the sender are rejected and counted in
+    /// `replay_counters`.
+    pub async fn decrypt_clipboard_message(
+        &self,
+        msg: &ClipboardMessage,
+        from_peer: Option<&str>,
+        max_age_secs: i64,
+    ) -> Option<SealedClipboard> {
+        let sealed = self.open_clipboard_message(msg, from_peer).await?;
+
+        if sealed.message_id != msg.id
+            || sealed.timestamp != msg.timestamp
+            || sealed.hlc != msg.hlc
+            || sealed.origin_device_id != msg.origin_device_id
+            || hash_content(&sealed.content) != msg.content_hash
+        {
+            warn!(
+                "Clipboard message {} doesn't match its sealed fields",
+                msg.id
+            );
+            self.replay_counters.record(ReplayRejection::Tampered);
+            return None;
+        }
+
+        let now = Utc::now();
+        let checked = match check_timestamp(sealed.timestamp, now, max_age_secs) {
+            Ok(()) => {
+                let sender = from_peer.unwrap_or(&msg.origin_device_id).to_string();
+                let mut windows = self.replay_windows.write().await;
+                windows.entry(sender).or_default().check(
+                    &sealed.message_id,
+                    sealed.timestamp,
+                    now,
+                    MAX_CLIPBOARD_MESSAGE_AGE_SECS,
+                )
+            }
+            Err(rejection) => Err(rejection),
+        };
+        if let Err(rejection) = checked {
+            warn!(
+                "Rejected clipboard message {} from {}: {:?}",
+                msg.id, msg.origin_device_name, rejection
+            );
+            self.replay_counters.record(rejection);
+            return None;
+        }
+
+        // Local copies made after this must order after it
+        self.hlc.observe(msg.hlc);
+        Some(sealed)
+    }
+
+    /// Decrypt `msg` with the matching key and parse the sealed payload.
+    async fn open_clipboard_message(
+        &self,
+        msg: &ClipboardMessage,
+        from_peer: Option<&str>,
+    ) -> Option<SealedClipboard> {
+        let verify = |decrypted: Vec<u8>| match SealedClipboard::from_bytes(&decrypted) {
+            Ok(sealed) => Some(sealed),
+            Err(e) => {
+                warn!("Can't read clipboard message {}: {}", msg.id, e);
+                None
+            }
+        };
+
+        if let Some(ref group_id) = msg.group_id {
+            let group = self.pairing_group.read().await;
+            let group = group.as_ref().filter(|g| &g.group_id == group_id)?;
+            if from_peer.is_some_and(|peer_id| !group.is_member(peer_id)) {
+                return None;
+            }
+            return decrypt_content(&msg.encrypted_content, &group.group_key)
+                .ok()
+                .and_then(verify);
+        }
+
+        let paired_peers = self.paired_peers.read().await;
+        paired_peers
+            .iter()
+            .filter(|p| match from_peer {
+                Some(peer_id) => p.peer_id == peer_id,
+                None => true,
+            })
+            .find_map(|p| {
+                decrypt_content(&msg.encrypted_content, &p.shared_secret)
+                    .ok()
+                    .and_then(verify)
+            })
+    }
+
+    /// Shared secret of a paired peer.
+    async fn peer_shared_secret(&self, peer_id: &str) -> Result<Vec<u8>> {
+        let peers = self.paired_peers.read().await;
+        peers
+            .iter()
+            .find(|p| p.peer_id == peer_id)
+            .map(|p| p.shared_secret.clone())
+            .ok_or_else(|| DecentPasteError::PeerNotFound(peer_id.to_string()))
+    }
+
+    // =========================================================================
+    // Pairing Groups
+    // =========================================================================
+
+    /// Encrypt `payload` with the secret shared with `peer_id` and send it.
+    pub async fn send_group_payload(&self, peer_id: &str, payload: &GroupPayload) -> Result<()> {
+        let secret = self.peer_shared_secret(peer_id).await?;
+        let encrypted_payload = encrypt_content(&serde_json::to_vec(payload)?, &secret)?;
+
+        let tx = self.network_command_tx.read().await;
+        let tx = tx.as_ref().ok_or(DecentPasteError::NotInitialized)?;
+        tx.send(NetworkCommand::SendGroupMessage {
+            peer_id: peer_id.to_string(),
+            encrypted_payload,
+        })
+        .await
+        .map_err(|_| DecentPasteError::ChannelSend)
+    }
+
+    /// Decrypt a group payload from `peer_id`.
+    /// Only succeeds for peers we are paired with, which authenticates the sender.
+    pub async fn open_group_payload(
+        &self,
+        peer_id: &str,
+        encrypted_payload: &[u8],
+    ) -> Result<GroupPayload> {
+        let secret = self.peer_shared_secret(peer_id).await?;
+        let decrypted = decrypt_content(encrypted_payload, &secret)?;
+        Ok(serde_json::from_slice(&decrypted)?)
+    }
+
+    /// Send our current group state to every other member.
+    pub async fn broadcast_group_state(&self, local_peer_id: &str) {
+        let group = self.pairing_group.read().await.clone();
+        if let Some(group) = group {
+            let payload = GroupPayload::State(group.clone());
+            for member in group.members.iter().filter(|m| m.peer_id != local_peer_id) {
+                if let Err(e) = self.send_group_payload(&member.peer_id, &payload).await {
+                    warn!("Failed to send group state to {}: {}", member.peer_id, e);
+                }
+            }
+        }
+    }
+
+    /// Adopt `group` and reconcile pairings with its members.
+    ///
+    /// Members we aren't paired with get a pairing derived from the group key,
+    /// existing group-derived pairings are re-keyed, and those of members no
+    /// longer in the group are removed. Pairings from a pairing ceremony are kept.
+    pub async fn apply_pairing_group(
+        &self,
+        group: PairingGroup,
+        local_peer_id: &str,
+    ) -> Result<()> {
+        {
+            let mut peers = self.paired_peers.write().await;
+            peers.retain(|p| {
+                p.group_id.as_ref() != Some(&group.group_id) || group.is_member(&p.peer_id)
+            });
+
+            let local_key = group
+                .members
+                .iter()
+                .find(|m| m.peer_id == local_peer_id)
+                .and_then(|m| m.public_key.clone())
+                .unwrap_or_default();
+            for member in group.members.iter().filter(|m| m.peer_id != local_peer_id) {
+                let member_key = member.public_key.as_deref().unwrap_or_default();
+                let secret = derive_group_pair_secret(
+                    &group.group_key,
+                    (local_peer_id, &local_key),
+                    (&member.peer_id, member_key),
+                );
+                match peers.iter_mut().find(|p| p.peer_id == member.peer_id) {
+                    Some(peer) => {
+                        if peer.group_id.as_ref() == Some(&group.group_id) {
+                            peer.shared_secret = secret;
+                        }
+                    }
+                    None => peers.push(PairedPeer {
+                        peer_id: member.peer_id.clone(),
+                        device_name: member.device_name.clone(),
+                        shared_secret: secret,
+                        paired_at: Utc::now(),
+                        last_seen: None,
+                        last_known_addresses: member.addresses.clone(),
+                        group_id: Some(group.group_id.clone()),
+                        // Bound into the secret above; later changes arrive as signed rotations
+                        public_key: member.public_key.clone(),
+                        verified: false,
+                        sync_policy: SyncPolicy::default(),
+                        expiry: None,
+                        applied_rotation: None,
+                    }),
+                }
+            }
+        }
+
+        *self.pairing_group.write().await = Some(group);
+        self.flush_paired_peers().await?;
+        self.flush_pairing_group().await
+    }
+
+    /// Forget our pairing group and every pairing derived from it.
+    /// Returns the group we were in, if any.
+    pub async fn clear_pairing_group(&self) -> Result<Option<PairingGroup>> {
+        let group = self.pairing_group.write().await.take();
+        if let Some(ref group) = group {
+            self.paired_peers
+                .write()
+                .await
+                .retain(|p| p.group_id.as_ref() != Some(&group.group_id));
+            self.flush_paired_peers().await?;
+        }
+        self.flush_pairing_group().await?;
+        Ok(group)
+    }
+
+    // =========================================================================
+    // Unpair Notifications
+    // =========================================================================
+
+    /// Remove our pairing with `peer_id`, wipe its shared secret and tell the peer.
+    ///
+    /// The unpair proof is computed before the secret is wiped. The notification
+    /// stays queued until the peer acknowledges it (it is resent whenever the peer
+    /// is ready). Returns the removed pairing, if there was one.
+    pub async fn unpair_peer(&self, peer_id: &str) -> Result<Option<PairedPeer>> {
+        let removed = {
+            let mut peers = self.paired_peers.write().await;
+            let index = peers.iter().position(|p| p.peer_id == peer_id);
+            index.map(|i| peers.remove(i))
+        };
+        let mut removed = match removed {
+            Some(peer) => peer,
+            None => return Ok(None),
+        };
+        // Flush-on-write: persist immediately to prevent data loss
+        self.flush_paired_peers().await?;
+        self.remove_message_buffer(peer_id).await;
+        self.remove_pending_rotation(peer_id).await?;
+
+        let local_peer_id = self.local_peer_id.read().await.clone();
+        if let Some(local_peer_id) = local_peer_id {
+            let proof = unpair_proof(&removed.shared_secret, &local_peer_id, peer_id);
+            self.queue_unpair(peer_id, proof.clone()).await?;
+            let tx = self.network_command_tx.read().await;
+            if let Some(tx) = tx.as_ref() {
+                let _ = tx
+                    .send(NetworkCommand::SendUnpair {
+                        peer_id: peer_id.to_string(),
+                        proof,
+                    })
+                    .await;
+            }
+        }
+        removed.shared_secret.zeroize();
+        Ok(Some(removed))
+    }
+
+    /// Queue an `Unpair` notification for `peer_id` (replacing any older one).
+    pub async fn queue_unpair(&self, peer_id: &str, proof: Vec<u8>) -> Result<()> {
+        {
+            let mut pending = self.pending_unpairs.write().await;
+            pending.retain(|u| u.peer_id != peer_id);
+            pending.push(PendingUnpair {
+                peer_id: peer_id.to_string(),
+                proof,
+                created_at: Utc::now(),
+            });
+        }
+        self.flush_pending_unpairs().await
+    }
+
+    /// Proof of the queued `Unpair` notification for `peer_id`, if any.
+    pub async fn pending_unpair_proof(&self, peer_id: &str) -> Option<Vec<u8>> {
+        let pending = self.pending_unpairs.read().await;
+        pending
+            .iter()
+            .find(|u| u.peer_id == peer_id)
+            .map(|u| u.proof.clone())
+    }
+
+    /// Drop the queued `Unpair` notification for `peer_id`
+    /// (delivered, or superseded by pairing with the peer again).
+    pub async fn remove_pending_unpair(&self, peer_id: &str) -> Result<()> {
+        let removed = {
+            let mut pending = self.pending_unpairs.write().await;
+            let before = pending.len();
+            pending.retain(|u| u.peer_id != peer_id);
+            pending.len() != before
+        };
+        if removed {
+            self.flush_pending_unpairs().await?;
+        }
+        Ok(())
+    }
+
+    // =========================================================================
+    // Delivery Acknowledgements
+    // =========================================================================
+
+    /// Tell `peer_id` (the sender of `message`) what we did with it.
+    pub async fn send_delivery_report(
+        &self,
+        peer_id: &str,
+        message: &ClipboardMessage,
+        status: DeliveryStatus,
+    ) -> Result<()> {
+        let report = DeliveryReport {
+            message_id: message.id.clone(),
+            content_hash: message.content_hash.clone(),
+            status,
+        };
+        let secret = self.peer_shared_secret(peer_id).await?;
+        let encrypted_payload = encrypt_content(&serde_json::to_vec(&report)?, &secret)?;
+
+        let tx = self.network_command_tx.read().await;
+        let tx = tx.as_ref().ok_or(DecentPasteError::NotInitialized)?;
+        tx.send(NetworkCommand::SendDeliveryReport {
+            peer_id: peer_id.to_string(),
+            encrypted_payload,
+        })
+        .await
+        .map_err(|_| DecentPasteError::ChannelSend)
+    }
+
+    /// Decrypt a delivery report from `peer_id`, which authenticates the sender.
+    pub async fn open_delivery_report(
+        &self,
+        peer_id: &str,
+        encrypted_payload: &[u8],
+    ) -> Result<DeliveryReport> {
+        let secret = self.peer_shared_secret(peer_id).await?;
+        let decrypted = decrypt_content(encrypted_payload, &secret)?;
+        Ok(serde_json::from_slice(&decrypted)?)
+    }
+
+    /// Record `status` for `peer_id` on the entry we sent with `content_hash`.
+    /// Returns the entry ID if the status changed.
+    pub async fn update_delivery_status(
+        &self,
+        content_hash: &str,
+        peer_id: &str,
+        status: DeliveryStatus,
+    ) -> Option<String> {
+        let entry_id = {
+            let mut history = self.clipboard_history.write().await;
+            let entry = history
+                .iter_mut()
+                .find(|e| e.content_hash == content_hash && e.delivery.contains_key(peer_id))?;
+            let current = entry.delivery.get_mut(peer_id)?;
+            if !status.supersedes(*current) {
+                return None;
+            }
+            *current = status;
+            entry.id.clone()
+        };
+
+        if let Err(e) = self.flush_clipboard_history().await {
+            warn!("Failed to flush clipboard history: {}", e);
+        }
+        Some(entry_id)
+    }
+
+    /// Mark deliveries pending for longer than `DELIVERY_ACK_TIMEOUT_SECS` whose
+    /// message is no longer queued for the recipient (expired, trimmed, or never
+    /// queued) as failed. Returns `(entry_id, peer_id)` for each one.
+    pub async fn expire_pending_deliveries(&self) -> Vec<(String, String)> {
+        let now = Utc::now();
+        let failed = {
+            let mut history = self.clipboard_history.write().await;
+            let buffers = self.message_buffers.read().await;
+            let mut failed = Vec::new();
+            for entry in history.iter_mut().filter(|e| {
+                now.signed_duration_since(e.timestamp).num_seconds() > DELIVERY_ACK_TIMEOUT_SECS
+            }) {
+                for (peer_id, status) in entry.delivery.iter_mut() {
+                    let queued = buffers.get(peer_id).is_some_and(|buffer| {
+                        buffer.iter().any(|m| m.content_hash == entry.content_hash)
+                    });
+                    if *status == DeliveryStatus::Pending && !queued {
+                        *status = DeliveryStatus::Failed;
+                        failed.push((entry.id.clone(), peer_id.clone()));
+                    }
+                }
+            }
+            failed
+        };
+
+        if !failed.is_empty() {
+            if let Err(e) = self.flush_clipboard_history().await {
+                warn!("Failed to flush clipboard history: {}", e);
+            }
+        }
+        failed
+    }
+
+    // =========================================================================
+    // History Reconciliation
+    // =========================================================================
+
+    /// Whether we reconcile history with `peer_id`: history sync is enabled and the
+    /// peer is a permanent pairing that both sends and receives.
+    pub async fn history_sync_allowed(&self, peer_id: &str) -> bool {
+        if !self.settings.read().await.history_sync_enabled {
+            return false;
+        }
+        let peers = self.paired_peers.read().await;
+        peers.iter().any(|p| {
+            p.peer_id == peer_id
+                && p.expiry.is_none()
+                && p.sync_policy.send
+                && p.sync_policy.receive
+        })
+    }
+
+    /// Encrypt `payload` with the secret shared with `peer_id` and send it.
+    pub async fn send_history_payload(
+        &self,
+        peer_id: &str,
+        payload: &HistoryPayload,
+    ) -> Result<()> {
+        let secret = self.peer_shared_secret(peer_id).await?;
+        let encrypted_payload = encrypt_content(&serde_json::to_vec(payload)?, &secret)?;
+
+        let tx = self.network_command_tx.read().await;
+        let tx = tx.as_ref().ok_or(DecentPasteError::NotInitialized)?;
+        tx.send(NetworkCommand::SendHistorySync {
+            peer_id: peer_id.to_string(),
+            encrypted_payload,
+        })
+        .await
+        .map_err(|_| DecentPasteError::ChannelSend)
+    }
+
+    /// Decrypt a history payload from `peer_id`, which authenticates the sender.
+    pub async fn open_history_payload(
+        &self,
+        peer_id: &str,
+        encrypted_payload: &[u8],
+    ) -> Result<HistoryPayload> {
+        let secret = self.peer_shared_secret(peer_id).await?;
+        let decrypted = decrypt_content(encrypted_payload, &secret)?;
+        Ok(serde_json::from_slice(&decrypted)?)
+    }
+
+    /// Our newest `limit` entries and all tombstones, as reconciliation items.
+    pub async fn history_items(&self, limit: usize) -> Vec<HistoryItemRef> {
+        let history = self.clipboard_history.read().await;
+        let tombstones = self.history_tombstones.read().await;
+        reconcile::history_items(&history, &tombstones, limit)
+    }
+
+    /// Start reconciling history with `peer_id` by sending our bucket digests.
+    pub async fn start_history_sync(&self, peer_id: &str) -> Result<()> {
+        let limit = self.settings.read().await.clipboard_history_limit;
+        let items = self.history_items(limit).await;
+        let payload = HistoryPayload::Digest {
+            limit,
+            buckets: reconcile::bucket_digests(&items),
+        };
+        self.send_history_payload(peer_id, &payload).await
+    }
+
+    /// Send the history entries with the given content hashes (and `tombstones`)
+    /// to `peer_id`, split into batches of `MAX_HISTORY_BATCH_BYTES`.
+    pub async fn send_history_entries(
+        &self,
+        peer_id: &str,
+        hashes: &[String],
+        tombstones: Vec<HistoryTombstone>,
+    ) -> Result<()> {
+        let entries: Vec<ClipboardEntry> = {
+            let history = self.clipboard_history.read().await;
+            history
+                .iter()
+                .filter(|e| hashes.contains(&e.content_hash))
+                .cloned()
+                .collect()
+        };
+
+        let mut batches: Vec<Vec<ClipboardEntry>> = Vec::new();
+        let mut batch_bytes = 0;
+        for entry in entries {
+            match batches.last_mut() {
+                Some(batch) if batch_bytes + entry.content.len() <= MAX_HISTORY_BATCH_BYTES => {
+                    batch_bytes += entry.content.len();
+                    batch.push(entry);
+                }
+                _ => {
+                    batch_bytes = entry.content.len();
+                    batches.push(vec![entry]);
+                }
+            }
+        }
+
+        // Tombstones ride along with the first batch
+        let mut tombstones = Some(tombstones);
+        if batches.is_empty() {
+            batches.push(Vec::new());
+        }
+        for entries in batches {
+            let payload = HistoryPayload::Entries {
+                entries,
+                tombstones: tombstones.take().unwrap_or_default(),
+            };
+            self.send_history_payload(peer_id, &payload).await?;
+        }
+        Ok(())
+    }
+
+    /// Merge entries and tombstones received by history reconciliation.
+    ///
+    /// Entries only go into the history (by HLC, trimmed to the history limit),
+    /// never to the system clipboard. Returns `true` if the history changed.
+    pub async fn merge_history(
+        &self,
+        entries: Vec<ClipboardEntry>,
+        tombstones: &[HistoryTombstone],
+    ) -> bool {
+        let changed = {
+            let mut all_tombstones = self.history_tombstones.write().await;
+            reconcile::merge_tombstones(&mut all_tombstones, tombstones);
+
+            let mut history = self.clipboard_history.write().await;
+            let before = history.len();
+            history.retain(|e| !reconcile::is_deleted(e, &all_tombstones));
+            let mut changed = history.len() != before;
+
+            for entry in entries {
+                if reconcile::is_deleted(&entry, &all_tombstones) {
+                    continue;
+                }
+                if !entry.hlc.is_unset() {
+                    self.hlc.observe(entry.hlc);
+                }
+
+                // Keep the newer of two entries with the same content
+                if let Some(idx) = history
+                    .iter()
+                    .position(|e| e.content_hash == entry.content_hash)
+                {
+                    if history[idx].order_key() >= entry.order_key() {
+                        continue;
+                    }
+                    history.remove(idx);
+                }
+                let insert_pos = history
+                    .iter()
+                    .position(|e| e.order_key() < entry.order_key())
+                    .unwrap_or(history.len());
+                history.insert(insert_pos, entry);
+                changed = true;
+            }
+
+            let max_size = self.settings.read().await.clipboard_history_limit;
+            history.truncate(max_size);
+            changed
+        };
+
+        if let Err(e) = self.flush_history_tombstones().await {
+            warn!("Failed to flush history tombstones: {}", e);
+        }
+        if changed {
+            if let Err(e) = self.flush_clipboard_history().await {
+                warn!("Failed to flush clipboard history: {}", e);
+            }
+        }
+        changed
+    }
+
+    /// Record tombstones for deleted history entries, if history sync is enabled.
+    pub async fn record_history_deletions(&self, deleted: &[ClipboardEntry]) -> Result<()> {
+        if deleted.is_empty() || !self.settings.read().await.history_sync_enabled {
+            return Ok(());
+        }
+
+        let now = self.hlc.now();
+        let new_tombstones: Vec<HistoryTombstone> = deleted
+            .iter()
+            .map(|e| HistoryTombstone {
+                content_hash: e.content_hash.clone(),
+                deleted_at: now.max(e.order_key().0),
+            })
+            .collect();
+        {
+            let mut tombstones = self.history_tombstones.write().await;
+            reconcile::merge_tombstones(&mut tombstones, &new_tombstones);
+        }
+        self.flush_history_tombstones().await
+    }
+
+    // =========================================================================
+    // Store-and-forward Relaying
+    // =========================================================================
+
+    /// Give online relays a copy of a clipboard item for each of `recipients` that is
+    /// offline. Each copy is sealed with the recipient's pairwise secret, so relays
+    /// can't read it.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn relay_to_offline_peers(
+        &self,
+        identity: &DeviceIdentity,
+        recipients: &[String],
+        content: &str,
+        content_hash: &str,
+        hlc: HlcTimestamp,
+        ttl_secs: Option<u64>,
+        history: HistoryRetention,
+    ) {
+        if !self.settings.read().await.relay_enabled {
+            return;
+        }
+        let local_peer_id = match self.local_peer_id.read().await.clone() {
+            Some(peer_id) => peer_id,
+            None => return,
+        };
+
+        let offline: Vec<String> = {
+            let ready = self.ready_peers.read().await;
+            recipients
+                .iter()
+                .filter(|peer_id| !ready.contains(*peer_id))
+                .cloned()
+                .collect()
+        };
+        for recipient in offline {
+            if self.is_temporary_pairing(&recipient).await {
+                continue;
+            }
+            let sealed = self
+                .prepare_clipboard_message_for_peer(
+                    identity,
+                    &recipient,
+                    content,
+                    content_hash,
+                    hlc,
+                    ttl_secs,
+                    history,
+                )
+                .await;
+            let mut message = match sealed {
+                Ok(message) => message,
+                Err(e) => {
+                    warn!("Failed to seal relayed message for {}: {}", recipient, e);
+                    continue;
+                }
+            };
+            message.relay = Some(RelayInfo {
+                origin_peer_id: local_peer_id.clone(),
+                recipient_peer_id: recipient.clone(),
+                hops_left: MAX_RELAY_HOPS,
+            });
+            match self.send_to_relay(message, &[]).await {
+                Some(relay) => debug!("Handed message for {} to relay {}", recipient, relay),
+                None => debug!("No relay online for {}", recipient),
+            }
+        }
+    }
+
+    /// Pass a relayed message on to one online, permanently paired peer we may send
+    /// to, other than its origin, its recipient and `exclude`. Returns that relay.
+    ///
+    /// A single relay per hop keeps a message from fanning out across the whole
+    /// mesh; the origin still queues its own copy, so losing a relay only delays it.
+    pub async fn send_to_relay(
+        &self,
+        message: ClipboardMessage,
+        exclude: &[&str],
+    ) -> Option<String> {
+        let relay = match message.relay {
+            Some(ref relay) if relay.hops_left > 0 => relay.clone(),
+            _ => return None,
+        };
+
+        let relay_peer = {
+            let ready = self.ready_peers.read().await;
+            let peers = self.paired_peers.read().await;
+            peers
+                .iter()
+                .find(|p| {
+                    p.expiry.is_none()
+                        && p.sync_policy.send
+                        && ready.contains(&p.peer_id)
+                        && p.peer_id != relay.origin_peer_id
+                        && p.peer_id != relay.recipient_peer_id
+                        && !exclude.contains(&p.peer_id.as_str())
+                })
+                .map(|p| p.peer_id.clone())?
+        };
+
+        let tx = self.network_command_tx.read().await;
+        tx.as_ref()?
+            .send(NetworkCommand::SendRelayMessage {
+                peer_id: relay_peer.clone(),
+                message,
+            })
+            .await
+            .ok()?;
+        Some(relay_peer)
+    }
+
+    /// Hold a message relayed to us by `from_peer` until its recipient syncs with us.
+    ///
+    /// Only accepted with relaying enabled, from a permanent pairing, for a recipient
+    /// we are permanently paired with and may send to. Returns the message with one
+    /// hop used up, or `None` if it was refused or is already held.
+    pub async fn hold_relayed_message(
+        &self,
+        from_peer: &str,
+        mut message: ClipboardMessage,
+    ) -> Option<ClipboardMessage> {
+        if !self.settings.read().await.relay_enabled {
+            return None;
+        }
+        let relay = message.relay.as_mut()?;
+        relay.hops_left = relay.hops_left.checked_sub(1)?;
+        let recipient = relay.recipient_peer_id.clone();
+        let origin = relay.origin_peer_id.clone();
+
+        {
+            let peers = self.paired_peers.read().await;
+            let from_permanent = peers
+                .iter()
+                .any(|p| p.peer_id == from_peer && p.expiry.is_none());
+            let to_allowed = peers
+                .iter()
+                .any(|p| p.peer_id == recipient && p.expiry.is_none() && p.sync_policy.send);
+            if !from_permanent || !to_allowed {
+                return None;
+            }
+        }
+
+        {
+            let mut buffers = self.relay_buffers.write().await;
+            // The same message may reach us again (e.g. resent by its origin)
+            let held = buffers
+                .get(&recipient)
+                .is_some_and(|buffer| buffer.iter().any(|m| m.id == message.id));
+            if held {
+                return None;
+            }
+
+            // Each origin gets its own quota, so one device can't push out the others
+            let from_origin =
+                |m: &ClipboardMessage| m.relay.as_ref().is_some_and(|r| r.origin_peer_id == origin);
+            let mut held_from_origin: Vec<(DateTime<Utc>, String)> = buffers
+                .values()
+                .flatten()
+                .filter(|m| from_origin(m))
+                .map(|m| (m.timestamp, m.id.clone()))
+                .collect();
+            if held_from_origin.len() >= MAX_RELAYED_PER_ORIGIN {
+                held_from_origin.sort();
+                let excess = held_from_origin.len() + 1 - MAX_RELAYED_PER_ORIGIN;
+                let dropped: Vec<String> = held_from_origin
+                    .into_iter()
+                    .take(excess)
+                    .map(|(_, id)| id)
+                    .collect();
+                for buffer in buffers.values_mut() {
+                    buffer.retain(|m| !dropped.contains(&m.id));
+                }
+                buffers.retain(|_, buffer| !buffer.is_empty());
+                debug!(
+                    "Relay quota reached for {}, dropped {} oldest",
+                    origin, excess
+                );
+            }
+
+            buffers.entry(recipient).or_default().push(message.clone());
+        }
+
+        if let Err(e) = self.flush_relay_buffers().await {
+            warn!("Failed to flush relay buffers: {}", e);
+        }
+        Some(message)
+    }
 
     // =========================================================================
-    // Connection Management State
+    // Temporary Pairings
     // =========================================================================
-    /// Guard against concurrent reconnection attempts.
-    /// Only one ensure_connected() operation runs at a time.
-    pub reconnect_in_progress: AtomicBool,
 
-    /// Count of pending dial attempts during reconnection.
-    /// Decremented as connections succeed or fail.
-    pub pending_dials: AtomicUsize,
+    pub async fn is_temporary_pairing(&self, peer_id: &str) -> bool {
+        let peers = self.paired_peers.read().await;
+        peers
+            .iter()
+            .any(|p| p.peer_id == peer_id && p.expiry.is_some())
+    }
 
-    /// Notified when all pending dials complete.
-    /// Used by ensure_connected() to await completion.
-    pub dials_complete_notify: Arc<Notify>,
+    /// Count one clipboard message exchanged with each of `peer_ids` against the
+    /// message limit of their temporary pairing.
+    pub async fn count_pairing_messages(&self, peer_ids: &[String]) -> Result<()> {
+        let changed = {
+            let mut peers = self.paired_peers.write().await;
+            let mut changed = false;
+            for peer in peers.iter_mut().filter(|p| peer_ids.contains(&p.peer_id)) {
+                if let Some(PairingExpiry {
+                    messages_remaining: Some(ref mut remaining),
+                    ..
+                }) = peer.expiry
+                {
+                    *remaining = remaining.saturating_sub(1);
+                    changed = true;
+                }
+            }
+            changed
+        };
+        if changed {
+            self.flush_paired_peers().await?;
+        }
+        Ok(())
+    }
 
-    /// Per-recipient buffering: we store messages WE sent that THEY missed.
-    /// Key = peer_id of the recipient (who missed the message)
-    /// Value = messages we sent that they should receive on reconnection
+    /// Peer IDs of temporary pairings past their expiry or message limit.
+    pub async fn expired_pairings(&self) -> Vec<String> {
+        let now = Utc::now();
+        let peers = self.paired_peers.read().await;
+        peers
+            .iter()
+            .filter(|p| p.expiry.as_ref().is_some_and(|e| e.is_reached(now)))
+            .map(|p| p.peer_id.clone())
+            .collect()
+    }
+
+    // =========================================================================
+    // Identity Rotation
+    // =========================================================================
+
+    /// Build a rotation announcement for every paired peer and switch our side of
+    /// each pairing to the new identity key.
     ///
-    /// ALWAYS buffer for all paired peers, regardless of online status.
-    /// This handles the race condition where a peer goes offline mid-transmission.
-    /// Message buffers for offline peers.
-    /// Maps peer_id -> buffered messages (messages WE sent that THEY missed).
-    pub message_buffers: Arc<RwLock<HashMap<String, Vec<ClipboardMessage>>>>,
-}
+    /// Each announcement is encrypted with the secret the peer still holds, then
+    /// queued until the peer acks it. Pairwise secrets are re-derived from the new
+    /// key; group pairings keep theirs since it comes from the group key.
+    pub async fn queue_identity_rotation(
+        &self,
+        rotation: &IdentityRotation,
+        new_private_key: &[u8],
+    ) -> Result<usize> {
+        let mut queued = Vec::new();
+        {
+            let mut peers = self.paired_peers.write().await;
+            for peer in peers.iter_mut() {
+                let rekey = peer.group_id.is_none() && peer.public_key.is_some();
+                let payload = RotationPayload {
+                    rotation: rotation.clone(),
+                    rekey,
+                };
+                let encrypted_payload =
+                    encrypt_content(&serde_json::to_vec(&payload)?, &peer.shared_secret)?;
+                if rekey {
+                    if let Some(ref peer_public_key) = peer.public_key {
+                        peer.shared_secret =
+                            derive_shared_secret(new_private_key, peer_public_key)?;
+                    }
+                }
+                queued.push(PendingRotation {
+                    peer_id: peer.peer_id.clone(),
+                    old_peer_id: rotation.old_peer_id.clone(),
+                    encrypted_payload,
+                    created_at: Utc::now(),
+                });
+            }
+        }
+        let count = queued.len();
+        *self.pending_rotations.write().await = queued;
 
-impl AppState {
-    pub fn new() -> Self {
-        Self {
-            device_identity: Arc::new(RwLock::new(None)),
-            settings: Arc::new(RwLock::new(AppSettings::default())),
-            paired_peers: Arc::new(RwLock::new(Vec::new())),
-            discovered_peers: Arc::new(RwLock::new(Vec::new())),
-            clipboard_history: Arc::new(RwLock::new(Vec::new())),
-            network_status: Arc::new(RwLock::new(NetworkStatus::Disconnected)),
-            pairing_sessions: Arc::new(RwLock::new(Vec::new())),
-            network_command_tx: Arc::new(RwLock::new(None)),
-            pending_clipboard: Arc::new(RwLock::new(None)),
-            is_foreground: Arc::new(RwLock::new(true)), // Assume foreground at start
-            ready_peers: Arc::new(RwLock::new(HashSet::new())), // No peers ready initially
-            vault_status: Arc::new(RwLock::new(VaultStatus::NotSetup)), // Vault starts as not setup
-            vault_manager: Arc::new(RwLock::new(None)), // No vault manager until unlocked
+        // Buffered messages were encrypted with the old secrets
+        self.message_buffers.write().await.clear();
 
-            // Connection management
-            reconnect_in_progress: AtomicBool::new(false),
-            pending_dials: AtomicUsize::new(0),
-            dials_complete_notify: Arc::new(Notify::new()),
+        // One write, so the new secrets are never persisted without the queue
+        self.flush_all_to_vault().await?;
+        Ok(count)
+    }
 
-            // Sync message buffers (per-recipient)
-            message_buffers: Arc::new(RwLock::new(HashMap::new())),
+    /// Whether `encrypted_payload` is the rotation announcement we last applied
+    /// from `peer_id` (its ack may have been lost).
+    pub async fn is_rotation_applied(&self, peer_id: &str, encrypted_payload: &[u8]) -> bool {
+        let digest = announcement_digest(encrypted_payload);
+        let peers = self.paired_peers.read().await;
+        peers
+            .iter()
+            .any(|p| p.peer_id == peer_id && p.applied_rotation.as_ref() == Some(&digest))
+    }
+
+    /// The queued rotation announcement for `peer_id`, if any.
+    pub async fn pending_rotation(&self, peer_id: &str) -> Option<PendingRotation> {
+        let pending = self.pending_rotations.read().await;
+        pending.iter().find(|r| r.peer_id == peer_id).cloned()
+    }
+
+    /// Drop the queued rotation announcement for `peer_id` (acknowledged, or the
+    /// pairing was removed or replaced). Returns true if no announcements remain.
+    pub async fn remove_pending_rotation(&self, peer_id: &str) -> Result<bool> {
+        let (removed, remaining) = {
+            let mut pending = self.pending_rotations.write().await;
+            let before = pending.len();
+            pending.retain(|r| r.peer_id != peer_id);
+            (pending.len() != before, pending.len())
+        };
+        if removed {
+            self.flush_pending_rotations().await?;
         }
+        Ok(remaining == 0)
     }
 
-    pub async fn add_clipboard_entry(&self, entry: ClipboardEntry) {
-        let modified = {
-            let mut history = self.clipboard_history.write().await;
+    /// Apply a rotation announcement received from `peer_id`.
+    ///
+    /// The payload must decrypt with the secret of our pairing with `old_peer_id`,
+    /// carry a valid signature from the old key, and (if the PeerId changed) arrive
+    /// from the new PeerId. Returns the updated pairing.
+    pub async fn apply_identity_rotation(
+        &self,
+        peer_id: &str,
+        old_peer_id: &str,
+        encrypted_payload: &[u8],
+    ) -> Result<PairedPeer> {
+        let invalid = |msg: &str| DecentPasteError::Pairing(format!("Identity rotation: {}", msg));
 
-            // Check for existing entry with the same content hash (deduplication)
-            if let Some(idx) = history
-                .iter()
-                .position(|e| e.content_hash == entry.content_hash)
-            {
-                // Remove existing entry - it will be reinserted at the correct position
-                history.remove(idx);
-                debug!(
-                    "Updated existing clipboard entry (will reinsert): {}",
-                    &entry.content_hash[..8]
-                );
-            }
+        let private_key = {
+            let identity = self.device_identity.read().await;
+            identity
+                .as_ref()
+                .and_then(|id| id.private_key.clone())
+                .ok_or(DecentPasteError::NotInitialized)?
+        };
 
-            // Insert at the correct chronological position by timestamp.
-            // History is sorted newest-first, so find the first entry older than this one.
-            // This is important for sync: synced messages may have older timestamps
-            // and should appear in the correct position in history.
-            let insert_pos = history
-                .iter()
-                .position(|e| e.timestamp < entry.timestamp)
-                .unwrap_or(history.len());
+        let updated = {
+            let mut peers = self.paired_peers.write().await;
+            if peer_id != old_peer_id && peers.iter().any(|p| p.peer_id == peer_id) {
+                return Err(invalid("new peer ID is already paired"));
+            }
+            let peer = peers
+                .iter_mut()
+                .find(|p| p.peer_id == old_peer_id)
+                .ok_or_else(|| DecentPasteError::PeerNotFound(old_peer_id.to_string()))?;
 
-            history.insert(insert_pos, entry);
+            let decrypted = decrypt_content(encrypted_payload, &peer.shared_secret)?;
+            let payload: RotationPayload = serde_json::from_slice(&decrypted)?;
+            let rotation = payload.rotation;
+            rotation.verify()?;
+            if rotation.old_peer_id != old_peer_id || rotation.new_peer_id != peer_id {
+                return Err(invalid("peer IDs don't match the sender"));
+            }
+            if rotation.peer_id_changed() && peer.group_id.is_some() {
+                return Err(invalid("group members can't change peer ID"));
+            }
+            if let Some(ref recorded) = peer.public_key {
+                if *recorded != rotation.old_public_key {
+                    return Err(invalid("old identity key doesn't match our records"));
+                }
+            }
 
-            // Trim to max size from settings
-            let max_size = self.settings.read().await.clipboard_history_limit;
-            history.truncate(max_size);
-            true
+            if payload.rekey {
+                peer.shared_secret = derive_shared_secret(&private_key, &rotation.new_public_key)?;
+            }
+            peer.peer_id = rotation.new_peer_id.clone();
+            peer.public_key = Some(rotation.new_public_key.clone());
+            peer.applied_rotation = Some(announcement_digest(encrypted_payload));
+            peer.clone()
         };
 
-        // Flush-on-write: persist clipboard history immediately
-        if modified {
-            if let Err(e) = self.flush_clipboard_history().await {
-                warn!("Failed to flush clipboard history: {}", e);
-            }
+        // Anything buffered for the peer used the old secret or old peer ID
+        self.remove_message_buffer(old_peer_id).await;
+        if peer_id != old_peer_id {
+            self.ready_peers.write().await.remove(old_peer_id);
         }
-    }
 
-    pub async fn is_peer_paired(&self, peer_id: &str) -> bool {
-        let peers = self.paired_peers.read().await;
-        peers.iter().any(|p| p.peer_id == peer_id)
+        self.flush_paired_peers().await?;
+        Ok(updated)
     }
 
     /// Store a clipboard message in buffer for a specific peer.
     /// ALWAYS buffers, regardless of peer's online status (handles race conditions).
     /// Buffer is per-recipient: messages WE sent that THEY missed.
     pub async fn store_buffered_message(&self, peer_id: &str, message: ClipboardMessage) {
-        let mut buffers = self.message_buffers.write().await;
-        let buffer = buffers.entry(peer_id.to_string()).or_default();
-        buffer.push(message);
+        {
+            let mut buffers = self.message_buffers.write().await;
+            let buffer = buffers.entry(peer_id.to_string()).or_default();
+            buffer.push(message);
+            debug!(
+                "Buffered message for peer {} (buffer size: {})",
+                peer_id,
+                buffer.len()
+            );
+        }
+
+        // Truncate to the configured depth and TTL, then persist
+        self.prune_message_buffers().await;
+    }
 
-        // Truncate to max size (keep the latest messages only)
-        if buffer.len() > SYNC_MAX_BUFFER_SIZE {
-            buffer.drain(0..buffer.len() - SYNC_MAX_BUFFER_SIZE);
+    /// Drop buffered messages past the configured TTL and trim every buffer to the
+    /// configured depth (keeping the latest messages), then persist the buffers.
+    pub async fn prune_message_buffers(&self) {
+        let (max_size, ttl) = self.sync_buffer_limits().await;
+        let now = Utc::now();
+        {
+            let mut buffers = self.message_buffers.write().await;
+            for buffer in buffers.values_mut() {
+                buffer.retain(|msg| {
+                    now.signed_duration_since(msg.timestamp) < ttl
+                        && !msg.expires_at.is_some_and(|at| at <= now)
+                });
+                if buffer.len() > max_size {
+                    buffer.drain(0..buffer.len() - max_size);
+                }
+            }
+            buffers.retain(|_, buffer| !buffer.is_empty());
         }
 
-        debug!(
-            "Buffered message for peer {} (buffer size: {})",
-            peer_id,
-            buffer.len()
-        );
+        if let Err(e) = self.flush_message_buffers().await {
+            warn!("Failed to flush message buffers: {}", e);
+        }
+    }
+
+    /// Drop queued self-destructing items past their expiry. Only persists the
+    /// buffers when something was dropped (called by the background sweeper).
+    pub async fn purge_expired_buffered_messages(&self) {
+        let now = Utc::now();
+        let purged = {
+            let mut buffers = self.message_buffers.write().await;
+            let mut purged = 0;
+            for buffer in buffers.values_mut() {
+                let before = buffer.len();
+                buffer.retain(|msg| !msg.expires_at.is_some_and(|at| at <= now));
+                purged += before - buffer.len();
+            }
+            buffers.retain(|_, buffer| !buffer.is_empty());
+            purged
+        };
+
+        if purged > 0 {
+            debug!("Dropped {} expired self-destructing messages", purged);
+            if let Err(e) = self.flush_message_buffers().await {
+                warn!("Failed to flush message buffers: {}", e);
+            }
+        }
+
+        // Messages held as a relay also go once they are older than the queue TTL
+        let (_, ttl) = self.sync_buffer_limits().await;
+        let purged = {
+            let mut buffers = self.relay_buffers.write().await;
+            let mut purged = 0;
+            for buffer in buffers.values_mut() {
+                let before = buffer.len();
+                buffer.retain(|msg| {
+                    now.signed_duration_since(msg.timestamp) < ttl
+                        && !msg.expires_at.is_some_and(|at| at <= now)
+                });
+                purged += before - buffer.len();
+            }
+            buffers.retain(|_, buffer| !buffer.is_empty());
+            purged
+        };
+
+        if purged > 0 {
+            debug!("Dropped {} expired relayed messages", purged);
+            if let Err(e) = self.flush_relay_buffers().await {
+                warn!("Failed to flush relay buffers: {}", e);
+            }
+        }
+    }
+
+    /// Drop everything buffered for `peer_id` (unpaired, re-keyed, or no longer sent to),
+    /// including messages we hold for it as a relay.
+    pub async fn remove_message_buffer(&self, peer_id: &str) {
+        let removed = self.message_buffers.write().await.remove(peer_id);
+        if removed.is_some() {
+            if let Err(e) = self.flush_message_buffers().await {
+                warn!("Failed to flush message buffers: {}", e);
+            }
+        }
+        let removed = self.relay_buffers.write().await.remove(peer_id);
+        if removed.is_some() {
+            if let Err(e) = self.flush_relay_buffers().await {
+                warn!("Failed to flush relay buffers: {}", e);
+            }
+        }
+    }
+
+    /// Configured buffer depth and TTL, clamped to the supported maximums.
+    async fn sync_buffer_limits(&self) -> (usize, Duration) {
+        let settings = self.settings.read().await;
+        let max_size = settings.sync_buffer_size.clamp(1, MAX_SYNC_BUFFER_SIZE);
+        let ttl_secs = settings.sync_buffer_ttl_secs.min(MAX_SYNC_BUFFER_TTL_SECS);
+        (max_size, Duration::seconds(ttl_secs as i64))
     }
 
-    /// Get buffered messages for a specific peer (read-only, does NOT remove).
-    /// Filters out expired messages (older than SYNC_TTL_SECONDS).
+    /// Get buffered messages for a specific peer (read-only, does NOT remove),
+    /// followed by the ones we hold for it as a relay.
+    /// Filters out expired messages (older than `sync_buffer_ttl_secs`, or past
+    /// their `expires_at`).
     /// Used when building HashListResponse for sync.
     pub async fn get_buffer_for_peer(&self, peer_id: &str) -> Vec<ClipboardMessage> {
+        let (_, ttl) = self.sync_buffer_limits().await;
         let buffers = self.message_buffers.read().await;
+        let relay_buffers = self.relay_buffers.read().await;
         let now = Utc::now();
-        let ttl = Duration::seconds(SYNC_TTL_SECONDS);
 
         buffers
             .get(peer_id)
-            .map(|msgs| {
-                msgs.iter()
-                    .filter(|msg| now.signed_duration_since(msg.timestamp) < ttl)
-                    .cloned()
-                    .collect()
+            .into_iter()
+            .chain(relay_buffers.get(peer_id))
+            .flatten()
+            .filter(|msg| {
+                now.signed_duration_since(msg.timestamp) < ttl
+                    && !msg.expires_at.is_some_and(|at| at <= now)
             })
-            .unwrap_or_default()
+            .cloned()
+            .collect()
     }
 
     /// Find a message by content_hash in a SPECIFIC peer's buffer.
@@ -201,24 +1866,55 @@ impl AppState {
         hash: &str,
     ) -> Option<ClipboardMessage> {
         let buffers = self.message_buffers.read().await;
+        let relay_buffers = self.relay_buffers.read().await;
         buffers
             .get(peer_id)
-            .and_then(|buffer| buffer.iter().find(|msg| msg.content_hash == hash).cloned())
+            .into_iter()
+            .chain(relay_buffers.get(peer_id))
+            .flatten()
+            .find(|msg| msg.content_hash == hash)
+            .cloned()
     }
 
-    /// Remove a specific message from a specific peer's buffer by content_hash.
+    /// Remove a specific message from a specific peer's buffer (and the messages we
+    /// hold for it as a relay) by content_hash.
     /// Called after peer successfully receives content via ContentResponse.
     pub async fn remove_buffered_message_for_peer(&self, peer_id: &str, hash: &str) {
-        let mut buffers = self.message_buffers.write().await;
-        if let Some(buffer) = buffers.get_mut(peer_id) {
-            let before_len = buffer.len();
-            buffer.retain(|msg| msg.content_hash != hash);
-            if buffer.len() < before_len {
-                debug!(
-                    "Removed buffered message {} for peer {} (was delivered)",
-                    &hash[..8.min(hash.len())],
-                    peer_id
-                );
+        fn remove(
+            buffers: &mut HashMap<String, Vec<ClipboardMessage>>,
+            peer_id: &str,
+            hash: &str,
+        ) -> bool {
+            let mut removed = false;
+            if let Some(buffer) = buffers.get_mut(peer_id) {
+                let before_len = buffer.len();
+                buffer.retain(|msg| msg.content_hash != hash);
+                removed = buffer.len() < before_len;
+                if buffer.is_empty() {
+                    buffers.remove(peer_id);
+                }
+            }
+            removed
+        }
+
+        let removed = remove(&mut *self.message_buffers.write().await, peer_id, hash);
+        let removed_relayed = remove(&mut *self.relay_buffers.write().await, peer_id, hash);
+
+        if removed || removed_relayed {
+            debug!(
+                "Removed buffered message {} for peer {} (was delivered)",
+                &hash[..8.min(hash.len())],
+                peer_id
+            );
+        }
+        if removed {
+            if let Err(e) = self.flush_message_buffers().await {
+                warn!("Failed to flush message buffers: {}", e);
+            }
+        }
+        if removed_relayed {
+            if let Err(e) = self.flush_relay_buffers().await {
+                warn!("Failed to flush relay buffers: {}", e);
             }
         }
     }
@@ -243,6 +1939,101 @@ impl AppState {
         }
     }
 
+    /// Flush the pairing group to vault immediately.
+    ///
+    /// This should be called after any change to group membership or key.
+    pub async fn flush_pairing_group(&self) -> Result<()> {
+        let mut vault_manager = self.vault_manager.write().await;
+        if let Some(ref mut manager) = *vault_manager {
+            let group = self.pairing_group.read().await;
+            manager.set_pairing_group(group.as_ref())?;
+            manager.flush()?;
+            debug!("Flushed pairing group to vault");
+            Ok(())
+        } else {
+            warn!("Cannot flush pairing group: vault not open");
+            Ok(())
+        }
+    }
+
+    /// Flush pending unpair notifications to vault immediately.
+    pub async fn flush_pending_unpairs(&self) -> Result<()> {
+        let mut vault_manager = self.vault_manager.write().await;
+        if let Some(ref mut manager) = *vault_manager {
+            let pending = self.pending_unpairs.read().await;
+            manager.set_pending_unpairs(&pending)?;
+            manager.flush()?;
+            debug!("Flushed {} pending unpairs to vault", pending.len());
+            Ok(())
+        } else {
+            warn!("Cannot flush pending unpairs: vault not open");
+            Ok(())
+        }
+    }
+
+    /// Flush the messages held as a relay to vault immediately.
+    pub async fn flush_relay_buffers(&self) -> Result<()> {
+        let mut vault_manager = self.vault_manager.write().await;
+        if let Some(ref mut manager) = *vault_manager {
+            let buffers = self.relay_buffers.read().await;
+            manager.set_relay_buffers(&buffers)?;
+            manager.flush()?;
+            debug!("Flushed relay buffers for {} peers to vault", buffers.len());
+            Ok(())
+        } else {
+            warn!("Cannot flush relay buffers: vault not open");
+            Ok(())
+        }
+    }
+
+    /// Flush the offline delivery queue to vault immediately.
+    pub async fn flush_message_buffers(&self) -> Result<()> {
+        let mut vault_manager = self.vault_manager.write().await;
+        if let Some(ref mut manager) = *vault_manager {
+            let buffers = self.message_buffers.read().await;
+            manager.set_message_buffers(&buffers)?;
+            manager.flush()?;
+            debug!(
+                "Flushed message buffers for {} peers to vault",
+                buffers.len()
+            );
+            Ok(())
+        } else {
+            warn!("Cannot flush message buffers: vault not open");
+            Ok(())
+        }
+    }
+
+    /// Flush history tombstones to vault immediately.
+    pub async fn flush_history_tombstones(&self) -> Result<()> {
+        let mut vault_manager = self.vault_manager.write().await;
+        if let Some(ref mut manager) = *vault_manager {
+            let tombstones = self.history_tombstones.read().await;
+            manager.set_history_tombstones(&tombstones)?;
+            manager.flush()?;
+            debug!("Flushed {} history tombstones to vault", tombstones.len());
+            Ok(())
+        } else {
+            warn!("Cannot flush history tombstones: vault not open");
+            Ok(())
+        }
+    }
+
+    /// Flush pending identity rotations to vault immediately.
+    pub async fn flush_pending_rotations(&self) -> Result<()> {
+        let mut vault_manager = self.vault_manager.write().await;
+        if let Some(ref mut manager) = *vault_manager {
+            let pending = self.pending_rotations.read().await;
+            manager.set_pending_rotations(&pending)?;
+            manager.flush()?;
+            debug!("Flushed {} pending rotations to vault", pending.len());
+            Ok(())
+        } else {
+            warn!("Cannot flush pending rotations: vault not open");
+            Ok(())
+        }
+    }
+
     /// Flush clipboard history to vault immediately.
     ///
     /// This should be called after any mutation to clipboard_history:
@@ -312,6 +2103,36 @@ impl AppState {
                 warn!("Failed to set paired peers in vault: {}", e);
             }
 
+            let group = self.pairing_group.read().await;
+            if let Err(e) = manager.set_pairing_group(group.as_ref()) {
+                warn!("Failed to set pairing group in vault: {}", e);
+            }
+
+            let pending_unpairs = self.pending_unpairs.read().await;
+            if let Err(e) = manager.set_pending_unpairs(&pending_unpairs) {
+                warn!("Failed to set pending unpairs in vault: {}", e);
+            }
+
+            let pending_rotations = self.pending_rotations.read().await;
+            if let Err(e) = manager.set_pending_rotations(&pending_rotations) {
+                warn!("Failed to set pending rotations in vault: {}", e);
+            }
+
+            let message_buffers = self.message_buffers.read().await;
+            if let Err(e) = manager.set_message_buffers(&message_buffers) {
+                warn!("Failed to set message buffers in vault: {}", e);
+            }
+
+            let relay_buffers = self.relay_buffers.read().await;
+            if let Err(e) = manager.set_relay_buffers(&relay_buffers) {
+                warn!("Failed to set relay buffers in vault: {}", e);
+            }
+
+            let history_tombstones = self.history_tombstones.read().await;
+            if let Err(e) = manager.set_history_tombstones(&history_tombstones) {
+                warn!("Failed to set history tombstones in vault: {}", e);
+            }
+
             // Flush to disk
             manager.flush()?;
             debug!("Flushed all data to vault");
@@ -328,3 +2149,102 @@ impl Default for AppState {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::GroupMember;
+
+    fn identity() -> DeviceIdentity {
+        DeviceIdentity {
+            device_id: "local".into(),
+            device_name: "Local".into(),
+            public_key: vec![0; 32],
+            private_key: None,
+            created_at: Utc::now(),
+        }
+    }
+
+    fn peer(peer_id: &str, send: bool) -> PairedPeer {
+        PairedPeer {
+            peer_id: peer_id.into(),
+            device_name: peer_id.into(),
+            shared_secret: vec![7; 32],
+            paired_at: Utc::now(),
+            last_seen: None,
+            last_known_addresses: Vec::new(),
+            group_id: None,
+            public_key: None,
+            verified: false,
+            sync_policy: SyncPolicy {
+                send,
+                ..SyncPolicy::default()
+            },
+            expiry: None,
+            applied_rotation: None,
+        }
+    }
+
+    fn member(peer_id: &str) -> GroupMember {
+        GroupMember {
+            peer_id: peer_id.into(),
+            device_name: peer_id.into(),
+            addresses: Vec::new(),
+            public_key: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_opted_out_group_member_gets_nothing() {
+        let state = AppState::new();
+        let mut group = PairingGroup::new("Home".into(), vec![1; 32], member("local"));
+        group.members.extend([member("a"), member("b")]);
+        *state.pairing_group.write().await = Some(group);
+        *state.paired_peers.write().await =
+            vec![peer("a", true), peer("b", false), peer("c", true)];
+
+        let outgoing = state
+            .prepare_clipboard_messages(
+                &identity(),
+                "secret",
+                &hash_content("secret"),
+                state.hlc.now(),
+                ClipboardSelection::Clipboard,
+                HistoryRetention::Keep,
+            )
+            .await;
+
+        let recipients: Vec<&str> = outgoing
+            .iter()
+            .flat_map(|o| o.recipients.iter().map(String::as_str))
+            .collect();
+        assert_eq!(recipients, ["a", "c"]);
+        assert!(outgoing.iter().all(|o| o.message.group_id.is_none()));
+    }
+
+    #[tokio::test]
+    async fn test_self_destructing_item_needs_version_2() {
+        let state = AppState::new();
+        *state.paired_peers.write().await = vec![peer("a", true)];
+        let sealed_version = |message: ClipboardMessage| {
+            let plaintext = decrypt_content(&message.encrypted_content, &[7; 32]).unwrap();
+            SealedClipboard::from_bytes(&plaintext).unwrap().version
+        };
+
+        for (ttl_secs, version) in [(None, 1), (Some(30), 2)] {
+            let message = state
+                .prepare_clipboard_message_for_peer(
+                    &identity(),
+                    "a",
+                    "secret",
+                    &hash_content("secret"),
+                    state.hlc.now(),
+                    ttl_secs,
+                    HistoryRetention::Keep,
+                )
+                .await
+                .unwrap();
+            assert_eq!(sealed_version(message), version);
+        }
+    }
+}
@@ -2,6 +2,8 @@ use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
 use super::peers::get_data_dir;
+use crate::clipboard::filter::default_sensitive_rules;
+use crate::clipboard::{ClipboardSelection, SensitiveRule};
 use crate::error::Result;
 
 /// Application settings stored in settings.json.
@@ -27,6 +29,36 @@ pub struct AppSettings {
     pub hide_clipboard_content: bool,
     /// Auto-lock timeout in minutes. 0 means never auto-lock.
     pub auto_lock_minutes: u32,
+    /// How long a pairing session may stay unfinished before it is expired.
+    pub pairing_timeout_secs: u64,
+    /// Maximum clipboard messages queued per paired peer while it is offline.
+    pub sync_buffer_size: usize,
+    /// How long queued messages are kept for an offline peer.
+    pub sync_buffer_ttl_secs: u64,
+    /// Reconcile the full clipboard history with paired devices when they connect,
+    /// including deletions. Both devices must enable it.
+    pub history_sync_enabled: bool,
+    /// Ask before answering a paired device that pulls our latest clipboard entry.
+    pub pull_requires_approval: bool,
+    /// Hold encrypted messages for paired devices that are offline, and hand our
+    /// own messages for offline devices to paired relays.
+    pub relay_enabled: bool,
+    /// Keep incoming items in history instead of writing them to the system
+    /// clipboard (see `SyncPolicy::history_only` for per-peer overrides).
+    pub receive_history_only: bool,
+    /// Check local copies against `sensitive_rules` before they are broadcast.
+    pub sensitive_filter_enabled: bool,
+    /// Built-in detectors and user regex rules, each with its own action.
+    pub sensitive_rules: Vec<SensitiveRule>,
+    /// How long history keeps items matched by an `auto_expire` rule.
+    pub sensitive_expire_secs: u64,
+    /// Also sync the PRIMARY selection (select-to-copy, middle-click paste).
+    /// Linux only.
+    pub primary_selection_enabled: bool,
+    /// How long a selection must stay unchanged before it is sent.
+    pub primary_debounce_ms: u64,
+    /// Where items selected on another device are written on this one.
+    pub primary_receive_target: ClipboardSelection,
 }
 
 impl Default for AppSettings {
@@ -40,6 +72,19 @@ impl Default for AppSettings {
             auth_method: None,
             hide_clipboard_content: false,
             auto_lock_minutes: 15,
+            pairing_timeout_secs: 300,
+            sync_buffer_size: 20,
+            sync_buffer_ttl_secs: 60 * 60 * 24,
+            history_sync_enabled: false,
+            pull_requires_approval: false,
+            relay_enabled: false,
+            receive_history_only: false,
+            sensitive_filter_enabled: true,
+            sensitive_rules: default_sensitive_rules(),
+            sensitive_expire_secs: 60,
+            primary_selection_enabled: false,
+            primary_debounce_ms: 750,
+            primary_receive_target: ClipboardSelection::Clipboard,
         }
     }
 }
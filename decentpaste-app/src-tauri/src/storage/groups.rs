@@ -0,0 +1,152 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// A set of devices sharing one group key.
+///
+/// A new device pairs (PIN, URI or passphrase) with any one member and is then
+/// invited; the group state propagates to every member, so N devices need N-1
+/// pairing ceremonies instead of N*(N-1)/2.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PairingGroup {
+    pub group_id: String,
+    pub name: String,
+    /// Symmetric key for clipboard messages broadcast to the group.
+    /// Replaced whenever a member is removed.
+    pub group_key: Vec<u8>,
+    /// Incremented on every membership change. Members converge on the highest epoch.
+    pub epoch: u64,
+    pub members: Vec<GroupMember>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct GroupMember {
+    pub peer_id: String,
+    pub device_name: String,
+    /// Last known addresses, so members that never met can still reconnect.
+    #[serde(default)]
+    pub addresses: Vec<String>,
+    /// X25519 identity key, as recorded by the member that invited this one.
+    /// Bound into the pair secrets derived for this member; `None` for members
+    /// invited before keys were listed, whose pairings can't be verified.
+    #[serde(default)]
+    pub public_key: Option<Vec<u8>>,
+}
+
+impl PairingGroup {
+    pub fn new(name: String, group_key: Vec<u8>, founder: GroupMember) -> Self {
+        Self {
+            group_id: uuid::Uuid::new_v4().to_string(),
+            name,
+            group_key,
+            epoch: 1,
+            members: vec![founder],
+            created_at: Utc::now(),
+        }
+    }
+
+    pub fn is_member(&self, peer_id: &str) -> bool {
+        self.members.iter().any(|m| m.peer_id == peer_id)
+    }
+
+    /// Whether `self` should replace `current`.
+    ///
+    /// Higher epochs win. Two members changing the group concurrently can produce
+    /// the same epoch; the tie is broken on the key hash so everyone picks the same side.
+    pub fn supersedes(&self, current: &PairingGroup) -> bool {
+        if self.group_id != current.group_id {
+            return false;
+        }
+        match self.epoch.cmp(&current.epoch) {
+            std::cmp::Ordering::Greater => true,
+            std::cmp::Ordering::Less => false,
+            std::cmp::Ordering::Equal => {
+                Sha256::digest(&self.group_key) < Sha256::digest(&current.group_key)
+            }
+        }
+    }
+
+    /// Whether `self`, received from `sender`, may replace `current`.
+    ///
+    /// Only members of `current` can change the group, and they must stay members.
+    /// Members dropped since `current` must come with a new key: a removed device
+    /// still knows the old one.
+    pub fn is_valid_update(&self, current: &PairingGroup, sender: &str) -> bool {
+        if !current.is_member(sender) || !self.is_member(sender) {
+            return false;
+        }
+        let dropped = current.members.iter().any(|m| !self.is_member(&m.peer_id));
+        !dropped || self.group_key != current.group_key
+    }
+
+    /// The member responsible for re-keying when nobody else is (e.g. after a member
+    /// leaves on its own): the one with the lowest peer ID.
+    pub fn coordinator(&self) -> Option<&GroupMember> {
+        self.members.iter().min_by(|a, b| a.peer_id.cmp(&b.peer_id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn member(peer_id: &str) -> GroupMember {
+        GroupMember {
+            peer_id: peer_id.to_string(),
+            device_name: peer_id.to_string(),
+            addresses: Vec::new(),
+            public_key: None,
+        }
+    }
+
+    fn group(members: &[&str]) -> PairingGroup {
+        let mut group = PairingGroup::new("Home".into(), vec![1; 32], member(members[0]));
+        group.members = members.iter().map(|m| member(m)).collect();
+        group
+    }
+
+    #[test]
+    fn test_member_can_invite() {
+        let current = group(&["a", "b"]);
+        let mut update = current.clone();
+        update.members.push(member("c"));
+        update.epoch += 1;
+        assert!(update.supersedes(&current));
+        assert!(update.is_valid_update(&current, "a"));
+    }
+
+    #[test]
+    fn test_removed_member_cannot_rejoin() {
+        // "c" was removed and the group re-keyed; it still has a ceremony pairing with "a"
+        let current = group(&["a", "b"]);
+        let mut forged = group(&["a", "b", "c"]);
+        forged.group_id = current.group_id.clone();
+        forged.group_key = vec![9; 32];
+        forged.epoch = current.epoch + 5;
+        assert!(forged.supersedes(&current));
+        assert!(!forged.is_valid_update(&current, "c"));
+    }
+
+    #[test]
+    fn test_removal_requires_rekey() {
+        let current = group(&["a", "b", "c"]);
+        let mut update = current.clone();
+        update.members.retain(|m| m.peer_id != "c");
+        update.epoch += 1;
+        assert!(!update.is_valid_update(&current, "a"));
+
+        update.group_key = vec![2; 32];
+        assert!(update.is_valid_update(&current, "a"));
+    }
+
+    #[test]
+    fn test_sender_must_stay_member() {
+        let current = group(&["a", "b", "c"]);
+        let mut update = current.clone();
+        update.members.retain(|m| m.peer_id != "b");
+        update.group_key = vec![2; 32];
+        update.epoch += 1;
+        assert!(!update.is_valid_update(&current, "b"));
+    }
+}
@@ -1,5 +1,10 @@
 mod config;
+mod groups;
 mod peers;
 
 pub use config::{load_settings, save_settings, AppSettings};
-pub use peers::{get_data_dir, init_data_dir, DeviceIdentity, PairedPeer};
+pub use groups::{GroupMember, PairingGroup};
+pub use peers::{
+    get_data_dir, init_data_dir, DeviceIdentity, PairedPeer, PairingExpiry, PendingRotation,
+    PendingUnpair, SyncPolicy,
+};
@@ -32,6 +32,102 @@ pub struct PairedPeer {
     /// Stored as strings (Multiaddr format) for serialization compatibility.
     #[serde(default)]
     pub last_known_addresses: Vec<String>,
+    /// Set when this pairing was created by joining a `PairingGroup` rather than
+    /// a pairing ceremony. Its secret is derived from the group key and it is
+    /// re-keyed or removed together with the group.
+    #[serde(default)]
+    pub group_id: Option<String>,
+    /// X25519 identity key the peer presented when we paired (or first announced,
+    /// for pairings that didn't exchange one). Used for safety numbers and to detect
+    /// a peer ID showing up with a different key.
+    #[serde(default)]
+    pub public_key: Option<Vec<u8>>,
+    /// Set by the user after comparing safety numbers on both devices.
+    /// Cleared if the peer presents a different identity key.
+    #[serde(default)]
+    pub verified: bool,
+    #[serde(default)]
+    pub sync_policy: SyncPolicy,
+    /// Set for temporary (guest) pairings, which are removed once a limit is reached
+    /// and never get a sync backlog.
+    #[serde(default)]
+    pub expiry: Option<PairingExpiry>,
+    /// SHA-256 (hex) of the last identity rotation announcement we applied from
+    /// this peer, so a retry after a lost ack is recognised and acked again.
+    #[serde(default)]
+    pub applied_rotation: Option<String>,
+}
+
+/// Limits of a temporary pairing. Whichever is reached first ends it.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PairingExpiry {
+    pub expires_at: Option<DateTime<Utc>>,
+    /// Clipboard messages (sent or received) left before the pairing ends
+    pub messages_remaining: Option<u32>,
+}
+
+impl PairingExpiry {
+    pub fn is_reached(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at.is_some_and(|at| now >= at) || self.messages_remaining == Some(0)
+    }
+}
+
+/// What we exchange with a paired peer. Defaults to full two-way sync.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct SyncPolicy {
+    /// Send our clipboard to the peer.
+    /// Always on for group members, who can read anything sent to the group.
+    pub send: bool,
+    /// Accept clipboard content from the peer
+    pub receive: bool,
+    /// Write accepted content to the system clipboard; otherwise it only goes to history
+    pub apply_to_clipboard: bool,
+    /// Overrides `AppSettings::receive_history_only` for this peer (`None` follows it)
+    #[serde(default)]
+    pub history_only: Option<bool>,
+}
+
+impl Default for SyncPolicy {
+    fn default() -> Self {
+        Self {
+            send: true,
+            receive: true,
+            apply_to_clipboard: true,
+            history_only: None,
+        }
+    }
+}
+
+impl SyncPolicy {
+    /// Whether accepted content may replace the system clipboard, given the global
+    /// receive-into-history-only setting.
+    pub fn applies_to_clipboard(&self, receive_history_only: bool) -> bool {
+        self.apply_to_clipboard && !self.history_only.unwrap_or(receive_history_only)
+    }
+}
+
+/// An `Unpair` notification waiting to be acknowledged by a peer we removed.
+///
+/// The shared secret is gone once we unpair, so the proof is computed up front
+/// and kept until the peer is reachable.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingUnpair {
+    pub peer_id: String,
+    pub proof: Vec<u8>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// An identity rotation announcement waiting to be acknowledged by a paired peer.
+///
+/// The payload is encrypted with the shared secret from before the rotation,
+/// so it is built once and resent as is until the peer acks it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingRotation {
+    pub peer_id: String,
+    /// Our PeerId before the rotation, which the peer still knows us by
+    pub old_peer_id: String,
+    pub encrypted_payload: Vec<u8>,
+    pub created_at: DateTime<Utc>,
 }
 
 /// Initialize the data directory using Tauri's path resolver.
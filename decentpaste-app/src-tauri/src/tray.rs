@@ -26,9 +26,40 @@ pub fn setup_tray(app: &tauri::AppHandle) -> Result<(), Box<dyn std::error::Erro
     } else {
         "Auto Sync: Off"
     };
+    let send_item = MenuItem::with_id(
+        app,
+        "send_to_peer",
+        "Send Clipboard To…",
+        true,
+        Some("CommandOrControl+Alt+D"),
+    )?;
+    let apply_item = MenuItem::with_id(
+        app,
+        "apply_latest",
+        "Apply Latest Received",
+        true,
+        Some("CommandOrControl+Alt+V"),
+    )?;
+    let undo_item = MenuItem::with_id(
+        app,
+        "undo_remote_apply",
+        "Undo Remote Paste",
+        true,
+        Some("CommandOrControl+Alt+Z"),
+    )?;
     let sync_item = MenuItem::with_id(app, "sync_toggle", sync_label, true, None::<&str>)?;
     let quit_item = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?;
-    let menu = Menu::with_items(app, &[&show_item, &sync_item, &quit_item])?;
+    let menu = Menu::with_items(
+        app,
+        &[
+            &show_item,
+            &send_item,
+            &apply_item,
+            &undo_item,
+            &sync_item,
+            &quit_item,
+        ],
+    )?;
 
     // Keep a reference to the sync menu item for dynamic updates
     let sync_item = std::sync::Arc::new(sync_item);
@@ -69,6 +100,9 @@ pub fn setup_tray(app: &tauri::AppHandle) -> Result<(), Box<dyn std::error::Erro
                     let _ = window.set_focus();
                 }
             }
+            "send_to_peer" => request_send_to_peer(app),
+            "apply_latest" => apply_latest_received(app),
+            "undo_remote_apply" => undo_remote_apply(app),
             "sync_toggle" => {
                 let app = app.clone();
                 let sync_item = sync_item_clone.clone();
@@ -116,3 +150,47 @@ pub fn setup_tray(app: &tauri::AppHandle) -> Result<(), Box<dyn std::error::Erro
     info!("System tray initialized");
     Ok(())
 }
+
+/// Show the window and ask the frontend to pick a paired device to send the
+/// current clipboard to (tray item and global shortcut).
+#[cfg(not(any(target_os = "android", target_os = "ios")))]
+pub fn request_send_to_peer(app: &tauri::AppHandle) {
+    use tauri::{Emitter, Manager};
+
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+    let _ = app.emit("send-to-peer-requested", ());
+}
+
+/// Put the latest item received from another device on the clipboard (tray item
+/// and global shortcut), for use with `receive_history_only`.
+#[cfg(not(any(target_os = "android", target_os = "ios")))]
+pub fn apply_latest_received(app: &tauri::AppHandle) {
+    use tauri::Emitter;
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        match crate::apply_history_entry(&app, None).await {
+            Ok(entry) => {
+                let _ = app.emit("history-entry-applied", entry);
+            }
+            Err(e) => tracing::warn!("Failed to apply latest received item: {}", e),
+        }
+    });
+}
+
+/// Restore the clipboard content the last received item replaced (tray item and
+/// global shortcut).
+#[cfg(not(any(target_os = "android", target_os = "ios")))]
+pub fn undo_remote_apply(app: &tauri::AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        match crate::undo_last_remote_apply(&app).await {
+            Ok(true) => {}
+            Ok(false) => tracing::debug!("No remote clipboard overwrite to undo"),
+            Err(e) => tracing::warn!("Failed to undo remote clipboard overwrite: {}", e),
+        }
+    });
+}
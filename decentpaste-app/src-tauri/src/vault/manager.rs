@@ -9,6 +9,7 @@
 //! - Vault destruction for factory reset
 //! - Encrypted storage for clipboard history, paired peers, device identity, and keypairs
 
+use std::collections::HashMap;
 use std::path::PathBuf;
 
 use argon2::{Algorithm, Argon2, Params, Version};
@@ -17,9 +18,10 @@ use tauri::{AppHandle, Runtime};
 use tauri_plugin_decentsecret::DecentsecretExt;
 use tracing::{debug, info, warn};
 
-use crate::clipboard::ClipboardEntry;
+use crate::clipboard::{ClipboardEntry, HistoryTombstone};
 use crate::error::{DecentPasteError, Result};
-use crate::storage::{DeviceIdentity, PairedPeer};
+use crate::network::protocol::ClipboardMessage;
+use crate::storage::{DeviceIdentity, PairedPeer, PairingGroup, PendingRotation, PendingUnpair};
 use crate::vault::salt::{delete_salt, get_or_create_salt};
 #[cfg(desktop)]
 use crate::vault::storage::EncryptedVaultKeyData;
@@ -643,6 +645,157 @@ impl VaultManager {
         Ok(())
     }
 
+    // =========================================================================
+    // Data Operations - Pairing Group
+    // =========================================================================
+
+    /// Get the pairing group from the vault.
+    ///
+    /// Returns `None` if this device is not in a group.
+    pub fn get_pairing_group(&self) -> Result<Option<PairingGroup>> {
+        if !self.is_open() {
+            return Err(DecentPasteError::Storage("Vault is not open".into()));
+        }
+        Ok(self.data.pairing_group.clone())
+    }
+
+    /// Set (or clear) the pairing group in the vault.
+    ///
+    /// Call `flush()` to persist.
+    pub fn set_pairing_group(&mut self, group: Option<&PairingGroup>) -> Result<()> {
+        if !self.is_open() {
+            return Err(DecentPasteError::Storage("Vault is not open".into()));
+        }
+        self.data.pairing_group = group.cloned();
+        debug!("Stored pairing group in vault");
+        Ok(())
+    }
+
+    // =========================================================================
+    // Data Operations - Pending Unpairs
+    // =========================================================================
+
+    /// Get unpair notifications that haven't been delivered yet.
+    pub fn get_pending_unpairs(&self) -> Result<Vec<PendingUnpair>> {
+        if !self.is_open() {
+            return Err(DecentPasteError::Storage("Vault is not open".into()));
+        }
+        Ok(self.data.pending_unpairs.clone())
+    }
+
+    /// Set pending unpair notifications in the vault.
+    ///
+    /// Call `flush()` to persist.
+    pub fn set_pending_unpairs(&mut self, unpairs: &[PendingUnpair]) -> Result<()> {
+        if !self.is_open() {
+            return Err(DecentPasteError::Storage("Vault is not open".into()));
+        }
+        self.data.pending_unpairs = unpairs.to_vec();
+        debug!("Stored {} pending unpairs in vault", unpairs.len());
+        Ok(())
+    }
+
+    // =========================================================================
+    // Data Operations - Pending Rotations
+    // =========================================================================
+
+    /// Get identity rotation announcements that haven't been acknowledged yet.
+    pub fn get_pending_rotations(&self) -> Result<Vec<PendingRotation>> {
+        if !self.is_open() {
+            return Err(DecentPasteError::Storage("Vault is not open".into()));
+        }
+        Ok(self.data.pending_rotations.clone())
+    }
+
+    /// Set pending identity rotation announcements in the vault.
+    ///
+    /// Call `flush()` to persist.
+    pub fn set_pending_rotations(&mut self, rotations: &[PendingRotation]) -> Result<()> {
+        if !self.is_open() {
+            return Err(DecentPasteError::Storage("Vault is not open".into()));
+        }
+        self.data.pending_rotations = rotations.to_vec();
+        debug!("Stored {} pending rotations in vault", rotations.len());
+        Ok(())
+    }
+
+    // =========================================================================
+    // Data Operations - Message Buffers
+    // =========================================================================
+
+    /// Get the offline delivery queue (messages each peer hasn't fetched yet).
+    pub fn get_message_buffers(&self) -> Result<HashMap<String, Vec<ClipboardMessage>>> {
+        if !self.is_open() {
+            return Err(DecentPasteError::Storage("Vault is not open".into()));
+        }
+        Ok(self.data.message_buffers.clone())
+    }
+
+    /// Set the offline delivery queue in the vault.
+    ///
+    /// Call `flush()` to persist.
+    pub fn set_message_buffers(
+        &mut self,
+        buffers: &HashMap<String, Vec<ClipboardMessage>>,
+    ) -> Result<()> {
+        if !self.is_open() {
+            return Err(DecentPasteError::Storage("Vault is not open".into()));
+        }
+        self.data.message_buffers = buffers.clone();
+        debug!(
+            "Stored message buffers for {} peers in vault",
+            buffers.len()
+        );
+        Ok(())
+    }
+
+    /// Get the messages held for other devices as a relay.
+    pub fn get_relay_buffers(&self) -> Result<HashMap<String, Vec<ClipboardMessage>>> {
+        if !self.is_open() {
+            return Err(DecentPasteError::Storage("Vault is not open".into()));
+        }
+        Ok(self.data.relay_buffers.clone())
+    }
+
+    /// Set the messages held as a relay in the vault.
+    ///
+    /// Call `flush()` to persist.
+    pub fn set_relay_buffers(
+        &mut self,
+        buffers: &HashMap<String, Vec<ClipboardMessage>>,
+    ) -> Result<()> {
+        if !self.is_open() {
+            return Err(DecentPasteError::Storage("Vault is not open".into()));
+        }
+        self.data.relay_buffers = buffers.clone();
+        debug!("Stored relay buffers for {} peers in vault", buffers.len());
+        Ok(())
+    }
+
+    // =========================================================================
+    // Data Operations - History Tombstones
+    // =========================================================================
+
+    /// Get tombstones of deleted history entries.
+    pub fn get_history_tombstones(&self) -> Result<Vec<HistoryTombstone>> {
+        if !self.is_open() {
+            return Err(DecentPasteError::Storage("Vault is not open".into()));
+        }
+        Ok(self.data.history_tombstones.clone())
+    }
+
+    /// Set tombstones of deleted history entries in the vault.
+    ///
+    /// Call `flush()` to persist.
+    pub fn set_history_tombstones(&mut self, tombstones: &[HistoryTombstone]) -> Result<()> {
+        if !self.is_open() {
+            return Err(DecentPasteError::Storage("Vault is not open".into()));
+        }
+        self.data.history_tombstones = tombstones.to_vec();
+        debug!("Stored {} history tombstones in vault", tombstones.len());
+        Ok(())
+    }
+
     // =========================================================================
     // Data Operations - Device Identity
     // =========================================================================
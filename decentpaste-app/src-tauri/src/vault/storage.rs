@@ -11,12 +11,16 @@ use aes_gcm::{
 };
 use rand::RngCore;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::path::PathBuf;
 use zeroize::{Zeroize, ZeroizeOnDrop};
 
-use crate::clipboard::ClipboardEntry;
+use crate::clipboard::{ClipboardEntry, HistoryTombstone};
 use crate::error::{DecentPasteError, Result};
-use crate::storage::{get_data_dir, DeviceIdentity, PairedPeer};
+use crate::network::protocol::ClipboardMessage;
+use crate::storage::{
+    get_data_dir, DeviceIdentity, PairedPeer, PairingGroup, PendingRotation, PendingUnpair,
+};
 
 /// Nonce size for AES-GCM (96 bits = 12 bytes)
 const NONCE_SIZE: usize = 12;
@@ -80,6 +84,30 @@ pub struct VaultData {
     /// libp2p Ed25519 keypair (protobuf-encoded)
     #[serde(default)]
     pub libp2p_keypair: Option<Vec<u8>>,
+
+    /// Pairing group this device belongs to, including the group key
+    #[serde(default)]
+    pub pairing_group: Option<PairingGroup>,
+
+    /// Unpair notifications not yet delivered to the removed peers
+    #[serde(default)]
+    pub pending_unpairs: Vec<PendingUnpair>,
+
+    /// Identity rotation announcements not yet acknowledged by paired peers
+    #[serde(default)]
+    pub pending_rotations: Vec<PendingRotation>,
+
+    /// Encrypted clipboard messages each paired peer hasn't fetched yet (peer_id -> messages)
+    #[serde(default)]
+    pub message_buffers: HashMap<String, Vec<ClipboardMessage>>,
+
+    /// Messages we hold as a relay for other devices (recipient peer_id -> messages)
+    #[serde(default)]
+    pub relay_buffers: HashMap<String, Vec<ClipboardMessage>>,
+
+    /// Deleted history entries, propagated by history reconciliation
+    #[serde(default)]
+    pub history_tombstones: Vec<HistoryTombstone>,
 }
 
 /// Get the path to the vault file.
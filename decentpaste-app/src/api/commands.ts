@@ -5,10 +5,16 @@ import type {
   ClipboardEntry,
   DeviceInfo,
   DiscoveredPeer,
+  GroupInfo,
+  IdentityRotationResult,
   NetworkStatus,
   PairedPeer,
   PairingSession,
+  PairingUriInfo,
+  PeerFingerprint,
+  ReplayStats,
   SecretStorageStatus,
+  SyncPolicy,
   VaultStatus,
 } from './types';
 
@@ -100,11 +106,98 @@ export async function removePairedPeer(peerId: string): Promise<void> {
   return invoke('remove_paired_peer', { peerId });
 }
 
+/** Key fingerprints and safety number to compare with the peer device */
+export async function getPeerFingerprint(peerId: string): Promise<PeerFingerprint> {
+  return invoke('get_peer_fingerprint', { peerId });
+}
+
+/** Mark a peer verified after comparing safety numbers (or clear the flag) */
+export async function setPeerVerified(peerId: string, verified: boolean): Promise<void> {
+  return invoke('set_peer_verified', { peerId, verified });
+}
+
+/** Choose whether to send to, receive from, or only keep history of a paired peer */
+export async function setPeerSyncPolicy(peerId: string, policy: SyncPolicy): Promise<void> {
+  return invoke('set_peer_sync_policy', { peerId, policy });
+}
+
+/**
+ * Make a pairing temporary (removed after `expiresInSecs` or `maxMessages` messages,
+ * whichever comes first). Pass null for both to make it permanent again.
+ */
+export async function setPairingExpiry(
+  peerId: string,
+  expiresInSecs: number | null,
+  maxMessages: number | null,
+): Promise<void> {
+  return invoke('set_pairing_expiry', { peerId, expiresInSecs, maxMessages });
+}
+
+/** Offer one clipboard item to an unpaired discovered device; returns the transfer ID */
+export async function startNearbySend(peerId: string, content: string): Promise<string> {
+  return invoke('start_nearby_send', { peerId, content });
+}
+
+/** Accept or decline an incoming nearby send; returns the confirmation code when accepted */
+export async function respondToNearbySend(transferId: string, accept: boolean): Promise<string | null> {
+  return invoke('respond_to_nearby_send', { transferId, accept });
+}
+
+/** Send the item after the user checked that both devices show the same code */
+export async function confirmNearbySend(transferId: string): Promise<void> {
+  return invoke('confirm_nearby_send', { transferId });
+}
+
+export async function cancelNearbySend(transferId: string): Promise<void> {
+  return invoke('cancel_nearby_send', { transferId });
+}
+
+/** Replace this device's identity key (and optionally its peer ID) and notify paired devices */
+export async function rotateIdentity(rotatePeerId: boolean): Promise<IdentityRotationResult> {
+  return invoke('rotate_identity', { rotatePeerId });
+}
+
 // Pairing flow
 export async function initiatePairing(peerId: string): Promise<string> {
   return invoke('initiate_pairing', { peerId });
 }
 
+/**
+ * Create a one-time pairing link (decentpaste://pair?...) and QR code for devices
+ * that can't discover each other via mDNS. Expires after the pairing timeout.
+ */
+export async function createPairingUri(): Promise<PairingUriInfo> {
+  return invoke('create_pairing_uri');
+}
+
+/**
+ * Pair with the device that created a pairing link. No PIN is needed.
+ * @returns The pairing session ID
+ */
+export async function pairWithUri(uri: string): Promise<string> {
+  return invoke('pair_with_uri', { uri });
+}
+
+/**
+ * Accept the next passphrase pairing attempt (for devices that can't display a PIN).
+ * The passphrase is single-use and expires after the pairing timeout.
+ */
+export async function enablePassphrasePairing(passphrase: string): Promise<void> {
+  return invoke('enable_passphrase_pairing', { passphrase });
+}
+
+export async function disablePassphrasePairing(): Promise<void> {
+  return invoke('disable_passphrase_pairing');
+}
+
+/**
+ * Pair with a peer that enabled passphrase pairing with the same passphrase.
+ * @returns The pairing session ID
+ */
+export async function initiatePassphrasePairing(peerId: string, passphrase: string): Promise<string> {
+  return invoke('initiate_passphrase_pairing', { peerId, passphrase });
+}
+
 export async function respondToPairing(sessionId: string, accept: boolean): Promise<string | null> {
   return invoke('respond_to_pairing', { sessionId, accept });
 }
@@ -121,6 +214,37 @@ export async function getPairingSessions(): Promise<PairingSession[]> {
   return invoke('get_pairing_sessions');
 }
 
+// Pairing groups
+export async function getGroup(): Promise<GroupInfo | null> {
+  return invoke('get_group');
+}
+
+export async function createGroup(name: string): Promise<GroupInfo> {
+  return invoke('create_group', { name });
+}
+
+/**
+ * Add an already paired peer to the group. Every member is then paired with it,
+ * without further pairing ceremonies.
+ */
+export async function inviteToGroup(peerId: string): Promise<GroupInfo> {
+  return invoke('invite_to_group', { peerId });
+}
+
+/** Remove a member from the group. The group is re-keyed. */
+export async function removeGroupMember(peerId: string): Promise<GroupInfo> {
+  return invoke('remove_group_member', { peerId });
+}
+
+export async function leaveGroup(): Promise<void> {
+  return invoke('leave_group');
+}
+
+/** Join the group we were invited to (returns it), or decline (returns null). */
+export async function respondToGroupInvite(groupId: string, accept: boolean): Promise<GroupInfo | null> {
+  return invoke('respond_to_group_invite', { groupId, accept });
+}
+
 // Clipboard operations
 export async function getClipboardHistory(limit?: number): Promise<ClipboardEntry[]> {
   return invoke('get_clipboard_history', { limit: limit ?? null });
@@ -134,6 +258,30 @@ export async function clearClipboardHistory(): Promise<void> {
   return invoke('clear_clipboard_history');
 }
 
+/** Send one item to a single paired device; `content` defaults to the current clipboard */
+export async function sendToPeer(peerId: string, content?: string, ttlSecs?: number): Promise<ClipboardEntry> {
+  return invoke('send_to_peer', { peerId, content: content ?? null, ttlSecs: ttlSecs ?? null });
+}
+
+/** Put a history entry on the clipboard without syncing it again (default: latest received) */
+export async function applyHistoryEntry(entryId?: string): Promise<ClipboardEntry> {
+  return invoke('apply_history_entry', { entryId: entryId ?? null });
+}
+
+/** Restore the clipboard content replaced by the last item received from another device. */
+export async function undoLastRemoteApply(): Promise<void> {
+  return invoke('undo_last_remote_apply');
+}
+
+/** Ask a paired device for its latest clipboard entry. Returns the request id. */
+export async function pullClipboard(peerId: string): Promise<string> {
+  return invoke('pull_clipboard', { peerId });
+}
+
+export async function respondToClipboardPull(requestId: string, accept: boolean): Promise<void> {
+  return invoke('respond_to_clipboard_pull', { requestId, accept });
+}
+
 // Settings
 export async function getSettings(): Promise<AppSettings> {
   return invoke('get_settings');
@@ -147,6 +295,11 @@ export async function getDeviceInfo(): Promise<DeviceInfo> {
   return invoke('get_device_info');
 }
 
+/** Clipboard messages rejected by replay protection since the app started */
+export async function getReplayStats(): Promise<ReplayStats> {
+  return invoke('get_replay_stats');
+}
+
 // Vault commands - Secure storage authentication and management
 
 /**
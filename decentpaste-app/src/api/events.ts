@@ -1,14 +1,36 @@
 import { listen, UnlistenFn } from '@tauri-apps/api/event';
 import type {
   ClipboardBroadcastPayload,
+  ClipboardEntriesExpiredPayload,
   ClipboardEntry,
+  ClipboardHeldPayload,
+  ClipboardPullFailedPayload,
+  ClipboardPullRequestPayload,
+  ClipboardSelfDestructPayload,
+  ClipboardSelfDestructedPayload,
+  DeliveryStatusPayload,
   DiscoveredPeer,
+  GroupInfo,
+  GroupInvitePayload,
+  GroupRemovedPayload,
+  HistorySyncedPayload,
+  NearbySendCodePayload,
+  NearbySendCompletePayload,
+  NearbySendFailedPayload,
+  NearbySendRequestPayload,
   NetworkStatus,
   PairingCompletePayload,
+  PairingExpiredPayload,
   PairingFailedPayload,
+  PairingInviteAcceptedPayload,
+  PairingPassphraseStartedPayload,
   PairingPinPayload,
   PairingRequestPayload,
+  PeerIdentityRotatedPayload,
+  PeerKeyChangedPayload,
   PeerNameUpdatedPayload,
+  PeerUnpairedPayload,
+  SensitiveContentDetectedPayload,
   VaultStatus,
 } from './types';
 
@@ -42,6 +64,32 @@ interface EventListeners {
   appMinimizedToTray: EventHandler<void>[];
   vaultStatus: EventHandler<VaultStatus>[];
   settingsChanged: EventHandler<SettingsChangedPayload>[];
+  pairingInviteAccepted: EventHandler<PairingInviteAcceptedPayload>[];
+  pairingPassphraseStarted: EventHandler<PairingPassphraseStartedPayload>[];
+  groupUpdated: EventHandler<GroupInfo>[];
+  groupRemoved: EventHandler<GroupRemovedPayload>[];
+  peerUnpaired: EventHandler<PeerUnpairedPayload>[];
+  peerKeyChanged: EventHandler<PeerKeyChangedPayload>[];
+  peerIdentityRotated: EventHandler<PeerIdentityRotatedPayload>[];
+  pairingExpired: EventHandler<PairingExpiredPayload>[];
+  nearbySendRequest: EventHandler<NearbySendRequestPayload>[];
+  nearbySendCode: EventHandler<NearbySendCodePayload>[];
+  nearbySendComplete: EventHandler<NearbySendCompletePayload>[];
+  nearbySendFailed: EventHandler<NearbySendFailedPayload>[];
+  historySynced: EventHandler<HistorySyncedPayload>[];
+  deliveryStatus: EventHandler<DeliveryStatusPayload>[];
+  sendToPeerRequested: EventHandler<null>[];
+  clipboardPullRequest: EventHandler<ClipboardPullRequestPayload>[];
+  clipboardPullFailed: EventHandler<ClipboardPullFailedPayload>[];
+  clipboardHeld: EventHandler<ClipboardHeldPayload>[];
+  historyEntryApplied: EventHandler<ClipboardEntry>[];
+  clipboardRestored: EventHandler<null>[];
+  sensitiveContentDetected: EventHandler<SensitiveContentDetectedPayload>[];
+  clipboardEntriesExpired: EventHandler<ClipboardEntriesExpiredPayload>[];
+  clipboardSelfDestruct: EventHandler<ClipboardSelfDestructPayload>[];
+  clipboardSelfDestructed: EventHandler<ClipboardSelfDestructedPayload>[];
+  clipboardSelfDestructRefused: EventHandler<ClipboardSelfDestructedPayload>[];
+  groupInvite: EventHandler<GroupInvitePayload>[];
 }
 
 class EventManager {
@@ -62,6 +110,32 @@ class EventManager {
     appMinimizedToTray: [],
     vaultStatus: [],
     settingsChanged: [],
+    pairingInviteAccepted: [],
+    pairingPassphraseStarted: [],
+    groupUpdated: [],
+    groupRemoved: [],
+    peerUnpaired: [],
+    peerKeyChanged: [],
+    peerIdentityRotated: [],
+    pairingExpired: [],
+    nearbySendRequest: [],
+    nearbySendCode: [],
+    nearbySendComplete: [],
+    nearbySendFailed: [],
+    historySynced: [],
+    deliveryStatus: [],
+    sendToPeerRequested: [],
+    clipboardPullRequest: [],
+    clipboardPullFailed: [],
+    clipboardHeld: [],
+    historyEntryApplied: [],
+    clipboardRestored: [],
+    sensitiveContentDetected: [],
+    clipboardEntriesExpired: [],
+    clipboardSelfDestruct: [],
+    clipboardSelfDestructed: [],
+    clipboardSelfDestructRefused: [],
+    groupInvite: [],
   };
 
   private unlistenFns: UnlistenFn[] = [];
@@ -116,6 +190,84 @@ class EventManager {
       listen<SettingsChangedPayload>('settings-changed', (e) => {
         this.listeners.settingsChanged.forEach((fn) => fn(e.payload));
       }),
+      listen<PairingInviteAcceptedPayload>('pairing-invite-accepted', (e) => {
+        this.listeners.pairingInviteAccepted.forEach((fn) => fn(e.payload));
+      }),
+      listen<PairingPassphraseStartedPayload>('pairing-passphrase-started', (e) => {
+        this.listeners.pairingPassphraseStarted.forEach((fn) => fn(e.payload));
+      }),
+      listen<GroupInfo>('group-updated', (e) => {
+        this.listeners.groupUpdated.forEach((fn) => fn(e.payload));
+      }),
+      listen<GroupRemovedPayload>('group-removed', (e) => {
+        this.listeners.groupRemoved.forEach((fn) => fn(e.payload));
+      }),
+      listen<PeerUnpairedPayload>('peer-unpaired', (e) => {
+        this.listeners.peerUnpaired.forEach((fn) => fn(e.payload));
+      }),
+      listen<PeerKeyChangedPayload>('peer-key-changed', (e) => {
+        this.listeners.peerKeyChanged.forEach((fn) => fn(e.payload));
+      }),
+      listen<PeerIdentityRotatedPayload>('peer-identity-rotated', (e) => {
+        this.listeners.peerIdentityRotated.forEach((fn) => fn(e.payload));
+      }),
+      listen<PairingExpiredPayload>('pairing-expired', (e) => {
+        this.listeners.pairingExpired.forEach((fn) => fn(e.payload));
+      }),
+      listen<NearbySendRequestPayload>('nearby-send-request', (e) => {
+        this.listeners.nearbySendRequest.forEach((fn) => fn(e.payload));
+      }),
+      listen<NearbySendCodePayload>('nearby-send-code', (e) => {
+        this.listeners.nearbySendCode.forEach((fn) => fn(e.payload));
+      }),
+      listen<NearbySendCompletePayload>('nearby-send-complete', (e) => {
+        this.listeners.nearbySendComplete.forEach((fn) => fn(e.payload));
+      }),
+      listen<NearbySendFailedPayload>('nearby-send-failed', (e) => {
+        this.listeners.nearbySendFailed.forEach((fn) => fn(e.payload));
+      }),
+      listen<HistorySyncedPayload>('history-synced', (e) => {
+        this.listeners.historySynced.forEach((fn) => fn(e.payload));
+      }),
+      listen<DeliveryStatusPayload>('delivery-status', (e) => {
+        this.listeners.deliveryStatus.forEach((fn) => fn(e.payload));
+      }),
+      listen<null>('send-to-peer-requested', (e) => {
+        this.listeners.sendToPeerRequested.forEach((fn) => fn(e.payload));
+      }),
+      listen<ClipboardPullRequestPayload>('clipboard-pull-request', (e) => {
+        this.listeners.clipboardPullRequest.forEach((fn) => fn(e.payload));
+      }),
+      listen<ClipboardPullFailedPayload>('clipboard-pull-failed', (e) => {
+        this.listeners.clipboardPullFailed.forEach((fn) => fn(e.payload));
+      }),
+      listen<ClipboardHeldPayload>('clipboard-held', (e) => {
+        this.listeners.clipboardHeld.forEach((fn) => fn(e.payload));
+      }),
+      listen<ClipboardEntry>('history-entry-applied', (e) => {
+        this.listeners.historyEntryApplied.forEach((fn) => fn(e.payload));
+      }),
+      listen<null>('clipboard-restored', (e) => {
+        this.listeners.clipboardRestored.forEach((fn) => fn(e.payload));
+      }),
+      listen<SensitiveContentDetectedPayload>('sensitive-content-detected', (e) => {
+        this.listeners.sensitiveContentDetected.forEach((fn) => fn(e.payload));
+      }),
+      listen<ClipboardEntriesExpiredPayload>('clipboard-entries-expired', (e) => {
+        this.listeners.clipboardEntriesExpired.forEach((fn) => fn(e.payload));
+      }),
+      listen<ClipboardSelfDestructPayload>('clipboard-self-destruct', (e) => {
+        this.listeners.clipboardSelfDestruct.forEach((fn) => fn(e.payload));
+      }),
+      listen<ClipboardSelfDestructedPayload>('clipboard-self-destructed', (e) => {
+        this.listeners.clipboardSelfDestructed.forEach((fn) => fn(e.payload));
+      }),
+      listen<ClipboardSelfDestructedPayload>('clipboard-self-destruct-refused', (e) => {
+        this.listeners.clipboardSelfDestructRefused.forEach((fn) => fn(e.payload));
+      }),
+      listen<GroupInvitePayload>('group-invite', (e) => {
+        this.listeners.groupInvite.forEach((fn) => fn(e.payload));
+      }),
     ]);
   }
 
@@ -14,6 +14,51 @@ export interface PairedPeer {
   device_name: string;
   paired_at: string;
   last_seen: string | null;
+  /** Set when the pairing was derived from a pairing group rather than a pairing ceremony */
+  group_id: string | null;
+  /** Set by the user after comparing safety numbers on both devices */
+  verified: boolean;
+  sync_policy: SyncPolicy;
+  /** Set for temporary (guest) pairings */
+  expiry: PairingExpiry | null;
+}
+
+/** Limits of a temporary pairing; it is removed when either is reached */
+export interface PairingExpiry {
+  expires_at: string | null;
+  messages_remaining: number | null;
+}
+
+/** What this device exchanges with a paired peer (all true by default) */
+export interface SyncPolicy {
+  /** Send our clipboard to the peer (always on for group members) */
+  send: boolean;
+  /** Accept clipboard content from the peer */
+  receive: boolean;
+  /** Write accepted content to the system clipboard; otherwise history only */
+  apply_to_clipboard: boolean;
+  /** Overrides the global receive_history_only setting for this peer (null follows it) */
+  history_only?: boolean | null;
+}
+
+/** Identity key fingerprints for a paired peer (from getPeerFingerprint) */
+export interface PeerFingerprint {
+  peer_id: string;
+  local_fingerprint: string;
+  /** null until the peer's identity key is known */
+  peer_fingerprint: string | null;
+  /** 30 digits in groups of five, identical on both devices */
+  safety_number: string | null;
+  verified: boolean;
+}
+
+/** Result of rotateIdentity */
+export interface IdentityRotationResult {
+  peer_id: string;
+  /** Paired devices that will be told about the new keys */
+  notified_peers: number;
+  /** A new peer ID only takes effect after restarting the app */
+  restart_required: boolean;
 }
 
 export interface ConnectedPeer {
@@ -23,22 +68,39 @@ export interface ConnectedPeer {
 }
 
 // Clipboard types
+/** Hybrid logical clock timestamp, used to order clipboard history across devices */
+export interface HlcTimestamp {
+  wall_ms: number;
+  counter: number;
+}
+
 export interface ClipboardEntry {
   id: string;
   content: string;
   content_hash: string;
+  /** Wall-clock time on the origin device (display only) */
   timestamp: string;
+  hlc: HlcTimestamp;
   origin_device_id: string;
   origin_device_name: string;
   is_local: boolean;
+  /** Delivery state at each recipient (peer_id -> status), for entries we sent */
+  delivery: Record<string, DeliveryStatus>;
+  /** Set when the entry was sent to this one paired device only */
+  target_peer_id: string | null;
+  /** Removed from history at this time (auto_expire sensitive-content rule) */
+  expires_at: string | null;
 }
 
+export type DeliveryStatus = 'pending' | 'delivered' | 'applied' | 'failed';
+
 // Pairing types
 export type PairingState =
   | 'Initiated'
   | 'AwaitingPinConfirmation'
   | 'AwaitingPeerConfirmation'
   | 'Completed'
+  | 'Expired'
   | { Failed: string };
 
 export interface PairingSession {
@@ -51,6 +113,23 @@ export interface PairingSession {
   created_at: string;
 }
 
+// Pairing group types
+export interface GroupMember {
+  peer_id: string;
+  device_name: string;
+  addresses: string[];
+}
+
+/** Pairing group this device belongs to (the group key never leaves the backend) */
+export interface GroupInfo {
+  group_id: string;
+  name: string;
+  /** Incremented on every membership change */
+  epoch: number;
+  members: GroupMember[];
+  created_at: string;
+}
+
 // Vault types
 export type VaultStatus = 'NotSetup' | 'Locked' | 'Unlocked';
 export type AuthMethod = 'pin' | 'secure_storage' | 'secure_storage_with_pin';
@@ -83,6 +162,54 @@ export interface AppSettings {
   hide_clipboard_content: boolean;
   /** Auto-lock timeout in minutes. 0 means never auto-lock */
   auto_lock_minutes: number;
+  /** Seconds an unfinished pairing session stays open before it expires */
+  pairing_timeout_secs: number;
+  /** Clipboard messages queued per paired peer while it is offline (1-500) */
+  sync_buffer_size: number;
+  /** Seconds queued messages are kept for an offline peer (max 7 days) */
+  sync_buffer_ttl_secs: number;
+  /** Reconcile the full history (including deletions) with paired devices that also enable it */
+  history_sync_enabled: boolean;
+  /** Ask before answering a paired device that pulls our latest clipboard entry */
+  pull_requires_approval: boolean;
+  /** Hold encrypted messages for offline paired devices and use paired devices as relays */
+  relay_enabled: boolean;
+  /** Keep incoming items in history instead of replacing the system clipboard */
+  receive_history_only: boolean;
+  /** Check local copies against sensitive_rules before they are broadcast */
+  sensitive_filter_enabled: boolean;
+  /** Built-in detectors and user regex rules, each with its own action */
+  sensitive_rules: SensitiveRule[];
+  /** Seconds history keeps items matched by an auto_expire rule */
+  sensitive_expire_secs: number;
+  /** Also sync the PRIMARY selection (select-to-copy, middle-click paste; Linux only) */
+  primary_selection_enabled: boolean;
+  /** Milliseconds a selection must stay unchanged before it is sent */
+  primary_debounce_ms: number;
+  /** Where items selected on another device are written on this one */
+  primary_receive_target: ClipboardSelection;
+}
+
+export type ClipboardSelection = 'clipboard' | 'primary';
+
+export type SensitiveDetector =
+  | { kind: 'private_key' }
+  | { kind: 'aws_key' }
+  | { kind: 'github_token' }
+  | { kind: 'jwt' }
+  | { kind: 'credit_card' }
+  | { kind: 'high_entropy' }
+  | { kind: 'regex'; pattern: string };
+
+/** What happens to a matching local copy: not sent, sent but not kept in history, or kept for a while */
+export type SensitiveAction = 'dont_sync' | 'no_history' | 'auto_expire';
+
+export interface SensitiveRule {
+  id: string;
+  name: string;
+  detector: SensitiveDetector;
+  action: SensitiveAction;
+  enabled: boolean;
 }
 
 // Device info
@@ -91,6 +218,13 @@ export interface DeviceInfo {
   peer_id: string | null;
 }
 
+export interface ReplayStats {
+  replayed: number;
+  too_old: number;
+  clock_skew: number;
+  tampered: number;
+}
+
 // Event payloads
 export interface PairingRequestPayload {
   sessionId: string;
@@ -110,9 +244,149 @@ export interface PairingCompletePayload {
   deviceName: string;
 }
 
+/** Sent on the device that created a pairing URI when another device scans it */
+export interface PairingInviteAcceptedPayload {
+  sessionId: string;
+  peerId: string;
+  deviceName: string;
+}
+
+/** Sent on the device with passphrase pairing enabled when a peer starts pairing */
+export interface PairingPassphraseStartedPayload {
+  sessionId: string;
+  peerId: string;
+  deviceName: string;
+}
+
+/** One-time pairing link and its QR code (from createPairingUri) */
+export interface PairingUriInfo {
+  uri: string;
+  qr_svg: string;
+  expires_at: string;
+}
+
+/** Sent when a paired device presents a different identity key than the one recorded at pairing */
+export interface PeerKeyChangedPayload {
+  peerId: string;
+  deviceName: string;
+}
+
+/** Sent when a paired device rotated its identity keys (and possibly its peer ID) */
+export interface PeerIdentityRotatedPayload {
+  oldPeerId: string;
+  peerId: string;
+  deviceName: string;
+}
+
+/** Sent when a device we are not paired with offers a one-shot nearby send */
+export interface NearbySendRequestPayload {
+  transferId: string;
+  peerId: string;
+  deviceName: string;
+}
+
+/** Confirmation code both devices show once a nearby send was accepted */
+export interface NearbySendCodePayload {
+  transferId: string;
+  code: string;
+}
+
+/** Sent when the receiver acknowledged a nearby send */
+export interface NearbySendCompletePayload {
+  transferId: string;
+}
+
+/** Sent when a nearby send was declined, cancelled, timed out or failed */
+export interface NearbySendFailedPayload {
+  transferId: string;
+  error: string;
+}
+
+/** Sent when a paired device asks for our latest clipboard entry and approval is required */
+export interface ClipboardPullRequestPayload {
+  requestId: string;
+  peerId: string;
+  deviceName: string;
+}
+
+/** Sent when a clipboard pull was declined by the other device, timed out or failed */
+export interface ClipboardPullFailedPayload {
+  requestId: string;
+  peerId: string;
+  error: string;
+}
+
+/** Sent when an incoming item was kept in history instead of replacing the clipboard */
+export interface ClipboardHeldPayload {
+  entryId: string;
+  deviceName: string;
+}
+
+/** Sent when a local copy matched a sensitive-content rule */
+export interface SensitiveContentDetectedPayload {
+  ruleId: string;
+  ruleName: string;
+  action: SensitiveAction;
+}
+
+/** Sent when a self-destructing item was put on the clipboard */
+export interface ClipboardSelfDestructPayload {
+  deviceName: string;
+  ttlSecs: number;
+}
+
+/** Sent when a self-destructing item was cleared from the clipboard, or refused
+ * because we receive into history only */
+export interface ClipboardSelfDestructedPayload {
+  deviceName: string;
+}
+
+/** Sent when auto_expire history entries were removed */
+export interface ClipboardEntriesExpiredPayload {
+  entryIds: string[];
+}
+
+export interface DeliveryStatusPayload {
+  entryId: string;
+  peerId: string;
+  status: DeliveryStatus;
+}
+
+export interface HistorySyncedPayload {
+  peerId: string;
+}
+
+/** Sent when a temporary pairing reached its expiry or message limit and was removed */
+export interface PairingExpiredPayload {
+  peerId: string;
+  deviceName: string;
+}
+
+/** Sent when a paired device removed its pairing with this device */
+export interface PeerUnpairedPayload {
+  peerId: string;
+  deviceName: string;
+}
+
+/** Sent when a paired device invites this device to its pairing group */
+export interface GroupInvitePayload {
+  groupId: string;
+  name: string;
+  peerId: string;
+  deviceName: string;
+  memberCount: number;
+}
+
+/** Sent when another member removed this device from its pairing group */
+export interface GroupRemovedPayload {
+  groupId: string;
+}
+
 export interface PairingFailedPayload {
   sessionId: string;
   error: string;
+  /** Set to 'timeout' when the session expired on either side */
+  reason?: 'timeout';
 }
 
 export interface ClipboardBroadcastPayload {
@@ -3,11 +3,20 @@ import { eventManager } from './api/events';
 import * as commands from './api/commands';
 import { getVersion } from '@tauri-apps/api/app';
 import { icon, type IconName } from './components/icons';
-import { $, escapeHtml, formatTime, truncate } from './utils/dom';
+import { $, escapeHtml, formatSeconds, formatTime, truncate } from './utils/dom';
 import { getErrorMessage } from './utils/error';
-import { isDesktop, isMac } from './utils/platform';
+import { isDesktop, isLinux, isMac } from './utils/platform';
 import { checkForUpdates, downloadAndInstallUpdate, formatBytes, getDownloadPercentage } from './api/updater';
-import type { ClipboardEntry, DiscoveredPeer, PairedPeer, SecretStorageMethod } from './api/types';
+import type {
+  ClipboardEntry,
+  ClipboardSelection,
+  DiscoveredPeer,
+  PairedPeer,
+  PairingExpiry,
+  SecretStorageMethod,
+  SensitiveAction,
+  SensitiveRule,
+} from './api/types';
 // ?url suffix prevents race condition where Tauri webview loads before Vite is ready,
 // causing "image/svg+xml is not a valid JavaScript MIME type" error on first load
 import logoDark from './assets/logo_dark.svg?url';
@@ -18,6 +27,8 @@ class App {
   private modalRenderPending: boolean = false; // Debounce modal renders
   private autoLockTimer: ReturnType<typeof setTimeout> | null = null; // Auto-lock timer
   private pairingTimeout: ReturnType<typeof setTimeout> | null = null; // Pairing timeout
+  private nearbySends = new Map<string, string>(); // Outgoing nearby sends: transferId -> device name
+  private heldEntries = new Set<string>(); // Received entries kept in history only
 
   constructor(rootElement: HTMLElement) {
     this.root = rootElement;
@@ -157,7 +168,12 @@ class App {
         const history = store.get('clipboardHistory');
         const item = history.find((h) => h.id === id);
         if (item) {
-          await commands.setClipboard(item.content);
+          // Received items are applied without being synced back to other devices
+          if (item.is_local) {
+            await commands.setClipboard(item.content);
+          } else {
+            await commands.applyHistoryEntry(item.id);
+          }
 
           // Visual feedback: change to checkmark with green tint
           const button = copyEl as HTMLElement;
@@ -173,6 +189,26 @@ class App {
         return;
       }
 
+      // Nearby send buttons (one item to an unpaired device)
+      const nearbyEl = target.closest('[data-nearby-send]');
+      if (nearbyEl) {
+        const peerId = nearbyEl.getAttribute('data-nearby-send');
+        const latest = store.get('clipboardHistory')[0];
+        if (peerId && !latest) {
+          store.addToast('Copy something first to send it', 'info');
+        } else if (peerId && latest) {
+          const peer = store.get('discoveredPeers').find((p) => p.peer_id === peerId);
+          try {
+            const transferId = await commands.startNearbySend(peerId, latest.content);
+            this.nearbySends.set(transferId, peer?.device_name || 'the other device');
+            store.addToast('Waiting for the other device to accept...', 'info');
+          } catch (error) {
+            store.addToast(`Failed to send: ${getErrorMessage(error)}`, 'error');
+          }
+        }
+        return;
+      }
+
       // Pair buttons
       const pairEl = target.closest('[data-pair]');
       if (pairEl) {
@@ -219,6 +255,21 @@ class App {
         return;
       }
 
+      // Pull a paired device's latest clipboard entry
+      const pullEl = target.closest('[data-pull-from]');
+      if (pullEl) {
+        const peerId = pullEl.getAttribute('data-pull-from');
+        if (peerId) {
+          try {
+            await commands.pullClipboard(peerId);
+            store.addToast(`Asked ${this.peerName(peerId)} for its clipboard`, 'info');
+          } catch (error) {
+            store.addToast(`Failed to pull clipboard: ${getErrorMessage(error)}`, 'error');
+          }
+        }
+        return;
+      }
+
       // Unpair buttons
       const unpairEl = target.closest('[data-unpair]');
       if (unpairEl) {
@@ -235,6 +286,37 @@ class App {
         return;
       }
 
+      // Remove a user sensitive-content rule
+      const removeRuleEl = target.closest('[data-remove-sensitive-rule]');
+      if (removeRuleEl) {
+        const id = removeRuleEl.getAttribute('data-remove-sensitive-rule');
+        const rules = store.get('settings').sensitive_rules.filter((rule) => rule.id !== id);
+        await this.updateSensitiveRules(rules);
+        return;
+      }
+
+      // Add a user sensitive-content rule
+      if (target.closest('#btn-add-sensitive-rule')) {
+        const nameInput = $('#sensitive-rule-name') as HTMLInputElement | null;
+        const patternInput = $('#sensitive-rule-pattern') as HTMLInputElement | null;
+        const pattern = patternInput?.value.trim() ?? '';
+        if (!pattern) {
+          store.addToast('Enter a pattern', 'error');
+          return;
+        }
+        const rule: SensitiveRule = {
+          id: crypto.randomUUID(),
+          name: nameInput?.value.trim() || pattern,
+          detector: { kind: 'regex', pattern },
+          action: 'dont_sync',
+          enabled: true,
+        };
+        if (await this.updateSensitiveRules([...store.get('settings').sensitive_rules, rule])) {
+          store.addToast('Rule added', 'success');
+        }
+        return;
+      }
+
       // Dismiss toasts
       const dismissEl = target.closest('[data-dismiss-toast]');
       if (dismissEl) {
@@ -270,6 +352,43 @@ class App {
         return;
       }
 
+      // Send a history item to one device - show the device picker
+      const sendItemEl = target.closest('[data-send-item]');
+      if (sendItemEl) {
+        const id = sendItemEl.getAttribute('data-send-item');
+        const item = store.get('clipboardHistory').find((h) => h.id === id);
+        if (item) {
+          store.set('sendToPeerContent', item.content);
+          store.set('showSendToPeer', true);
+        }
+        return;
+      }
+
+      // Device picker - send to the chosen device
+      const sendToPeerEl = target.closest('[data-send-to-peer]');
+      if (sendToPeerEl) {
+        const peerId = sendToPeerEl.getAttribute('data-send-to-peer');
+        const peer = store.get('pairedPeers').find((p) => p.peer_id === peerId);
+        if (peerId) {
+          const ttlSelect = $('#send-ttl-select') as HTMLSelectElement | null;
+          const ttlSecs = ttlSelect ? parseInt(ttlSelect.value, 10) : 0;
+          try {
+            await commands.sendToPeer(peerId, store.get('sendToPeerContent') ?? undefined, ttlSecs || undefined);
+            store.addToast(`Sent to ${peer?.device_name ?? 'device'}`, 'success');
+          } catch (error) {
+            store.addToast(`Failed to send: ${getErrorMessage(error)}`, 'error');
+          }
+          store.set('showSendToPeer', false);
+        }
+        return;
+      }
+
+      // Device picker - cancel button
+      if (target.closest('#btn-cancel-send-to-peer')) {
+        store.set('showSendToPeer', false);
+        return;
+      }
+
       // Clear history buttons - show confirmation modal
       if (target.closest('#btn-clear-history') || target.closest('#btn-clear-all-history')) {
         const historyCount = store.get('clipboardHistory').length;
@@ -777,6 +896,152 @@ class App {
         return;
       }
 
+      // History sync toggle (direct: checked = history_sync_enabled)
+      if (target.id === 'history-sync-toggle') {
+        const checked = (target as HTMLInputElement).checked;
+        const settings = { ...store.get('settings'), history_sync_enabled: checked };
+        try {
+          await commands.updateSettings(settings);
+          store.set('settings', settings);
+        } catch (error) {
+          store.addToast(`Failed to update settings: ${getErrorMessage(error)}`, 'error');
+          (target as HTMLInputElement).checked = !checked;
+        }
+        return;
+      }
+
+      // Sensitive-content filter toggle (direct: checked = sensitive_filter_enabled)
+      if (target.id === 'sensitive-filter-toggle') {
+        const checked = (target as HTMLInputElement).checked;
+        const settings = { ...store.get('settings'), sensitive_filter_enabled: checked };
+        try {
+          await commands.updateSettings(settings);
+          store.set('settings', settings);
+        } catch (error) {
+          store.addToast(`Failed to update settings: ${getErrorMessage(error)}`, 'error');
+          (target as HTMLInputElement).checked = !checked;
+        }
+        return;
+      }
+
+      // Sensitive-content expiry select
+      if (target.id === 'sensitive-expire-select') {
+        const value = parseInt((target as HTMLSelectElement).value, 10);
+        const oldSettings = store.get('settings');
+        const settings = { ...oldSettings, sensitive_expire_secs: value };
+        try {
+          await commands.updateSettings(settings);
+          store.set('settings', settings);
+        } catch (error) {
+          store.addToast(`Failed to update settings: ${getErrorMessage(error)}`, 'error');
+          (target as HTMLSelectElement).value = String(oldSettings.sensitive_expire_secs);
+        }
+        return;
+      }
+
+      // Sensitive-content rule: action select and enabled checkbox
+      const ruleActionId = target.getAttribute('data-sensitive-action');
+      const ruleEnabledId = target.getAttribute('data-sensitive-enabled');
+      if (ruleActionId || ruleEnabledId) {
+        const rules = store.get('settings').sensitive_rules.map((rule) => {
+          if (rule.id === ruleActionId) {
+            return { ...rule, action: (target as HTMLSelectElement).value as SensitiveAction };
+          }
+          if (rule.id === ruleEnabledId) {
+            return { ...rule, enabled: (target as HTMLInputElement).checked };
+          }
+          return rule;
+        });
+        await this.updateSensitiveRules(rules);
+        return;
+      }
+
+      // PRIMARY selection sync toggle (direct: checked = primary_selection_enabled)
+      if (target.id === 'primary-selection-toggle') {
+        const checked = (target as HTMLInputElement).checked;
+        const settings = { ...store.get('settings'), primary_selection_enabled: checked };
+        try {
+          await commands.updateSettings(settings);
+          store.set('settings', settings);
+        } catch (error) {
+          store.addToast(`Failed to update settings: ${getErrorMessage(error)}`, 'error');
+          (target as HTMLInputElement).checked = !checked;
+        }
+        return;
+      }
+
+      // PRIMARY selection debounce select
+      if (target.id === 'primary-debounce-select') {
+        const value = parseInt((target as HTMLSelectElement).value, 10);
+        const oldSettings = store.get('settings');
+        const settings = { ...oldSettings, primary_debounce_ms: value };
+        try {
+          await commands.updateSettings(settings);
+          store.set('settings', settings);
+        } catch (error) {
+          store.addToast(`Failed to update settings: ${getErrorMessage(error)}`, 'error');
+          (target as HTMLSelectElement).value = String(oldSettings.primary_debounce_ms);
+        }
+        return;
+      }
+
+      // Receive target for items selected on other devices
+      if (target.id === 'primary-target-select') {
+        const value = (target as HTMLSelectElement).value as ClipboardSelection;
+        const oldSettings = store.get('settings');
+        const settings = { ...oldSettings, primary_receive_target: value };
+        try {
+          await commands.updateSettings(settings);
+          store.set('settings', settings);
+        } catch (error) {
+          store.addToast(`Failed to update settings: ${getErrorMessage(error)}`, 'error');
+          (target as HTMLSelectElement).value = oldSettings.primary_receive_target;
+        }
+        return;
+      }
+
+      // History-only receive toggle (direct: checked = receive_history_only)
+      if (target.id === 'history-only-toggle') {
+        const checked = (target as HTMLInputElement).checked;
+        const settings = { ...store.get('settings'), receive_history_only: checked };
+        try {
+          await commands.updateSettings(settings);
+          store.set('settings', settings);
+        } catch (error) {
+          store.addToast(`Failed to update settings: ${getErrorMessage(error)}`, 'error');
+          (target as HTMLInputElement).checked = !checked;
+        }
+        return;
+      }
+
+      // Relay toggle (direct: checked = relay_enabled)
+      if (target.id === 'relay-toggle') {
+        const checked = (target as HTMLInputElement).checked;
+        const settings = { ...store.get('settings'), relay_enabled: checked };
+        try {
+          await commands.updateSettings(settings);
+          store.set('settings', settings);
+        } catch (error) {
+          store.addToast(`Failed to update settings: ${getErrorMessage(error)}`, 'error');
+          (target as HTMLInputElement).checked = !checked;
+        }
+        return;
+      }
+
+      // Pull approval toggle (direct: checked = pull_requires_approval)
+      if (target.id === 'pull-approval-toggle') {
+        const checked = (target as HTMLInputElement).checked;
+        const settings = { ...store.get('settings'), pull_requires_approval: checked };
+        try {
+          await commands.updateSettings(settings);
+          store.set('settings', settings);
+        } catch (error) {
+          store.addToast(`Failed to update settings: ${getErrorMessage(error)}`, 'error');
+          (target as HTMLInputElement).checked = !checked;
+        }
+        return;
+      }
+
       // Autostart toggle (desktop only - launch at login)
       if (target.id === 'autostart-toggle') {
         const checked = (target as HTMLInputElement).checked;
@@ -832,13 +1097,26 @@ class App {
 
     eventManager.on('clipboardReceived', (entry) => {
       store.addClipboardEntry(entry);
-      store.addToast(`Clipboard received from ${entry.origin_device_name}`, 'success');
+      // Held items already got their own toast (clipboard-held is emitted first)
+      if (!this.heldEntries.delete(entry.id)) {
+        store.addToast(`Clipboard received from ${entry.origin_device_name}`, 'success');
+      }
     });
 
     eventManager.on('clipboardSent', (entry) => {
       store.addClipboardEntry(entry);
     });
 
+    // Tray item / global shortcut: pick a device for the current clipboard
+    eventManager.on('sendToPeerRequested', () => {
+      store.set('sendToPeerContent', null);
+      store.set('showSendToPeer', true);
+    });
+
+    eventManager.on('deliveryStatus', (payload) => {
+      store.updateDeliveryStatus(payload.entryId, payload.peerId, payload.status);
+    });
+
     eventManager.on('pairingRequest', (payload) => {
       store.set('showPairingModal', true);
       store.set('pairingModalMode', 'respond');
@@ -886,6 +1164,148 @@ class App {
       store.set('activePairingSession', null);
     });
 
+    eventManager.on('peerKeyChanged', (payload) => {
+      store.addToast(`Warning: ${payload.deviceName} presented a different identity key`, 'error', 8000);
+      this.loadPairedPeers();
+    });
+
+    eventManager.on('peerIdentityRotated', (payload) => {
+      store.addToast(`${payload.deviceName} updated its identity keys`, 'info');
+      this.loadPairedPeers();
+    });
+
+    eventManager.on('pairingExpired', (payload) => {
+      store.addToast(`Temporary pairing with ${payload.deviceName} ended`, 'info');
+      this.loadPairedPeers();
+    });
+
+    eventManager.on('nearbySendRequest', async (payload) => {
+      const accept = window.confirm(`${payload.deviceName} wants to send you one clipboard item. Accept?`);
+      try {
+        const code = await commands.respondToNearbySend(payload.transferId, accept);
+        if (code) {
+          store.addToast(
+            `Confirmation code: ${code}. Check that ${payload.deviceName} shows the same code.`,
+            'info',
+            15000,
+          );
+        }
+      } catch (error) {
+        store.addToast(`Nearby send failed: ${getErrorMessage(error)}`, 'error');
+      }
+    });
+
+    eventManager.on('nearbySendCode', async (payload) => {
+      // The receiver already saw its code when accepting
+      const deviceName = this.nearbySends.get(payload.transferId);
+      if (!deviceName) return;
+
+      const matches = window.confirm(`Does ${deviceName} show the code ${payload.code}?`);
+      try {
+        if (matches) {
+          await commands.confirmNearbySend(payload.transferId);
+        } else {
+          this.nearbySends.delete(payload.transferId);
+          await commands.cancelNearbySend(payload.transferId);
+          store.addToast('Nearby send cancelled: codes did not match', 'error');
+        }
+      } catch (error) {
+        store.addToast(`Nearby send failed: ${getErrorMessage(error)}`, 'error');
+      }
+    });
+
+    eventManager.on('nearbySendComplete', (payload) => {
+      const deviceName = this.nearbySends.get(payload.transferId);
+      this.nearbySends.delete(payload.transferId);
+      store.addToast(`Sent to ${deviceName || 'nearby device'}`, 'success');
+    });
+
+    eventManager.on('nearbySendFailed', (payload) => {
+      this.nearbySends.delete(payload.transferId);
+      store.addToast(`Nearby send failed: ${payload.error}`, 'error');
+    });
+
+    eventManager.on('clipboardHeld', (payload) => {
+      this.heldEntries.add(payload.entryId);
+      store.addToast(`Item from ${payload.deviceName} saved to history`, 'info');
+    });
+
+    eventManager.on('historyEntryApplied', (entry) => {
+      store.addToast(`Copied item from ${entry.origin_device_name}`, 'success');
+    });
+
+    eventManager.on('clipboardRestored', () => {
+      store.addToast('Restored previous clipboard content', 'success');
+    });
+
+    eventManager.on('sensitiveContentDetected', (payload) => {
+      const outcome = {
+        dont_sync: 'not synced',
+        no_history: 'synced without saving to history',
+        auto_expire: `will be removed from history in ${formatSeconds(store.get('settings').sensitive_expire_secs)}`,
+      }[payload.action];
+      store.addToast(`${payload.ruleName} detected: ${outcome}`, 'info');
+    });
+
+    eventManager.on('clipboardSelfDestruct', (payload) => {
+      store.addToast(`Copied item from ${payload.deviceName}, clears in ${formatSeconds(payload.ttlSecs)}`, 'info');
+    });
+
+    eventManager.on('clipboardSelfDestructed', (payload) => {
+      store.addToast(`Cleared item from ${payload.deviceName}`, 'info');
+    });
+
+    eventManager.on('clipboardSelfDestructRefused', (payload) => {
+      store.addToast(`Skipped self-destructing item from ${payload.deviceName} (receiving into history only)`, 'info');
+    });
+
+    eventManager.on('clipboardEntriesExpired', (payload) => {
+      store.removeClipboardEntries(payload.entryIds);
+    });
+
+    eventManager.on('clipboardPullRequest', async (payload) => {
+      const accept = window.confirm(`${payload.deviceName} wants to fetch your latest clipboard item. Allow?`);
+      try {
+        await commands.respondToClipboardPull(payload.requestId, accept);
+      } catch (error) {
+        store.addToast(`Clipboard pull failed: ${getErrorMessage(error)}`, 'error');
+      }
+    });
+
+    eventManager.on('groupInvite', async (payload) => {
+      const accept = window.confirm(
+        `${payload.deviceName} invited this device to the group "${payload.name}" (${payload.memberCount} devices). ` +
+          'Joining pairs it with every member. Join?',
+      );
+      try {
+        const group = await commands.respondToGroupInvite(payload.groupId, accept);
+        if (group) {
+          store.addToast(`Joined group "${group.name}"`, 'success');
+          await this.loadPairedPeers();
+        }
+      } catch (error) {
+        store.addToast(`Group invite failed: ${getErrorMessage(error)}`, 'error');
+      }
+    });
+
+    eventManager.on('clipboardPullFailed', (payload) => {
+      store.addToast(`Could not pull from ${this.peerName(payload.peerId)}: ${payload.error}`, 'error');
+    });
+
+    // History reconciliation merged entries or deletions - reload the whole list
+    eventManager.on('historySynced', async () => {
+      try {
+        store.set('clipboardHistory', await commands.getClipboardHistory());
+      } catch (error) {
+        console.error('Failed to reload clipboard history:', error);
+      }
+    });
+
+    eventManager.on('peerUnpaired', (payload) => {
+      store.addToast(`${payload.deviceName} removed this device`, 'info');
+      this.loadPairedPeers();
+    });
+
     eventManager.on('networkError', (error) => {
       store.addToast(`Network error: ${error}`, 'error');
     });
@@ -1025,6 +1445,7 @@ class App {
     store.subscribe('pairingModalMode', () => this.renderPairingModal());
     store.subscribe('activePairingSession', () => this.renderPairingModal());
     store.subscribe('showClearHistoryConfirm', () => this.updateClearHistoryModal());
+    store.subscribe('showSendToPeer', () => this.updateSendToPeerModal());
     store.subscribe('isLoading', () => this.render());
     store.subscribe('vaultStatus', () => this.render());
     store.subscribe('onboardingStep', () => this.render());
@@ -1223,6 +1644,11 @@ class App {
         <div id="clear-history-modal" class="${state.showClearHistoryConfirm ? '' : 'hidden'}">
           ${this.renderClearHistoryConfirmModal()}
         </div>
+
+        <!-- Send To Device Modal -->
+        <div id="send-to-peer-modal" class="${state.showSendToPeer ? '' : 'hidden'}">
+          ${this.renderSendToPeerModal()}
+        </div>
       </div>
     `;
   }
@@ -1437,6 +1863,45 @@ class App {
                 class="checkbox"
               />
             </label>
+            <div class="divider"></div>
+            <label class="flex items-center justify-between p-4 cursor-pointer hover:bg-white/[0.02] transition-colors">
+              <div>
+                <span class="text-sm text-white/70 block">Receive into history only</span>
+                <span class="text-xs text-white/40">Don't replace your clipboard with incoming items</span>
+              </div>
+              <input
+                type="checkbox"
+                id="history-only-toggle"
+                ${settings.receive_history_only ? 'checked' : ''}
+                class="checkbox"
+              />
+            </label>
+            <div class="divider"></div>
+            <label class="flex items-center justify-between p-4 cursor-pointer hover:bg-white/[0.02] transition-colors">
+              <div>
+                <span class="text-sm text-white/70 block">Sync full history</span>
+                <span class="text-xs text-white/40">Match history and deletions with paired devices</span>
+              </div>
+              <input
+                type="checkbox"
+                id="history-sync-toggle"
+                ${settings.history_sync_enabled ? 'checked' : ''}
+                class="checkbox"
+              />
+            </label>
+            <div class="divider"></div>
+            <label class="flex items-center justify-between p-4 cursor-pointer hover:bg-white/[0.02] transition-colors">
+              <div>
+                <span class="text-sm text-white/70 block">Relay for paired devices</span>
+                <span class="text-xs text-white/40">Pass on encrypted items between devices that are rarely online together</span>
+              </div>
+              <input
+                type="checkbox"
+                id="relay-toggle"
+                ${settings.relay_enabled ? 'checked' : ''}
+                class="checkbox"
+              />
+            </label>
           </div>
         </div>
 
@@ -1463,9 +1928,123 @@ class App {
                 <option value="60" ${settings.auto_lock_minutes === 60 ? 'selected' : ''}>1 hour</option>
               </select>
             </div>
+            <div class="divider"></div>
+            <label class="flex items-center justify-between p-4 cursor-pointer hover:bg-white/[0.02] transition-colors">
+              <div>
+                <span class="text-sm text-white/70 block">Approve clipboard pulls</span>
+                <span class="text-xs text-white/40">Ask before a paired device fetches your clipboard</span>
+              </div>
+              <input
+                type="checkbox"
+                id="pull-approval-toggle"
+                ${settings.pull_requires_approval ? 'checked' : ''}
+                class="checkbox"
+              />
+            </label>
+          </div>
+        </div>
+
+        <!-- Sensitive Content -->
+        <div class="mb-6">
+          <div class="flex items-center gap-2 mb-3">
+            <div class="icon-container-orange" style="width: 1.5rem; height: 1.5rem; border-radius: 0.5rem;">
+              ${icon('shield', 12)}
+            </div>
+            <h2 class="text-sm font-semibold text-white/80 tracking-tight font-display">Sensitive Content</h2>
+          </div>
+          <div class="card overflow-hidden">
+            <label class="flex items-center justify-between p-4 cursor-pointer hover:bg-white/[0.02] transition-colors">
+              <div>
+                <span class="text-sm text-white/70 block">Filter sensitive copies</span>
+                <span class="text-xs text-white/40">Check passwords, keys and tokens before syncing</span>
+              </div>
+              <input
+                type="checkbox"
+                id="sensitive-filter-toggle"
+                ${settings.sensitive_filter_enabled ? 'checked' : ''}
+                class="checkbox"
+              />
+            </label>
+            <div class="divider"></div>
+            <div class="flex items-center justify-between p-4">
+              <span class="text-sm text-white/70">Expire from history after</span>
+              <select id="sensitive-expire-select" class="select">
+                ${[30, 60, 300, 900]
+                  .map(
+                    (secs) =>
+                      `<option value="${secs}" ${settings.sensitive_expire_secs === secs ? 'selected' : ''}>${formatSeconds(secs)}</option>`,
+                  )
+                  .join('')}
+              </select>
+            </div>
+            <div id="sensitive-rules">
+              ${settings.sensitive_rules.map((rule) => this.renderSensitiveRule(rule)).join('')}
+            </div>
+            <div class="divider"></div>
+            <div class="p-4 space-y-2">
+              <input id="sensitive-rule-name" type="text" placeholder="Rule name" class="input w-full text-sm" />
+              <div class="flex items-center gap-2">
+                <input id="sensitive-rule-pattern" type="text" placeholder="Regular expression" class="input flex-1 min-w-0 text-sm font-mono" />
+                <button id="btn-add-sensitive-rule" class="btn-secondary text-sm">Add</button>
+              </div>
+            </div>
           </div>
         </div>
 
+        ${
+          isLinux()
+            ? `<!-- Selection (Linux only) -->
+        <div class="mb-6">
+          <div class="flex items-center gap-2 mb-3">
+            <div class="icon-container-teal" style="width: 1.5rem; height: 1.5rem; border-radius: 0.5rem;">
+              ${icon('clipboard', 12)}
+            </div>
+            <h2 class="text-sm font-semibold text-white/80 tracking-tight font-display">Selection</h2>
+          </div>
+          <div class="card overflow-hidden">
+            <label class="flex items-center justify-between p-4 cursor-pointer hover:bg-white/[0.02] transition-colors">
+              <div>
+                <span class="text-sm text-white/70 block">Sync selected text</span>
+                <span class="text-xs text-white/40">Send the PRIMARY selection (middle-click paste) too</span>
+              </div>
+              <input
+                type="checkbox"
+                id="primary-selection-toggle"
+                ${settings.primary_selection_enabled ? 'checked' : ''}
+                class="checkbox"
+              />
+            </label>
+            <div class="divider"></div>
+            <div class="flex items-center justify-between p-4">
+              <div>
+                <span class="text-sm text-white/70 block">Send after</span>
+                <span class="text-xs text-white/40">The selection must stop changing first</span>
+              </div>
+              <select id="primary-debounce-select" class="select">
+                ${[250, 500, 750, 1000, 2000]
+                  .map(
+                    (ms) =>
+                      `<option value="${ms}" ${settings.primary_debounce_ms === ms ? 'selected' : ''}>${ms} ms</option>`,
+                  )
+                  .join('')}
+              </select>
+            </div>
+            <div class="divider"></div>
+            <div class="flex items-center justify-between p-4">
+              <div>
+                <span class="text-sm text-white/70 block">Selections from other devices</span>
+                <span class="text-xs text-white/40">Where text selected elsewhere goes</span>
+              </div>
+              <select id="primary-target-select" class="select">
+                <option value="clipboard" ${settings.primary_receive_target === 'clipboard' ? 'selected' : ''}>Clipboard</option>
+                <option value="primary" ${settings.primary_receive_target === 'primary' ? 'selected' : ''}>Selection</option>
+              </select>
+            </div>
+          </div>
+        </div>`
+            : ''
+        }
+
         ${
           isDesktop()
             ? `<!-- System (desktop only) -->
@@ -1986,6 +2565,45 @@ class App {
     `;
   }
 
+  private renderSendToPeerModal(): string {
+    const peers = store.get('pairedPeers').filter((p) => p.sync_policy.send);
+    const content = store.get('sendToPeerContent');
+    return `
+      <div class="fixed inset-0 modal-overlay flex items-center justify-center z-50 p-4">
+        <div class="modal-content p-5 max-w-xs w-full">
+          <h2 class="text-lg font-semibold text-white mb-1 font-display text-center">Send To…</h2>
+          <p class="text-white/50 text-sm mb-4 text-center">
+            ${content === null ? 'Send the current clipboard to one device' : 'Send this item to one device'}
+          </p>
+          <div class="space-y-2 mb-4">
+            ${
+              peers.length > 0
+                ? peers
+                    .map(
+                      (peer) => `
+                <button data-send-to-peer="${peer.peer_id}" class="btn-secondary w-full py-2.5 flex items-center justify-center gap-2">
+                  ${icon('send', 14)}
+                  ${escapeHtml(peer.device_name)}
+                </button>
+              `,
+                    )
+                    .join('')
+                : '<p class="text-xs text-white/40 text-center">No paired devices to send to</p>'
+            }
+          </div>
+          <div class="flex items-center justify-between mb-4">
+            <span class="text-sm text-white/70">Self-destruct</span>
+            <select id="send-ttl-select" class="select">
+              <option value="0">Off</option>
+              ${[30, 60, 300].map((secs) => `<option value="${secs}">After ${formatSeconds(secs)}</option>`).join('')}
+            </select>
+          </div>
+          <button id="btn-cancel-send-to-peer" class="btn-secondary w-full py-2.5">Cancel</button>
+        </div>
+      </div>
+    `;
+  }
+
   private renderClipboardItem(item: ClipboardEntry, hideContent = false): string {
     const isLocal = item.is_local;
     // Escape HTML to prevent XSS attacks from malicious clipboard content
@@ -2001,9 +2619,18 @@ class App {
                 ${isLocal ? icon('monitor', 10) : icon('download', 10)}
                 ${isLocal ? 'Local' : escapeHtml(item.origin_device_name)}
               </span>
+              ${item.target_peer_id ? `<span class="text-xs text-white/40">→ ${escapeHtml(this.peerName(item.target_peer_id))}</span>` : ''}
               <span class="text-xs text-white/30 font-mono">${formatTime(item.timestamp)}</span>
+              ${this.renderDeliveryStatus(item)}
             </div>
           </div>
+          <button
+            data-send-item="${item.id}"
+            class="p-2 rounded-lg text-white/30 hover:text-teal-400 hover:bg-teal-500/10 transition-all flex-shrink-0"
+            title="Send to one device"
+          >
+            ${icon('send', 16)}
+          </button>
           <button
             data-copy="${item.id}"
             class="copy-btn p-2 rounded-lg text-white/30 hover:text-teal-400 hover:bg-teal-500/10 transition-all flex-shrink-0"
@@ -2016,8 +2643,24 @@ class App {
     `;
   }
 
+  /** "Delivered 2/3" for entries we sent; failed deliveries are shown in red. */
+  private renderDeliveryStatus(item: ClipboardEntry): string {
+    const statuses = Object.values(item.delivery);
+    if (statuses.length === 0) return '';
+
+    const delivered = statuses.filter((s) => s === 'delivered' || s === 'applied').length;
+    const failed = statuses.filter((s) => s === 'failed').length;
+    const title = `${statuses.filter((s) => s === 'applied').length} applied, ${failed} failed`;
+    return `
+      <span class="text-xs ${failed > 0 ? 'text-red-400/70' : 'text-white/30'}" title="${title}">
+        Delivered ${delivered}/${statuses.length}
+      </span>
+    `;
+  }
+
   private renderPairedPeer(peer: PairedPeer): string {
     const safeName = escapeHtml(peer.device_name);
+    const subtitle = peer.expiry ? this.describeExpiry(peer.expiry) : 'Paired device';
 
     return `
       <div class="card p-3 flex items-center justify-between">
@@ -2027,20 +2670,45 @@ class App {
           </div>
           <div>
             <p class="text-sm font-medium text-white">${safeName}</p>
-            <p class="text-xs text-white/40">Paired device</p>
+            <p class="text-xs ${peer.expiry ? 'text-amber-400/70' : 'text-white/40'}">${subtitle}</p>
           </div>
         </div>
-        <button
-          data-unpair="${peer.peer_id}"
-          class="p-2 rounded-lg text-white/30 hover:text-red-400 hover:bg-red-500/10 transition-all"
-          title="Unpair device"
-        >
-          ${icon('unlink', 16)}
-        </button>
+        <div class="flex items-center gap-1">
+          ${
+            peer.sync_policy.receive
+              ? `<button
+            data-pull-from="${peer.peer_id}"
+            class="p-2 rounded-lg text-white/30 hover:text-teal-400 hover:bg-teal-500/10 transition-all"
+            title="Pull clipboard from this device"
+          >
+            ${icon('download', 16)}
+          </button>`
+              : ''
+          }
+          <button
+            data-unpair="${peer.peer_id}"
+            class="p-2 rounded-lg text-white/30 hover:text-red-400 hover:bg-red-500/10 transition-all"
+            title="Unpair device"
+          >
+            ${icon('unlink', 16)}
+          </button>
+        </div>
       </div>
     `;
   }
 
+  private describeExpiry(expiry: PairingExpiry): string {
+    const limits: string[] = [];
+    if (expiry.expires_at) {
+      const time = new Date(expiry.expires_at).toLocaleTimeString([], { hour: '2-digit', minute: '2-digit' });
+      limits.push(`until ${time}`);
+    }
+    if (expiry.messages_remaining !== null) {
+      limits.push(`${expiry.messages_remaining} messages left`);
+    }
+    return `Temporary pairing · ${limits.join(', ')}`;
+  }
+
   private renderDiscoveredPeer(peer: DiscoveredPeer): string {
     const safeName = peer.device_name ? escapeHtml(peer.device_name) : 'Unknown Device';
     return `
@@ -2054,12 +2722,21 @@ class App {
             <p class="text-xs text-white/40">Discovered ${formatTime(peer.discovered_at)}</p>
           </div>
         </div>
-        <button
-          data-pair="${peer.peer_id}"
-          class="btn-primary text-xs px-4 py-1.5"
-        >
-          Pair
-        </button>
+        <div class="flex items-center gap-2">
+          <button
+            data-nearby-send="${peer.peer_id}"
+            class="btn-secondary text-xs px-3 py-1.5"
+            title="Send the latest clipboard item once, without pairing"
+          >
+            Send
+          </button>
+          <button
+            data-pair="${peer.peer_id}"
+            class="btn-primary text-xs px-4 py-1.5"
+          >
+            Pair
+          </button>
+        </div>
       </div>
     `;
   }
@@ -2236,6 +2913,73 @@ class App {
     });
   }
 
+  private updateSendToPeerModal(): void {
+    const modal = $('#send-to-peer-modal');
+    if (modal) {
+      modal.className = store.get('showSendToPeer') ? '' : 'hidden';
+      modal.innerHTML = this.renderSendToPeerModal();
+    }
+  }
+
+  private renderSensitiveRule(rule: SensitiveRule): string {
+    const actions: [SensitiveAction, string][] = [
+      ['dont_sync', "Don't sync"],
+      ['no_history', 'No history'],
+      ['auto_expire', 'Auto-expire'],
+    ];
+    // Only user rules have a pattern, and only they can be removed
+    const pattern = rule.detector.kind === 'regex' ? rule.detector.pattern : null;
+    return `
+      <div class="divider"></div>
+      <div class="flex items-center gap-3 p-4">
+        <input type="checkbox" class="checkbox" data-sensitive-enabled="${rule.id}" ${rule.enabled ? 'checked' : ''} title="Enabled" />
+        <div class="flex-1 min-w-0">
+          <span class="text-sm text-white/70 block truncate">${escapeHtml(rule.name)}</span>
+          ${pattern !== null ? `<span class="text-xs text-white/40 font-mono block truncate">${escapeHtml(pattern)}</span>` : ''}
+        </div>
+        <select class="select" data-sensitive-action="${rule.id}">
+          ${actions.map(([value, label]) => `<option value="${value}" ${rule.action === value ? 'selected' : ''}>${label}</option>`).join('')}
+        </select>
+        ${
+          pattern !== null
+            ? `<button data-remove-sensitive-rule="${rule.id}" class="p-1.5 rounded-lg text-white/40 hover:text-red-400 hover:bg-red-500/10 transition-all" title="Remove rule">
+                ${icon('trash', 14)}
+              </button>`
+            : ''
+        }
+      </div>
+    `;
+  }
+
+  /** Save new sensitive-content rules and redraw the rule list. Returns whether they were saved. */
+  private async updateSensitiveRules(rules: SensitiveRule[]): Promise<boolean> {
+    const settings = { ...store.get('settings'), sensitive_rules: rules };
+    try {
+      await commands.updateSettings(settings);
+      store.set('settings', settings);
+      const name = $('#sensitive-rule-name') as HTMLInputElement | null;
+      const pattern = $('#sensitive-rule-pattern') as HTMLInputElement | null;
+      if (name) name.value = '';
+      if (pattern) pattern.value = '';
+      return true;
+    } catch (error) {
+      store.addToast(`Failed to update settings: ${getErrorMessage(error)}`, 'error');
+      return false;
+    } finally {
+      const list = $('#sensitive-rules');
+      if (list) {
+        list.innerHTML = store
+          .get('settings')
+          .sensitive_rules.map((rule) => this.renderSensitiveRule(rule))
+          .join('');
+      }
+    }
+  }
+
+  private peerName(peerId: string): string {
+    return store.get('pairedPeers').find((p) => p.peer_id === peerId)?.device_name ?? 'Unknown device';
+  }
+
   private updateClearHistoryModal(): void {
     const modal = $('#clear-history-modal');
     if (modal) {
@@ -2,6 +2,7 @@ import type {
   AppSettings,
   AuthMethod,
   ClipboardEntry,
+  DeliveryStatus,
   DeviceInfo,
   DiscoveredPeer,
   NetworkStatus,
@@ -14,6 +15,22 @@ import type {
   VaultStatus,
 } from '../api/types';
 
+/**
+ * Order of two history entries (negative if `a` is older): HLC first, origin device to break
+ * ties. Entries from before HLCs fall back to their timestamp, like `ClipboardEntry::order_key`.
+ */
+function compareClipboardOrder(a: ClipboardEntry, b: ClipboardEntry): number {
+  const key = (e: ClipboardEntry) =>
+    e.hlc && (e.hlc.wall_ms !== 0 || e.hlc.counter !== 0)
+      ? e.hlc
+      : { wall_ms: new Date(e.timestamp).getTime(), counter: 0 };
+  const ka = key(a);
+  const kb = key(b);
+  if (ka.wall_ms !== kb.wall_ms) return ka.wall_ms - kb.wall_ms;
+  if (ka.counter !== kb.counter) return ka.counter - kb.counter;
+  return a.origin_device_id < b.origin_device_id ? -1 : a.origin_device_id > b.origin_device_id ? 1 : 0;
+}
+
 export type View = 'dashboard' | 'peers' | 'settings';
 export type OnboardingStep = 'device-name' | 'auth-choice' | 'pin-setup' | null;
 
@@ -76,6 +93,10 @@ export interface AppState {
   // Clear history confirmation state
   showClearHistoryConfirm: boolean;
 
+  // Device picker for a targeted send (null content = current clipboard)
+  showSendToPeer: boolean;
+  sendToPeerContent: string | null;
+
   // App version (fetched from Tauri)
   appVersion: string;
 
@@ -111,6 +132,19 @@ class Store {
         auth_method: null,
         hide_clipboard_content: false,
         auto_lock_minutes: 15,
+        pairing_timeout_secs: 300,
+        sync_buffer_size: 20,
+        sync_buffer_ttl_secs: 86400,
+        history_sync_enabled: false,
+        pull_requires_approval: false,
+        relay_enabled: false,
+        receive_history_only: false,
+        sensitive_filter_enabled: true,
+        sensitive_rules: [],
+        sensitive_expire_secs: 60,
+        primary_selection_enabled: false,
+        primary_debounce_ms: 750,
+        primary_receive_target: 'clipboard',
       },
       deviceInfo: null,
       isLoading: true,
@@ -131,6 +165,8 @@ class Store {
       showResetConfirmation: false,
       // Clear history confirmation state
       showClearHistoryConfirm: false,
+      showSendToPeer: false,
+      sendToPeerContent: null,
       // App version (fetched from Tauri on init)
       appVersion: '',
       // Share intent state (Android)
@@ -219,11 +255,10 @@ class Store {
       // This allows "re-sharing" same content to be reinserted at correct position
       const filtered = history.filter((e) => e.content_hash !== entry.content_hash);
 
-      // Insert at correct chronological position by timestamp (newest first).
-      // This is important for sync: synced messages may have older timestamps
+      // Insert at correct position by HLC (newest first), matching the backend.
+      // This is important for sync: synced messages may be older
       // and should appear in the correct position in history.
-      const entryTime = new Date(entry.timestamp).getTime();
-      const insertIndex = filtered.findIndex((e) => new Date(e.timestamp).getTime() < entryTime);
+      const insertIndex = filtered.findIndex((e) => compareClipboardOrder(e, entry) < 0);
       const position = insertIndex === -1 ? filtered.length : insertIndex;
 
       const result = [...filtered.slice(0, position), entry, ...filtered.slice(position)];
@@ -234,6 +269,16 @@ class Store {
     });
   }
 
+  removeClipboardEntries(ids: string[]): void {
+    this.update('clipboardHistory', (history) => history.filter((e) => !ids.includes(e.id)));
+  }
+
+  updateDeliveryStatus(entryId: string, peerId: string, status: DeliveryStatus): void {
+    this.update('clipboardHistory', (history) =>
+      history.map((e) => (e.id === entryId ? { ...e, delivery: { ...e.delivery, [peerId]: status } } : e)),
+    );
+  }
+
   addDiscoveredPeer(peer: DiscoveredPeer): void {
     this.update('discoveredPeers', (peers) => {
       const existing = peers.findIndex((p) => p.peer_id === peer.peer_id);
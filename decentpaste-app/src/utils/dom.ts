@@ -49,6 +49,15 @@ export function formatTime(isoString: string): string {
   }
 }
 
+/**
+ * Formats a duration in seconds as "30 seconds", "1 minute", "15 minutes".
+ */
+export function formatSeconds(secs: number): string {
+  if (secs < 60) return `${secs} second${secs === 1 ? '' : 's'}`;
+  const mins = Math.round(secs / 60);
+  return `${mins} minute${mins === 1 ? '' : 's'}`;
+}
+
 /**
  * Truncates a string to a maximum length, adding ellipsis if needed.
  */
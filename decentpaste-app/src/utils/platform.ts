@@ -53,6 +53,13 @@ export function isMac(): boolean {
   return cachedPlatform === 'macos';
 }
 
+/**
+ * Returns true if running on Linux (X11 or Wayland).
+ */
+export function isLinux(): boolean {
+  return cachedPlatform === 'linux';
+}
+
 /**
  * Returns true if running on a desktop platform (not mobile).
  */